@@ -0,0 +1,79 @@
+// Inputs shared by the disk and time benchmarks, so both measure the same messages.
+
+use libtaos::Field;
+use taosx_data_format_bench::{
+    avro::avro_datum_serialize,
+    generate_data,
+    line_protocol::line_protocol_serialize,
+    opentsdb::{opentsdb_json_serialize, opentsdb_telnet_serialize},
+    protobuf::protobuf_row_serialize,
+    sql::sql_serialize,
+    transform::{Pipeline, Transform},
+    SuperTable,
+};
+
+pub const ZSTD_TRAINING_BATCHES: usize = 500;
+pub const ZSTD_MESSAGES: usize = 100;
+pub const ZSTD_DICTIONARY_SIZE: usize = 16 * 1024;
+pub const ZSTD_LEVEL: i32 = 3;
+
+pub const ROW_FORMATS: [&str; 6] = [
+    "Avro",
+    "Protobuf-Row",
+    "LineProtocol",
+    "OpenTSDB-Telnet",
+    "OpenTSDB-JSON",
+    "SQL",
+];
+
+// One batch of `rows` as a message in each of the row oriented formats.
+pub fn row_message(
+    stable: &SuperTable,
+    format: &str,
+    tags: &[Field],
+    rows: &[Vec<Field>],
+) -> Vec<u8> {
+    match format {
+        "Avro" => avro_datum_serialize(stable.data_types, rows),
+        "Protobuf-Row" => protobuf_row_serialize(rows),
+        "LineProtocol" => line_protocol_serialize(stable, tags, rows).into_bytes(),
+        "OpenTSDB-Telnet" => opentsdb_telnet_serialize(stable, tags, rows).into_bytes(),
+        "OpenTSDB-JSON" => opentsdb_json_serialize(stable, tags, rows).into_bytes(),
+        "SQL" => sql_serialize(stable, "d0", tags, rows, rows.len()).into_bytes(),
+        _ => unreachable!(),
+    }
+}
+
+// Separate batches to train the dictionary on and to measure it with.
+pub fn dictionary_messages(
+    stable: &SuperTable,
+    format: &str,
+    tags: &[Field],
+    batch_size: u32,
+) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    let mut batches = (0..ZSTD_TRAINING_BATCHES + ZSTD_MESSAGES).map(|_| {
+        let rows = generate_data(stable.data_types, batch_size).0;
+        row_message(stable, format, tags, &rows)
+    });
+    let training = batches.by_ref().take(ZSTD_TRAINING_BATCHES).collect();
+    (training, batches.collect())
+}
+
+pub fn transform_pipelines() -> Vec<Pipeline> {
+    let compression = parquet::basic::Compression::LZ4;
+    let mut pipelines = vec![Pipeline::new(vec![], compression)];
+    for transform in [
+        Transform::Delta,
+        Transform::DeltaOfDelta,
+        Transform::Xor,
+        Transform::ByteShuffle,
+        Transform::BitShuffle,
+    ] {
+        pipelines.push(Pipeline::new(vec![transform], compression));
+    }
+    pipelines.push(Pipeline::new(
+        vec![Transform::Delta, Transform::ByteShuffle],
+        compression,
+    ));
+    pipelines
+}
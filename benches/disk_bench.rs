@@ -1,15 +1,44 @@
+mod common;
+
 use avro_rs::Codec;
+use common::{
+    dictionary_messages, transform_pipelines, ROW_FORMATS, ZSTD_DICTIONARY_SIZE, ZSTD_LEVEL,
+    ZSTD_MESSAGES,
+};
 use criterion::{
     criterion_group, criterion_main,
     measurement::{Measurement, ValueFormatter},
     BenchmarkId, Criterion, Throughput,
 };
 use filesize::PathExt;
+use libtaos::TimestampPrecision;
 use std::path::Path;
 use taosx_data_format_bench::{
-    avro::{avro_read, avro_serialized_write},
-    generate_data,
+    avro::{avro_read, avro_serialized_write, generate_avro_schema},
+    avro_message::{avro_single_object_read, avro_single_object_serialized_write, SchemaStore},
+    float_codec::{float_column_read, float_column_serialized_write, FloatCodec},
+    general_codec::{
+        fixed_width_buffer, general_codec_read, general_codec_serialized_write, general_codecs,
+    },
+    generate_data, generate_tags,
+    int_codec::{int_column_read, int_column_serialized_write, IntCodec},
+    line_protocol::{line_protocol_read, line_protocol_serialized_write},
+    lossy::{
+        lossy_column, max_error, sz_column_deserialize, sz_column_read, sz_column_serialize,
+        sz_column_serialized_write, ErrorBound,
+    },
     my_parquet::{parquet_read, parquet_serialized_write},
+    opentsdb::{
+        opentsdb_json_read, opentsdb_json_serialized_write, opentsdb_telnet_read,
+        opentsdb_telnet_serialized_write,
+    },
+    raw_block::{raw_block_read, raw_block_serialized_write},
+    sql::{sql_read, sql_serialized_write},
+    string_codec::{string_column_read, string_column_serialized_write, StringCodec},
+    transform::{transform_column_read, transform_column_serialized_write},
+    tscol::{tscol_read, tscol_serialized_write},
+    zstd_dict::{zstd_messages_read, zstd_messages_serialized_write, ZstdDictionary},
+    SuperTable,
 };
 
 extern crate lazy_static;
@@ -30,6 +59,13 @@ lazy_static::lazy_static! {
         "binary",
         "nchar",
     ];
+    pub static ref TAGTYPES: Vec<&'static str> = vec!["int", "binary"];
+    pub static ref STABLE: SuperTable<'static> = SuperTable {
+        name: "meters",
+        data_types: &DATATYPES,
+        tag_types: &TAGTYPES,
+        precision: TimestampPrecision::Milli,
+    };
 }
 
 pub const DATASIZE: u32 = 10000;
@@ -119,13 +155,86 @@ impl ValueFormatter for CompressabilityFormatter {
     }
 }
 
+// Not a size but the maximum error of a lossy column, next to the sizes of the same ids
+// in the "Persistence" group. Benches report it through `iter_custom`, and as it does
+// not vary it has no plots, which need some spread.
+pub struct MaxError;
+impl Measurement for MaxError {
+    type Intermediate = ();
+
+    type Value = f64;
+
+    fn start(&self) -> Self::Intermediate {}
+
+    fn end(&self, _: Self::Intermediate) -> Self::Value {
+        0.0
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0.0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value
+    }
+
+    fn formatter(&self) -> &dyn criterion::measurement::ValueFormatter {
+        &MaxErrorFormatter
+    }
+}
+
+struct MaxErrorFormatter;
+impl ValueFormatter for MaxErrorFormatter {
+    fn format_value(&self, value: f64) -> String {
+        format!("{:e}", value)
+    }
+
+    fn format_throughput(&self, _: &criterion::Throughput, value: f64) -> String {
+        self.format_value(value)
+    }
+
+    fn scale_values(&self, _: f64, _: &mut [f64]) -> &'static str {
+        "error"
+    }
+
+    fn scale_throughputs(&self, _: f64, _: &criterion::Throughput, _: &mut [f64]) -> &'static str {
+        "error"
+    }
+
+    fn scale_for_machines(&self, _: &mut [f64]) -> &'static str {
+        "error"
+    }
+}
+
 pub fn bench_write(c: &mut Criterion<Compressability>) {
     let mut group = c.benchmark_group("Persistence");
     group.sample_size(10);
     let mut i = 1;
     let mut step = 1;
+    let tags = generate_tags(&TAGTYPES);
+    let mut store = SchemaStore::new();
+    store.register(generate_avro_schema(&DATATYPES));
     while i <= DATASIZE {
         let (rows, cols) = generate_data(&DATATYPES, i);
+        for compression in [
+            parquet::basic::Compression::UNCOMPRESSED,
+            parquet::basic::Compression::SNAPPY,
+            parquet::basic::Compression::LZ4,
+            parquet::basic::Compression::ZSTD,
+        ] {
+            let id = format!("RawBlock-{}", compression);
+            group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                b.iter(|| {
+                    raw_block_serialized_write("sample", &cols, compression);
+                    assert_eq!(raw_block_read("sample", compression), i);
+                })
+            });
+        }
+
         group.bench_with_input(BenchmarkId::new("Parquet-SNAPPY", i), &i, |b, _| {
             b.iter(|| {
                 parquet_serialized_write(
@@ -212,28 +321,315 @@ pub fn bench_write(c: &mut Criterion<Compressability>) {
             })
         });
 
+        group.bench_with_input(BenchmarkId::new("TsCol", i), &i, |b, _| {
+            b.iter(|| {
+                tscol_serialized_write("sample", &cols, 4096);
+                assert_eq!(tscol_read("sample"), i);
+            })
+        });
+
+        // the double column alone, against parquet over the same column
+        let double = DATATYPES.iter().position(|t| *t == "double").unwrap();
+        for codec in [FloatCodec::Gorilla, FloatCodec::Chimp] {
+            for compression in [
+                parquet::basic::Compression::UNCOMPRESSED,
+                parquet::basic::Compression::ZSTD,
+            ] {
+                let id = format!("Double-{:?}-{}", codec, compression);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| {
+                        float_column_serialized_write("sample", &cols[double], codec, compression);
+                        assert_eq!(float_column_read("sample", codec, compression), i);
+                    })
+                });
+            }
+        }
+        let double_cols = vec![cols[double].clone()];
+        group.bench_with_input(BenchmarkId::new("Double-Parquet-ZSTD", i), &i, |b, _| {
+            b.iter(|| {
+                parquet_serialized_write(
+                    "sample",
+                    &vec!["double"],
+                    &double_cols,
+                    parquet::basic::Compression::ZSTD,
+                );
+                assert_eq!(parquet_read("sample"), i);
+            })
+        });
+
+        // every integer column alone, against parquet over the same column
+        for data_type in [
+            "tinyint",
+            "utinyint",
+            "smallint",
+            "usmallint",
+            "int",
+            "uint",
+            "bigint",
+            "ubigint",
+        ] {
+            let index = DATATYPES.iter().position(|t| *t == data_type).unwrap();
+            for codec in [
+                IntCodec::Varint,
+                IntCodec::Simple8b,
+                IntCodec::FrameOfReference,
+            ] {
+                let id = format!("{}-{:?}", data_type, codec);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| {
+                        int_column_serialized_write("sample", &cols[index], codec);
+                        assert_eq!(int_column_read("sample", codec), i);
+                    })
+                });
+            }
+            let int_cols = vec![cols[index].clone()];
+            let id = format!("{}-Parquet-UNCOMPRESSED", data_type);
+            group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                b.iter(|| {
+                    parquet_serialized_write(
+                        "sample",
+                        &vec![data_type],
+                        &int_cols,
+                        parquet::basic::Compression::UNCOMPRESSED,
+                    );
+                    assert_eq!(parquet_read("sample"), i);
+                })
+            });
+        }
+
+        // the binary and nchar columns alone, against parquet over the same column
+        for data_type in ["binary", "nchar"] {
+            let index = DATATYPES.iter().position(|t| *t == data_type).unwrap();
+            for codec in [StringCodec::Dictionary, StringCodec::Fsst] {
+                let id = format!("{}-{:?}", data_type, codec);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| {
+                        string_column_serialized_write("sample", &cols[index], codec);
+                        assert_eq!(string_column_read("sample", codec), i);
+                    })
+                });
+            }
+            let string_cols = vec![cols[index].clone()];
+            let id = format!("{}-Parquet-UNCOMPRESSED", data_type);
+            group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                b.iter(|| {
+                    parquet_serialized_write(
+                        "sample",
+                        &vec![data_type],
+                        &string_cols,
+                        parquet::basic::Compression::UNCOMPRESSED,
+                    );
+                    assert_eq!(parquet_read("sample"), i);
+                })
+            });
+        }
+
+        // every fixed width column alone through each transform pipeline
+        for (data_type, col) in DATATYPES.iter().zip(&cols) {
+            if ["binary", "nchar"].contains(data_type) {
+                continue;
+            }
+            for pipeline in transform_pipelines() {
+                let id = format!("{}-{}", data_type, pipeline.name());
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| {
+                        transform_column_serialized_write("sample", col, &pipeline);
+                        assert_eq!(transform_column_read("sample", &pipeline), i);
+                    })
+                });
+            }
+        }
+
+        // the float and double columns alone through both lossy modes, bench_lossy_error
+        // has the maximum error of each
+        for data_type in ["float", "double"] {
+            let index = DATATYPES.iter().position(|t| *t == data_type).unwrap();
+            let col = &cols[index];
+            for bound in [ErrorBound::Absolute(1e-3), ErrorBound::Relative(1e-4)] {
+                let lossy_cols = vec![lossy_column(col, bound)];
+                let id = format!("{}-Round-{}-Parquet-ZSTD", data_type, bound);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| {
+                        parquet_serialized_write(
+                            "sample",
+                            &vec![data_type],
+                            &lossy_cols,
+                            parquet::basic::Compression::ZSTD,
+                        );
+                        assert_eq!(parquet_read("sample"), i);
+                    })
+                });
+                let compression = parquet::basic::Compression::ZSTD;
+                let id = format!("{}-SZ-{}-ZSTD", data_type, bound);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| {
+                        sz_column_serialized_write("sample", col, bound, compression);
+                        assert_eq!(sz_column_read("sample", compression), i);
+                    })
+                });
+            }
+        }
+
         group.bench_with_input(BenchmarkId::new("Avro-Deflate", i), &i, |b, _| {
             b.iter(|| {
-                avro_serialized_write("sample", &DATATYPES, &rows, Codec::Deflate);
+                avro_serialized_write("sample", &DATATYPES, &rows, Codec::Deflate, None);
                 assert_eq!(avro_read("sample"), i);
             })
         });
 
         group.bench_with_input(BenchmarkId::new("Avro-Null", i), &i, |b, _| {
             b.iter(|| {
-                avro_serialized_write("sample", &DATATYPES, &rows, Codec::Null);
+                avro_serialized_write("sample", &DATATYPES, &rows, Codec::Null, None);
                 assert_eq!(avro_read("sample"), i);
             })
         });
+
+        // next to Avro-Null, the difference is the magic, fingerprint and length prefix
+        // of every message against the container's one header
+        group.bench_with_input(BenchmarkId::new("Avro-SingleObject", i), &i, |b, _| {
+            b.iter(|| {
+                avro_single_object_serialized_write("sample", &DATATYPES, &rows);
+                assert_eq!(avro_single_object_read("sample", &store), i);
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("LineProtocol", i), &i, |b, _| {
+            b.iter(|| {
+                line_protocol_serialized_write("sample", &STABLE, &tags, &rows);
+                assert_eq!(line_protocol_read("sample", &STABLE), i);
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("OpenTSDB-Telnet", i), &i, |b, _| {
+            b.iter(|| {
+                opentsdb_telnet_serialized_write("sample", &STABLE, &tags, &rows);
+                assert_eq!(opentsdb_telnet_read("sample", &STABLE), i);
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("OpenTSDB-JSON", i), &i, |b, _| {
+            b.iter(|| {
+                opentsdb_json_serialized_write("sample", &STABLE, &tags, &rows);
+                assert_eq!(opentsdb_json_read("sample", &STABLE), i);
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("SQL", i), &i, |b, _| {
+            b.iter(|| {
+                sql_serialized_write("sample", &STABLE, "d0", &tags, &rows, 1000);
+                assert_eq!(sql_read("sample", &STABLE), i);
+            })
+        });
         i = step * 1000;
         step += 1;
     }
     group.finish();
 }
 
+// The maximum error of the lossy columns in bench_write, under the same ids. It starts
+// at 1000 rows as a single row has no range for the relative bound to scale.
+pub fn bench_lossy_error(c: &mut Criterion<MaxError>) {
+    let mut group = c.benchmark_group("LossyError");
+    group.sample_size(10);
+    let mut i = 1000;
+    let mut step = 2;
+    while i <= DATASIZE {
+        let (_, cols) = generate_data(&DATATYPES, i);
+        for data_type in ["float", "double"] {
+            let index = DATATYPES.iter().position(|t| *t == data_type).unwrap();
+            let col = &cols[index];
+            for bound in [ErrorBound::Absolute(1e-3), ErrorBound::Relative(1e-4)] {
+                let id = format!("{}-Round-{}-Parquet-ZSTD", data_type, bound);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter_custom(|iters| max_error(col, &lossy_column(col, bound)) * iters as f64)
+                });
+                let compression = parquet::basic::Compression::ZSTD;
+                let id = format!("{}-SZ-{}-ZSTD", data_type, bound);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter_custom(|iters| {
+                        let decoded = sz_column_deserialize(
+                            &sz_column_serialize(col, bound, compression),
+                            compression,
+                        );
+                        max_error(col, &decoded) * iters as f64
+                    })
+                });
+            }
+        }
+        i = step * 1000;
+        step += 1;
+    }
+    group.finish();
+}
+
+// General purpose codecs straight over a plain fixed width buffer of all columns, with
+// the plain buffer as throughput so sizes read as compressed bytes per plain byte.
+pub fn bench_general_codec(c: &mut Criterion<Compressability>) {
+    let mut group = c.benchmark_group("GeneralCodec");
+    group.sample_size(10);
+    let mut i = 1;
+    let mut step = 1;
+    while i <= DATASIZE {
+        let (_, cols) = generate_data(&DATATYPES, i);
+        let buf = fixed_width_buffer(&cols);
+        group.throughput(Throughput::Bytes(buf.len() as u64));
+        for codec in general_codecs() {
+            group.bench_with_input(BenchmarkId::new(codec.to_string(), i), &i, |b, _| {
+                b.iter(|| {
+                    general_codec_serialized_write("sample", &buf, codec);
+                    assert_eq!(general_codec_read("sample", codec), buf.len());
+                })
+            });
+        }
+        i = step * 1000;
+        step += 1;
+    }
+    group.finish();
+}
+
+// Small batches of the row formats compressed one message at a time with plain zstd and
+// with a dictionary trained on other batches of the same format, with the plain
+// messages as throughput so sizes read as compressed bytes per plain byte.
+pub fn bench_zstd_dictionary(c: &mut Criterion<Compressability>) {
+    let mut group = c.benchmark_group("ZstdDictionary");
+    group.sample_size(10);
+    let tags = generate_tags(&TAGTYPES);
+    for batch_size in [1, 10, 100] {
+        for format in ROW_FORMATS {
+            let (training, messages) = dictionary_messages(&STABLE, format, &tags, batch_size);
+            let dictionary = ZstdDictionary::train(&training, ZSTD_DICTIONARY_SIZE, ZSTD_LEVEL);
+            let plain: usize = messages.iter().map(Vec::len).sum();
+            group.throughput(Throughput::Bytes(plain as u64));
+            for (id, dictionary) in [
+                (format!("{}-ZSTD", format), None),
+                (format!("{}-ZSTD-Dictionary", format), Some(&dictionary)),
+            ] {
+                group.bench_with_input(BenchmarkId::new(id, batch_size), &batch_size, |b, _| {
+                    b.iter(|| {
+                        zstd_messages_serialized_write("sample", &messages, dictionary, ZSTD_LEVEL);
+                        assert_eq!(
+                            zstd_messages_read("sample", dictionary),
+                            ZSTD_MESSAGES as u32
+                        );
+                    })
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
 criterion_group! {
     name = bench_compressability;
     config = Criterion::default().with_measurement(Compressability);
-    targets = bench_write
+    targets = bench_write, bench_general_codec, bench_zstd_dictionary
+}
+// criterion_group! would turn plots back on from the command line
+pub fn bench_max_error() {
+    let mut criterion = Criterion::default()
+        .with_measurement(MaxError)
+        .configure_from_args()
+        .without_plots();
+    bench_lossy_error(&mut criterion);
 }
-criterion_main!(bench_compressability);
+criterion_main!(bench_compressability, bench_max_error);
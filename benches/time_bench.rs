@@ -1,9 +1,56 @@
+mod common;
+
 use avro_rs::Codec;
-use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use common::{
+    dictionary_messages, transform_pipelines, ROW_FORMATS, ZSTD_DICTIONARY_SIZE, ZSTD_LEVEL,
+};
+use criterion::{
+    criterion_group, criterion_main,
+    measurement::{Measurement, ValueFormatter},
+    BenchmarkId, Criterion, Throughput,
+};
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use prost::Message;
 use taosx_data_format_bench::{
-    avro::{avro_read, avro_serialize, avro_serialized_write},
-    generate_data,
-    my_parquet::{parquet_read, parquet_serialize, parquet_serialized_write},
+    avro::{
+        avro_container_serialize, avro_deserialize, avro_read, avro_read_projected, avro_serialize,
+        avro_serialized_write, generate_avro_schema, AvroStreamWriter,
+    },
+    avro_index::AvroIndexedReader,
+    avro_message::{avro_single_object_deserialize, avro_single_object_serialize, SchemaStore},
+    float_codec::{float_column_avro_serialize, float_column_serialize, FloatCodec},
+    general_codec::{fixed_width_buffer, general_codecs},
+    generate_data, generate_tags,
+    int_codec::{int_column_encode, IntCodec},
+    line_protocol::{line_protocol_read, line_protocol_serialize, line_protocol_serialized_write},
+    lossy::{lossy_column, sz_column_encode, ErrorBound},
+    my_flatbuffers::{flatbuffers_deserialize, flatbuffers_root, flatbuffers_serialize},
+    my_parquet::{
+        parquet_read, parquet_read_projected, parquet_read_time_range, parquet_serialize,
+        parquet_serialized_write, parquet_serialized_write_row_groups, ParquetStreamWriter,
+    },
+    my_rkyv::{rkyv_access, rkyv_deserialize, rkyv_serialize, ArchivedRkyvValues},
+    opentsdb::{opentsdb_json_serialize, opentsdb_telnet_serialize},
+    parquet_index::{parquet_read_eq, parquet_serialized_write_indexed, ParquetIndexOptions},
+    partition::{partitioned_read, partitioned_write, Granularity, PartitionFormat},
+    protobuf::{
+        pb::{column, ColumnBatch},
+        protobuf_column_deserialize, protobuf_column_serialize, protobuf_row_serialize,
+    },
+    raw_block::{
+        raw_block_deserialize, raw_block_read, raw_block_serialize, raw_block_serialized_write,
+    },
+    rolling_sink::{RollingPolicy, RollingSink, SinkFormat},
+    schema_registry::{
+        avro_confluent_deserialize, avro_confluent_serialize, MockSchemaRegistry,
+        SchemaRegistryClient,
+    },
+    sql::sql_serialize,
+    string_codec::{string_column_encode, StringCodec, StringColumn},
+    transform::transform_column_serialize,
+    tscol::{tscol_read, tscol_serialize, tscol_serialized_write},
+    zstd_dict::{zstd_messages_deserialize, zstd_messages_serialize, ZstdDictionary},
+    SuperTable,
 };
 
 extern crate lazy_static;
@@ -24,22 +71,121 @@ lazy_static::lazy_static! {
         "binary",
         "nchar",
     ];
+    pub static ref TAGTYPES: Vec<&'static str> = vec!["int", "binary"];
+    pub static ref STABLE: SuperTable<'static> = SuperTable {
+        name: "meters",
+        data_types: &DATATYPES,
+        tag_types: &TAGTYPES,
+        precision: TimestampPrecision::Milli,
+    };
 }
 
 pub const DATASIZE: u32 = 10000;
+pub const LOOKUP_DEVICES: usize = 10000;
+pub const STREAM_BATCHES: usize = 1000;
+pub const STREAM_BATCH_ROWS: u32 = 100;
 
 pub fn bench_serialize(c: &mut Criterion) {
     let mut group = c.benchmark_group("Serialize");
     let mut i = 1;
     let step = 10;
+    let tags = generate_tags(&TAGTYPES);
     while i <= DATASIZE {
         let (rows, cols) = generate_data(&DATATYPES, i);
+        group.bench_with_input(BenchmarkId::new("RawBlock", i), &i, |b, _| {
+            b.iter(|| raw_block_serialize(&cols, parquet::basic::Compression::UNCOMPRESSED))
+        });
+        group.bench_with_input(BenchmarkId::new("RawBlock-SNAPPY", i), &i, |b, _| {
+            b.iter(|| raw_block_serialize(&cols, parquet::basic::Compression::SNAPPY))
+        });
         group.bench_with_input(BenchmarkId::new("Parquet", i), &i, |b, _| {
             b.iter(|| parquet_serialize(&DATATYPES, &cols, parquet::basic::Compression::SNAPPY))
         });
+        group.bench_with_input(BenchmarkId::new("TsCol", i), &i, |b, _| {
+            b.iter(|| tscol_serialize(&cols, 4096))
+        });
         group.bench_with_input(BenchmarkId::new("Avro", i), &i, |b, _| {
             b.iter(|| avro_serialize(&DATATYPES, &rows, Codec::Deflate))
         });
+        group.bench_with_input(BenchmarkId::new("Avro-Null", i), &i, |b, _| {
+            b.iter(|| avro_serialize(&DATATYPES, &rows, Codec::Null))
+        });
+        group.bench_with_input(BenchmarkId::new("Protobuf-Row", i), &i, |b, _| {
+            b.iter(|| protobuf_row_serialize(&rows))
+        });
+        group.bench_with_input(BenchmarkId::new("Protobuf-Column", i), &i, |b, _| {
+            b.iter(|| protobuf_column_serialize(&cols))
+        });
+        group.bench_with_input(BenchmarkId::new("FlatBuffers", i), &i, |b, _| {
+            b.iter(|| flatbuffers_serialize(&cols))
+        });
+        group.bench_with_input(BenchmarkId::new("Rkyv", i), &i, |b, _| {
+            b.iter(|| rkyv_serialize(&cols))
+        });
+        group.bench_with_input(BenchmarkId::new("LineProtocol", i), &i, |b, _| {
+            b.iter(|| line_protocol_serialize(&STABLE, &tags, &rows))
+        });
+        group.bench_with_input(BenchmarkId::new("OpenTSDB-Telnet", i), &i, |b, _| {
+            b.iter(|| opentsdb_telnet_serialize(&STABLE, &tags, &rows))
+        });
+        group.bench_with_input(BenchmarkId::new("OpenTSDB-JSON", i), &i, |b, _| {
+            b.iter(|| opentsdb_json_serialize(&STABLE, &tags, &rows))
+        });
+        group.bench_with_input(BenchmarkId::new("SQL", i), &i, |b, _| {
+            b.iter(|| sql_serialize(&STABLE, "d0", &tags, &rows, 1000))
+        });
+        i *= step;
+    }
+    group.finish();
+}
+
+// "OneColumn" sums the double column, "Materialize" decodes every column into `Field`s.
+pub fn bench_access(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Access");
+    let double = DATATYPES.iter().position(|t| *t == "double").unwrap();
+    let mut i = 1;
+    let step = 10;
+    while i <= DATASIZE {
+        let (_, cols) = generate_data(&DATATYPES, i);
+        let protobuf = protobuf_column_serialize(&cols);
+        let flatbuffers = flatbuffers_serialize(&cols);
+        let rkyv = rkyv_serialize(&cols);
+        let raw_block = raw_block_serialize(&cols, parquet::basic::Compression::UNCOMPRESSED);
+        group.bench_with_input(BenchmarkId::new("RawBlock-Materialize", i), &i, |b, _| {
+            b.iter(|| raw_block_deserialize(&raw_block, parquet::basic::Compression::UNCOMPRESSED))
+        });
+        group.bench_with_input(BenchmarkId::new("Protobuf-OneColumn", i), &i, |b, _| {
+            b.iter(|| {
+                let batch = ColumnBatch::decode(&protobuf[..]).unwrap();
+                match &batch.columns[double].values {
+                    Some(column::Values::Double(v)) => v.values.iter().sum::<f64>(),
+                    _ => unreachable!(),
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("Protobuf-Materialize", i), &i, |b, _| {
+            b.iter(|| protobuf_column_deserialize(&protobuf))
+        });
+        group.bench_with_input(BenchmarkId::new("FlatBuffers-OneColumn", i), &i, |b, _| {
+            b.iter(|| {
+                let batch = flatbuffers_root(&flatbuffers);
+                batch.column(double).f64s().unwrap().iter().sum::<f64>()
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("FlatBuffers-Materialize", i),
+            &i,
+            |b, _| b.iter(|| flatbuffers_deserialize(&flatbuffers)),
+        );
+        group.bench_with_input(BenchmarkId::new("Rkyv-OneColumn", i), &i, |b, _| {
+            b.iter(|| match &rkyv_access(&rkyv).columns[double].values {
+                ArchivedRkyvValues::Double(v) => v.iter().map(|v| v.to_native()).sum::<f64>(),
+                _ => unreachable!(),
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("Rkyv-Materialize", i), &i, |b, _| {
+            b.iter(|| rkyv_deserialize(&rkyv))
+        });
         i *= step;
     }
     group.finish();
@@ -49,8 +195,19 @@ pub fn bench_write(c: &mut Criterion) {
     let mut group = c.benchmark_group("Write");
     let mut i = 1;
     let step = 10;
+    let tags = generate_tags(&TAGTYPES);
     while i <= DATASIZE {
         let (rows, cols) = generate_data(&DATATYPES, i);
+        group.bench_with_input(BenchmarkId::new("RawBlock", i), &i, |b, _| {
+            b.iter(|| {
+                raw_block_serialized_write(
+                    "sample.raw",
+                    &cols,
+                    parquet::basic::Compression::SNAPPY,
+                );
+            })
+        });
+        raw_block_read("sample.raw", parquet::basic::Compression::SNAPPY);
         group.bench_with_input(BenchmarkId::new("Parquet", i), &i, |b, _| {
             b.iter(|| {
                 parquet_serialized_write(
@@ -62,14 +219,728 @@ pub fn bench_write(c: &mut Criterion) {
             })
         });
         parquet_read("sample.parquet");
+        group.bench_with_input(BenchmarkId::new("TsCol", i), &i, |b, _| {
+            b.iter(|| tscol_serialized_write("sample.tscol", &cols, 4096))
+        });
+        tscol_read("sample.tscol");
         group.bench_with_input(BenchmarkId::new("Avro", i), &i, |b, _| {
-            b.iter(|| avro_serialized_write("sample.avro", &DATATYPES, &rows, Codec::Deflate))
+            b.iter(|| avro_serialized_write("sample.avro", &DATATYPES, &rows, Codec::Deflate, None))
         });
         avro_read("sample.avro");
+        group.bench_with_input(BenchmarkId::new("LineProtocol", i), &i, |b, _| {
+            b.iter(|| line_protocol_serialized_write("sample.lp", &STABLE, &tags, &rows))
+        });
+        line_protocol_read("sample.lp", &STABLE);
         i *= step;
     }
     group.finish();
 }
 
-criterion_group!(benches, bench_write);
-criterion_main!(benches);
+// The float and double columns alone, against parquet over the same column.
+pub fn bench_float_codec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("FloatCodec");
+    let mut i = 1;
+    let step = 10;
+    while i <= DATASIZE {
+        let (_, cols) = generate_data(&DATATYPES, i);
+        for data_type in ["float", "double"] {
+            let index = DATATYPES.iter().position(|t| *t == data_type).unwrap();
+            let col = &cols[index];
+            for codec in [FloatCodec::Gorilla, FloatCodec::Chimp] {
+                for compression in [
+                    parquet::basic::Compression::UNCOMPRESSED,
+                    parquet::basic::Compression::ZSTD,
+                ] {
+                    let id = format!("{}-{:?}-{}", data_type, codec, compression);
+                    group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                        b.iter(|| float_column_serialize(col, codec, compression))
+                    });
+                }
+                let id = format!("{}-{:?}-Avro-Deflate", data_type, codec);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| float_column_avro_serialize(col, codec, Codec::Deflate))
+                });
+            }
+            let data_types = vec![data_type];
+            let parquet_cols = vec![col.clone()];
+            let id = format!("{}-Parquet-ZSTD", data_type);
+            group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                b.iter(|| {
+                    parquet_serialize(
+                        &data_types,
+                        &parquet_cols,
+                        parquet::basic::Compression::ZSTD,
+                    )
+                })
+            });
+        }
+        i *= step;
+    }
+    group.finish();
+}
+
+pub const INTTYPES: [&str; 8] = [
+    "tinyint",
+    "utinyint",
+    "smallint",
+    "usmallint",
+    "int",
+    "uint",
+    "bigint",
+    "ubigint",
+];
+
+// Every integer column alone, against parquet over the same column.
+pub fn bench_int_codec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("IntCodec");
+    let mut i = 1;
+    let step = 10;
+    while i <= DATASIZE {
+        let (_, cols) = generate_data(&DATATYPES, i);
+        for data_type in INTTYPES {
+            let index = DATATYPES.iter().position(|t| *t == data_type).unwrap();
+            let col = &cols[index];
+            for codec in [
+                IntCodec::Varint,
+                IntCodec::Simple8b,
+                IntCodec::FrameOfReference,
+            ] {
+                let id = format!("{}-{:?}", data_type, codec);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| int_column_encode(col, codec))
+                });
+            }
+            let data_types = vec![data_type];
+            let parquet_cols = vec![col.clone()];
+            let id = format!("{}-Parquet-UNCOMPRESSED", data_type);
+            group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                b.iter(|| {
+                    parquet_serialize(
+                        &data_types,
+                        &parquet_cols,
+                        parquet::basic::Compression::UNCOMPRESSED,
+                    )
+                })
+            });
+        }
+        i *= step;
+    }
+    group.finish();
+}
+
+// The binary and nchar columns alone against parquet's ByteArray columns, "Get"
+// decodes a single value out of the encoded column.
+pub fn bench_string_codec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("StringCodec");
+    let mut i = 1;
+    let step = 10;
+    while i <= DATASIZE {
+        let (_, cols) = generate_data(&DATATYPES, i);
+        for data_type in ["binary", "nchar"] {
+            let index = DATATYPES.iter().position(|t| *t == data_type).unwrap();
+            let col = &cols[index];
+            for codec in [StringCodec::Dictionary, StringCodec::Fsst] {
+                let id = format!("{}-{:?}", data_type, codec);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| string_column_encode(col, codec))
+                });
+                let buf = string_column_encode(col, codec);
+                let column = StringColumn::new(&buf, codec);
+                let id = format!("{}-{:?}-Get", data_type, codec);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| column.get(i as usize / 2))
+                });
+            }
+            let data_types = vec![data_type];
+            let parquet_cols = vec![col.clone()];
+            let id = format!("{}-Parquet-UNCOMPRESSED", data_type);
+            group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                b.iter(|| {
+                    parquet_serialize(
+                        &data_types,
+                        &parquet_cols,
+                        parquet::basic::Compression::UNCOMPRESSED,
+                    )
+                })
+            });
+        }
+        i *= step;
+    }
+    group.finish();
+}
+
+// Every fixed width column alone through each transform pipeline.
+pub fn bench_transform(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Transform");
+    let mut i = 1;
+    let step = 10;
+    while i <= DATASIZE {
+        let (_, cols) = generate_data(&DATATYPES, i);
+        for (data_type, col) in DATATYPES.iter().zip(&cols) {
+            if ["binary", "nchar"].contains(data_type) {
+                continue;
+            }
+            for pipeline in transform_pipelines() {
+                let id = format!("{}-{}", data_type, pipeline.name());
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| transform_column_serialize(col, &pipeline))
+                });
+            }
+        }
+        i *= step;
+    }
+    group.finish();
+}
+
+// The float and double columns through both lossy modes at a few error bounds.
+pub fn bench_lossy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Lossy");
+    let mut i = 1;
+    let step = 10;
+    while i <= DATASIZE {
+        let (_, cols) = generate_data(&DATATYPES, i);
+        for data_type in ["float", "double"] {
+            let index = DATATYPES.iter().position(|t| *t == data_type).unwrap();
+            let col = &cols[index];
+            for bound in [ErrorBound::Absolute(1e-3), ErrorBound::Relative(1e-4)] {
+                let id = format!("{}-Round-{}", data_type, bound);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| lossy_column(col, bound))
+                });
+                let id = format!("{}-SZ-{}", data_type, bound);
+                group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                    b.iter(|| sz_column_encode(col, bound))
+                });
+            }
+        }
+        i *= step;
+    }
+    group.finish();
+}
+
+// General purpose codecs straight over a plain fixed width buffer of all columns,
+// throughput in bytes of the plain buffer.
+pub fn bench_general_codec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("GeneralCodec");
+    let mut i = 1;
+    let step = 10;
+    while i <= DATASIZE {
+        let (_, cols) = generate_data(&DATATYPES, i);
+        let buf = fixed_width_buffer(&cols);
+        group.throughput(Throughput::Bytes(buf.len() as u64));
+        for codec in general_codecs() {
+            let id = format!("{}-Compress", codec);
+            group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                b.iter(|| codec.compress(&buf))
+            });
+            let compressed = codec.compress(&buf);
+            let id = format!("{}-Decompress", codec);
+            group.bench_with_input(BenchmarkId::new(id, i), &i, |b, _| {
+                b.iter(|| codec.decompress(&compressed))
+            });
+        }
+        i *= step;
+    }
+    group.finish();
+}
+
+// Compressing and decompressing small batches of the row formats one message at a
+// time, with plain zstd and with a trained dictionary.
+pub fn bench_zstd_dictionary(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ZstdDictionary");
+    let tags = generate_tags(&TAGTYPES);
+    for batch_size in [1, 10, 100] {
+        for format in ROW_FORMATS {
+            let (training, messages) = dictionary_messages(&STABLE, format, &tags, batch_size);
+            let id = format!("{}-Train", format);
+            group.bench_with_input(BenchmarkId::new(id, batch_size), &batch_size, |b, _| {
+                b.iter(|| ZstdDictionary::train(&training, ZSTD_DICTIONARY_SIZE, ZSTD_LEVEL))
+            });
+            let dictionary = ZstdDictionary::train(&training, ZSTD_DICTIONARY_SIZE, ZSTD_LEVEL);
+            for (id, dictionary) in [
+                (format!("{}-ZSTD", format), None),
+                (format!("{}-ZSTD-Dictionary", format), Some(&dictionary)),
+            ] {
+                group.bench_with_input(
+                    BenchmarkId::new(format!("{}-Compress", id), batch_size),
+                    &batch_size,
+                    |b, _| b.iter(|| zstd_messages_serialize(&messages, dictionary, ZSTD_LEVEL)),
+                );
+                let buf = zstd_messages_serialize(&messages, dictionary, ZSTD_LEVEL);
+                group.bench_with_input(
+                    BenchmarkId::new(format!("{}-Decompress", id), batch_size),
+                    &batch_size,
+                    |b, _| b.iter(|| zstd_messages_deserialize(&buf, dictionary)),
+                );
+            }
+        }
+    }
+    group.finish();
+}
+
+// Per message cost of single object encoding and the Confluent wire format against a
+// container file per message.
+pub fn bench_avro_message(c: &mut Criterion) {
+    let mut group = c.benchmark_group("AvroMessage");
+    let mut store = SchemaStore::new();
+    store.register(generate_avro_schema(&DATATYPES));
+    // ids and schemas are cached after the first batch, as in a long running producer
+    let registry = MockSchemaRegistry::start();
+    let mut client = SchemaRegistryClient::new(registry.url());
+    let mut i = 1;
+    let step = 10;
+    while i <= DATASIZE {
+        let (rows, _) = generate_data(&DATATYPES, i);
+        group.bench_with_input(BenchmarkId::new("SingleObject-Encode", i), &i, |b, _| {
+            b.iter(|| avro_single_object_serialize(&DATATYPES, &rows))
+        });
+        let messages = avro_single_object_serialize(&DATATYPES, &rows);
+        group.bench_with_input(BenchmarkId::new("SingleObject-Decode", i), &i, |b, _| {
+            b.iter(|| avro_single_object_deserialize(&store, &messages))
+        });
+        group.bench_with_input(BenchmarkId::new("Confluent-Encode", i), &i, |b, _| {
+            b.iter(|| avro_confluent_serialize(&mut client, "meters-value", &DATATYPES, &rows))
+        });
+        let messages = avro_confluent_serialize(&mut client, "meters-value", &DATATYPES, &rows);
+        group.bench_with_input(BenchmarkId::new("Confluent-Decode", i), &i, |b, _| {
+            b.iter(|| avro_confluent_deserialize(&mut client, &messages))
+        });
+        let container = || -> Vec<Vec<u8>> {
+            rows.chunks(1)
+                .map(|row| avro_container_serialize(&DATATYPES, &row.to_vec(), Codec::Null))
+                .collect()
+        };
+        group.bench_with_input(BenchmarkId::new("Container-Encode", i), &i, |b, _| {
+            b.iter(container)
+        });
+        let containers = container();
+        group.bench_with_input(BenchmarkId::new("Container-Decode", i), &i, |b, _| {
+            b.iter(|| -> Vec<_> { containers.iter().map(|c| avro_deserialize(c)).collect() })
+        });
+        i *= step;
+    }
+    group.finish();
+}
+
+// Reading `timestamp` plus a growing number of metrics out of files with every column.
+pub fn bench_projection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Projection");
+    let (rows, cols) = generate_data(&DATATYPES, DATASIZE);
+    parquet_serialized_write(
+        "sample.parquet",
+        &DATATYPES,
+        &cols,
+        parquet::basic::Compression::SNAPPY,
+    );
+    avro_serialized_write("sample.avro", &DATATYPES, &rows, Codec::Deflate, None);
+    for width in [1, 2, 4, 8, DATATYPES.len()] {
+        let columns = &DATATYPES[..width];
+        group.bench_with_input(BenchmarkId::new("Parquet", width), &width, |b, _| {
+            b.iter(|| parquet_read_projected("sample.parquet", columns))
+        });
+        group.bench_with_input(BenchmarkId::new("Avro", width), &width, |b, _| {
+            b.iter(|| avro_read_projected("sample.avro", columns))
+        });
+    }
+    group.finish();
+}
+
+// A file of monotonic timestamps a second apart in row groups of 1000 rows, with page
+// indexes over pages of 100 rows when `page_index` is set, and ranges covering a growing
+// share of it.
+fn time_range_file(filename: &str, page_index: bool) -> Vec<(i64, i64, i64)> {
+    let rows = DATASIZE as i64 * 10;
+    let (_, mut cols) = generate_data(&DATATYPES, rows as u32);
+    let start = 1_600_000_000_000;
+    cols[0] = (0..rows)
+        .map(|i| Field::Timestamp(Timestamp::new(start + i * 1000, TimestampPrecision::Milli)))
+        .collect();
+    let compression = parquet::basic::Compression::SNAPPY;
+    if page_index {
+        let options = ParquetIndexOptions {
+            page_index: true,
+            bloom_filter_columns: &[],
+            bloom_filter_fpp: 0.01,
+            page_rows: 100,
+        };
+        parquet_serialized_write_indexed(filename, &DATATYPES, &cols, compression, 1000, &options);
+    } else {
+        parquet_serialized_write_row_groups(filename, &DATATYPES, &cols, compression, 1000);
+    }
+    [1, 10, 50, 100]
+        .into_iter()
+        .map(|percent| {
+            // starting half way into a row group and a page
+            let from = start + 550 * 1000;
+            let to = from + (rows * percent / 100 - 1) * 1000;
+            (percent, from, to)
+        })
+        .collect()
+}
+
+pub fn bench_time_range(c: &mut Criterion) {
+    let mut group = c.benchmark_group("TimeRange");
+    for (name, page_index) in [("Parquet", false), ("Parquet-PageIndex", true)] {
+        for (percent, from, to) in time_range_file("sample.parquet", page_index) {
+            group.bench_with_input(BenchmarkId::new(name, percent), &percent, |b, _| {
+                b.iter(|| parquet_read_time_range("sample.parquet", "timestamp", from, to))
+            });
+        }
+    }
+    group.bench_function("Parquet-FullScan", |b| {
+        b.iter(|| parquet_read("sample.parquet"))
+    });
+    group.finish();
+}
+
+// Not a time but the rows of the timestamp column a time range read decodes. Benches
+// report it through `iter_custom` with the rows returned as throughput, so it also reads
+// as rows scanned per row returned. It does not vary, so it has no plots.
+pub struct RowsScanned;
+impl Measurement for RowsScanned {
+    type Intermediate = ();
+
+    type Value = u64;
+
+    fn start(&self) -> Self::Intermediate {}
+
+    fn end(&self, _: Self::Intermediate) -> Self::Value {
+        0
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &RowsScannedFormatter
+    }
+}
+
+struct RowsScannedFormatter;
+impl ValueFormatter for RowsScannedFormatter {
+    fn format_value(&self, value: f64) -> String {
+        format!("{} rows", value)
+    }
+
+    fn format_throughput(&self, throughput: &Throughput, value: f64) -> String {
+        match throughput {
+            Throughput::Elements(n) => format!("{:.4} scanned/returned", value / *n as f64),
+            Throughput::Bytes(_) => self.format_value(value),
+        }
+    }
+
+    fn scale_values(&self, _: f64, _: &mut [f64]) -> &'static str {
+        "rows"
+    }
+
+    fn scale_throughputs(
+        &self,
+        _: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        if let Throughput::Elements(n) = throughput {
+            for value in values {
+                *value /= *n as f64;
+            }
+        }
+        "scanned/returned"
+    }
+
+    fn scale_for_machines(&self, _: &mut [f64]) -> &'static str {
+        "rows"
+    }
+}
+
+// The rows scanned against the rows returned of the "TimeRange" reads.
+pub fn bench_time_range_scanned(c: &mut Criterion<RowsScanned>) {
+    let mut group = c.benchmark_group("TimeRangeScanned");
+    group.sample_size(10);
+    // warming up doubles the iterations until a wall clock time is up, and a sample is a
+    // single read however many iterations it stands for
+    group.warm_up_time(std::time::Duration::from_millis(1));
+    for (name, page_index) in [("Parquet", false), ("Parquet-PageIndex", true)] {
+        for (percent, from, to) in time_range_file("sample.parquet", page_index) {
+            let (rows, _) = parquet_read_time_range("sample.parquet", "timestamp", from, to);
+            group.throughput(Throughput::Elements(rows.len() as u64));
+            group.bench_with_input(BenchmarkId::new(name, percent), &percent, |b, _| {
+                b.iter_custom(|iters| {
+                    let (_, stats) =
+                        parquet_read_time_range("sample.parquet", "timestamp", from, to);
+                    stats.rows_scanned as u64 * iters
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+// criterion_group! would turn plots back on from the command line
+pub fn bench_rows_scanned() {
+    let mut criterion = Criterion::default()
+        .with_measurement(RowsScanned)
+        .configure_from_args()
+        .without_plots();
+    bench_time_range_scanned(&mut criterion);
+}
+
+pub fn bench_point_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("PointLookup");
+    let rows = DATASIZE as usize * 10;
+    let (_, mut cols) = generate_data(&DATATYPES, rows as u32);
+    let binary = DATATYPES.iter().position(|t| *t == "binary").unwrap();
+    // a device id per row in no particular order, like interleaved writes from many devices
+    cols[binary] = (0..rows)
+        .map(|i| Field::Binary(format!("d{:05}", i * 7919 % LOOKUP_DEVICES).into()))
+        .collect();
+    let options = [
+        ("Parquet", None),
+        (
+            "Parquet-PageIndex",
+            Some(ParquetIndexOptions {
+                page_index: true,
+                bloom_filter_columns: &[],
+                bloom_filter_fpp: 0.01,
+                page_rows: 100,
+            }),
+        ),
+        (
+            "Parquet-BloomFilter",
+            Some(ParquetIndexOptions {
+                page_index: true,
+                bloom_filter_columns: &["binary"],
+                bloom_filter_fpp: 0.01,
+                page_rows: 100,
+            }),
+        ),
+    ];
+    let value = Field::Binary("d01234".into());
+    for (name, options) in options {
+        let filename = format!("sample_{}.parquet", name);
+        match &options {
+            Some(options) => parquet_serialized_write_indexed(
+                &filename,
+                &DATATYPES,
+                &cols,
+                parquet::basic::Compression::SNAPPY,
+                1000,
+                options,
+            ),
+            None => parquet_serialized_write_row_groups(
+                &filename,
+                &DATATYPES,
+                &cols,
+                parquet::basic::Compression::SNAPPY,
+                1000,
+            ),
+        }
+        group.bench_function(name, |b| {
+            b.iter(|| parquet_read_eq(&filename, "binary", &value))
+        });
+        std::fs::remove_file(&filename).unwrap();
+    }
+    group.finish();
+}
+
+pub fn bench_avro_seek(c: &mut Criterion) {
+    let mut group = c.benchmark_group("AvroSeek");
+    let rows = DATASIZE as i64 * 10;
+    let (mut data, _) = generate_data(&DATATYPES, rows as u32);
+    let start = 1_600_000_000_000;
+    for (i, row) in data.iter_mut().enumerate() {
+        row[0] = Field::Timestamp(Timestamp::new(
+            start + i as i64 * 1000,
+            TimestampPrecision::Milli,
+        ));
+    }
+    avro_serialized_write("sample.avro", &DATATYPES, &data, Codec::Deflate, Some(1000));
+    let row = rows as u64 * 3 / 4;
+    let ts = start + row as i64 * 1000;
+    group.bench_function("Row", |b| {
+        b.iter(|| AvroIndexedReader::open("sample.avro").read_row(row))
+    });
+    group.bench_function("Timestamp", |b| {
+        b.iter(|| AvroIndexedReader::open("sample.avro").read_timestamp(ts))
+    });
+    group.bench_function("FullScan", |b| b.iter(|| avro_read("sample.avro")));
+    std::fs::remove_file("sample.avro.idx").unwrap();
+    group.finish();
+}
+
+// An ingestion stream of small batches into one open file, against buffering the whole
+// stream and writing it at once.
+pub fn bench_stream(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Stream");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(
+        (STREAM_BATCHES * STREAM_BATCH_ROWS as usize) as u64,
+    ));
+    let batches: Vec<_> = (0..STREAM_BATCHES)
+        .map(|_| generate_data(&DATATYPES, STREAM_BATCH_ROWS))
+        .collect();
+    let schema = generate_avro_schema(&DATATYPES);
+    group.bench_function("Avro-Stream", |b| {
+        b.iter(|| {
+            let mut writer =
+                AvroStreamWriter::new("sample.avro", &schema, &DATATYPES, Codec::Deflate, 16000);
+            for (rows, _) in &batches {
+                writer.append_batch(rows);
+            }
+            writer.close()
+        })
+    });
+    group.bench_function("Avro-AllAtOnce", |b| {
+        b.iter(|| {
+            let rows: Vec<_> = batches.iter().flat_map(|(rows, _)| rows.clone()).collect();
+            avro_serialized_write("sample.avro", &DATATYPES, &rows, Codec::Deflate, None)
+        })
+    });
+    group.bench_function("Parquet-Stream", |b| {
+        b.iter(|| {
+            let mut writer = ParquetStreamWriter::new(
+                "sample.parquet",
+                &DATATYPES,
+                parquet::basic::Compression::SNAPPY,
+                DATASIZE as usize,
+            );
+            for (_, cols) in &batches {
+                writer.append_batch(cols);
+            }
+            writer.close()
+        })
+    });
+    group.bench_function("Parquet-AllAtOnce", |b| {
+        b.iter(|| {
+            let mut all = vec![vec![]; DATATYPES.len()];
+            for (_, cols) in &batches {
+                for (all, col) in all.iter_mut().zip(cols) {
+                    all.extend_from_slice(col);
+                }
+            }
+            parquet_serialized_write_row_groups(
+                "sample.parquet",
+                &DATATYPES,
+                &all,
+                parquet::basic::Compression::SNAPPY,
+                DATASIZE as usize,
+            )
+        })
+    });
+    group.finish();
+}
+
+// The stream of `bench_stream` split into files of `max_rows` rows.
+pub fn bench_rolling_sink(c: &mut Criterion) {
+    let mut group = c.benchmark_group("RollingSink");
+    group.sample_size(10);
+    let batches: Vec<_> = (0..STREAM_BATCHES)
+        .map(|_| generate_data(&DATATYPES, STREAM_BATCH_ROWS).0)
+        .collect();
+    std::fs::create_dir_all("rolling").unwrap();
+    for max_rows in [5000, 20000, 100000] {
+        let policy = RollingPolicy {
+            max_rows: Some(max_rows),
+            ..Default::default()
+        };
+        group.bench_with_input(
+            BenchmarkId::new("Parquet", max_rows),
+            &policy,
+            |b, policy| {
+                b.iter(|| {
+                    let format = SinkFormat::Parquet {
+                        compression: parquet::basic::Compression::SNAPPY,
+                        row_group_rows: DATASIZE as usize,
+                    };
+                    let mut sink = RollingSink::new(
+                        format,
+                        &DATATYPES,
+                        policy.clone(),
+                        "rolling/{seq}.parquet",
+                    );
+                    let mut files = 0;
+                    for batch in &batches {
+                        files += sink.append_batch(batch).len();
+                    }
+                    files + sink.close().iter().count()
+                })
+            },
+        );
+    }
+    std::fs::remove_dir_all("rolling").unwrap();
+    group.finish();
+}
+
+// A week of 10 sub tables at a row a minute in hourly partitions, read back by ranges
+// of an hour, a day and the whole week.
+pub fn bench_partition(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Partition");
+    group.sample_size(10);
+    let start = 1_600_000_000_000;
+    let minutes = 7 * 24 * 60;
+    let tables: Vec<_> = (0..10)
+        .map(|_| {
+            let (mut rows, _) = generate_data(&DATATYPES, minutes);
+            for (i, row) in rows.iter_mut().enumerate() {
+                row[0] = Field::Timestamp(Timestamp::new(
+                    start + i as i64 * 60_000,
+                    TimestampPrecision::Milli,
+                ));
+            }
+            (generate_tags(&TAGTYPES), rows)
+        })
+        .collect();
+    let format = PartitionFormat::Parquet(parquet::basic::Compression::SNAPPY);
+    group.bench_function("Write", |b| {
+        b.iter(|| {
+            std::fs::remove_dir_all("partitioned").ok();
+            partitioned_write(
+                "partitioned",
+                &STABLE,
+                Granularity::Hour,
+                &[],
+                format,
+                &tables,
+            )
+        })
+    });
+    for (name, hours) in [("Hour", 1), ("Day", 24), ("Week", 7 * 24)] {
+        let end = start + hours * 3_600_000 - 1;
+        group.bench_function(name, |b| {
+            b.iter(|| partitioned_read("partitioned", &STABLE, start, end))
+        });
+    }
+    std::fs::remove_dir_all("partitioned").unwrap();
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_serialize,
+    bench_access,
+    bench_write,
+    bench_float_codec,
+    bench_int_codec,
+    bench_string_codec,
+    bench_transform,
+    bench_lossy,
+    bench_general_codec,
+    bench_zstd_dictionary,
+    bench_avro_message,
+    bench_projection,
+    bench_time_range,
+    bench_point_lookup,
+    bench_avro_seek,
+    bench_stream,
+    bench_rolling_sink,
+    bench_partition
+);
+criterion_main!(benches, bench_rows_scanned);
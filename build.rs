@@ -0,0 +1,4 @@
+fn main() {
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path().unwrap());
+    prost_build::compile_protos(&["proto/row_batch.proto"], &["proto/"]).unwrap();
+}
@@ -0,0 +1,541 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+
+
+// @generated
+
+use core::mem;
+use core::cmp::Ordering;
+
+extern crate flatbuffers;
+use self::flatbuffers::{EndianScalar, Follow};
+
+#[allow(unused_imports, dead_code)]
+pub mod taosx {
+
+  use core::mem;
+  use core::cmp::Ordering;
+
+  extern crate flatbuffers;
+  use self::flatbuffers::{EndianScalar, Follow};
+
+pub enum ColumnOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct Column<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for Column<'a> {
+  type Inner = Column<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> Column<'a> {
+  pub const VT_DATA_TYPE: flatbuffers::VOffsetT = 4;
+  pub const VT_PRECISION: flatbuffers::VOffsetT = 6;
+  pub const VT_NULLS: flatbuffers::VOffsetT = 8;
+  pub const VT_BOOLS: flatbuffers::VOffsetT = 10;
+  pub const VT_I8S: flatbuffers::VOffsetT = 12;
+  pub const VT_I16S: flatbuffers::VOffsetT = 14;
+  pub const VT_I32S: flatbuffers::VOffsetT = 16;
+  pub const VT_I64S: flatbuffers::VOffsetT = 18;
+  pub const VT_U8S: flatbuffers::VOffsetT = 20;
+  pub const VT_U16S: flatbuffers::VOffsetT = 22;
+  pub const VT_U32S: flatbuffers::VOffsetT = 24;
+  pub const VT_U64S: flatbuffers::VOffsetT = 26;
+  pub const VT_F32S: flatbuffers::VOffsetT = 28;
+  pub const VT_F64S: flatbuffers::VOffsetT = 30;
+  pub const VT_OFFSETS: flatbuffers::VOffsetT = 32;
+  pub const VT_DATA: flatbuffers::VOffsetT = 34;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+    Column { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ColumnArgs<'args>
+  ) -> flatbuffers::WIPOffset<Column<'bldr>> {
+    let mut builder = ColumnBuilder::new(_fbb);
+    if let Some(x) = args.data { builder.add_data(x); }
+    if let Some(x) = args.offsets { builder.add_offsets(x); }
+    if let Some(x) = args.f64s { builder.add_f64s(x); }
+    if let Some(x) = args.f32s { builder.add_f32s(x); }
+    if let Some(x) = args.u64s { builder.add_u64s(x); }
+    if let Some(x) = args.u32s { builder.add_u32s(x); }
+    if let Some(x) = args.u16s { builder.add_u16s(x); }
+    if let Some(x) = args.u8s { builder.add_u8s(x); }
+    if let Some(x) = args.i64s { builder.add_i64s(x); }
+    if let Some(x) = args.i32s { builder.add_i32s(x); }
+    if let Some(x) = args.i16s { builder.add_i16s(x); }
+    if let Some(x) = args.i8s { builder.add_i8s(x); }
+    if let Some(x) = args.bools { builder.add_bools(x); }
+    if let Some(x) = args.nulls { builder.add_nulls(x); }
+    builder.add_precision(args.precision);
+    builder.add_data_type(args.data_type);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn data_type(&self) -> u8 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u8>(Column::VT_DATA_TYPE, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn precision(&self) -> u8 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u8>(Column::VT_PRECISION, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn nulls(&self) -> Option<flatbuffers::Vector<'a, u32>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u32>>>(Column::VT_NULLS, None)}
+  }
+  #[inline]
+  pub fn bools(&self) -> Option<flatbuffers::Vector<'a, bool>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, bool>>>(Column::VT_BOOLS, None)}
+  }
+  #[inline]
+  pub fn i8s(&self) -> Option<flatbuffers::Vector<'a, i8>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, i8>>>(Column::VT_I8S, None)}
+  }
+  #[inline]
+  pub fn i16s(&self) -> Option<flatbuffers::Vector<'a, i16>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, i16>>>(Column::VT_I16S, None)}
+  }
+  #[inline]
+  pub fn i32s(&self) -> Option<flatbuffers::Vector<'a, i32>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, i32>>>(Column::VT_I32S, None)}
+  }
+  #[inline]
+  pub fn i64s(&self) -> Option<flatbuffers::Vector<'a, i64>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, i64>>>(Column::VT_I64S, None)}
+  }
+  #[inline]
+  pub fn u8s(&self) -> Option<flatbuffers::Vector<'a, u8>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u8>>>(Column::VT_U8S, None)}
+  }
+  #[inline]
+  pub fn u16s(&self) -> Option<flatbuffers::Vector<'a, u16>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u16>>>(Column::VT_U16S, None)}
+  }
+  #[inline]
+  pub fn u32s(&self) -> Option<flatbuffers::Vector<'a, u32>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u32>>>(Column::VT_U32S, None)}
+  }
+  #[inline]
+  pub fn u64s(&self) -> Option<flatbuffers::Vector<'a, u64>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u64>>>(Column::VT_U64S, None)}
+  }
+  #[inline]
+  pub fn f32s(&self) -> Option<flatbuffers::Vector<'a, f32>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, f32>>>(Column::VT_F32S, None)}
+  }
+  #[inline]
+  pub fn f64s(&self) -> Option<flatbuffers::Vector<'a, f64>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, f64>>>(Column::VT_F64S, None)}
+  }
+  #[inline]
+  pub fn offsets(&self) -> Option<flatbuffers::Vector<'a, u32>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u32>>>(Column::VT_OFFSETS, None)}
+  }
+  #[inline]
+  pub fn data(&self) -> Option<flatbuffers::Vector<'a, u8>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u8>>>(Column::VT_DATA, None)}
+  }
+}
+
+impl flatbuffers::Verifiable for Column<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<u8>("data_type", Self::VT_DATA_TYPE, false)?
+     .visit_field::<u8>("precision", Self::VT_PRECISION, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u32>>>("nulls", Self::VT_NULLS, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, bool>>>("bools", Self::VT_BOOLS, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, i8>>>("i8s", Self::VT_I8S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, i16>>>("i16s", Self::VT_I16S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, i32>>>("i32s", Self::VT_I32S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, i64>>>("i64s", Self::VT_I64S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u8>>>("u8s", Self::VT_U8S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u16>>>("u16s", Self::VT_U16S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u32>>>("u32s", Self::VT_U32S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u64>>>("u64s", Self::VT_U64S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, f32>>>("f32s", Self::VT_F32S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, f64>>>("f64s", Self::VT_F64S, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u32>>>("offsets", Self::VT_OFFSETS, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u8>>>("data", Self::VT_DATA, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ColumnArgs<'a> {
+    pub data_type: u8,
+    pub precision: u8,
+    pub nulls: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u32>>>,
+    pub bools: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, bool>>>,
+    pub i8s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, i8>>>,
+    pub i16s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, i16>>>,
+    pub i32s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, i32>>>,
+    pub i64s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, i64>>>,
+    pub u8s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u8>>>,
+    pub u16s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u16>>>,
+    pub u32s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u32>>>,
+    pub u64s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u64>>>,
+    pub f32s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, f32>>>,
+    pub f64s: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, f64>>>,
+    pub offsets: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u32>>>,
+    pub data: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u8>>>,
+}
+impl<'a> Default for ColumnArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    ColumnArgs {
+      data_type: 0,
+      precision: 0,
+      nulls: None,
+      bools: None,
+      i8s: None,
+      i16s: None,
+      i32s: None,
+      i64s: None,
+      u8s: None,
+      u16s: None,
+      u32s: None,
+      u64s: None,
+      f32s: None,
+      f64s: None,
+      offsets: None,
+      data: None,
+    }
+  }
+}
+
+pub struct ColumnBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> ColumnBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_data_type(&mut self, data_type: u8) {
+    self.fbb_.push_slot::<u8>(Column::VT_DATA_TYPE, data_type, 0);
+  }
+  #[inline]
+  pub fn add_precision(&mut self, precision: u8) {
+    self.fbb_.push_slot::<u8>(Column::VT_PRECISION, precision, 0);
+  }
+  #[inline]
+  pub fn add_nulls(&mut self, nulls: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u32>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_NULLS, nulls);
+  }
+  #[inline]
+  pub fn add_bools(&mut self, bools: flatbuffers::WIPOffset<flatbuffers::Vector<'b , bool>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_BOOLS, bools);
+  }
+  #[inline]
+  pub fn add_i8s(&mut self, i8s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , i8>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_I8S, i8s);
+  }
+  #[inline]
+  pub fn add_i16s(&mut self, i16s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , i16>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_I16S, i16s);
+  }
+  #[inline]
+  pub fn add_i32s(&mut self, i32s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , i32>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_I32S, i32s);
+  }
+  #[inline]
+  pub fn add_i64s(&mut self, i64s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , i64>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_I64S, i64s);
+  }
+  #[inline]
+  pub fn add_u8s(&mut self, u8s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u8>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_U8S, u8s);
+  }
+  #[inline]
+  pub fn add_u16s(&mut self, u16s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u16>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_U16S, u16s);
+  }
+  #[inline]
+  pub fn add_u32s(&mut self, u32s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u32>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_U32S, u32s);
+  }
+  #[inline]
+  pub fn add_u64s(&mut self, u64s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u64>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_U64S, u64s);
+  }
+  #[inline]
+  pub fn add_f32s(&mut self, f32s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , f32>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_F32S, f32s);
+  }
+  #[inline]
+  pub fn add_f64s(&mut self, f64s: flatbuffers::WIPOffset<flatbuffers::Vector<'b , f64>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_F64S, f64s);
+  }
+  #[inline]
+  pub fn add_offsets(&mut self, offsets: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u32>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_OFFSETS, offsets);
+  }
+  #[inline]
+  pub fn add_data(&mut self, data: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u8>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Column::VT_DATA, data);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> ColumnBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ColumnBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<Column<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl core::fmt::Debug for Column<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let mut ds = f.debug_struct("Column");
+      ds.field("data_type", &self.data_type());
+      ds.field("precision", &self.precision());
+      ds.field("nulls", &self.nulls());
+      ds.field("bools", &self.bools());
+      ds.field("i8s", &self.i8s());
+      ds.field("i16s", &self.i16s());
+      ds.field("i32s", &self.i32s());
+      ds.field("i64s", &self.i64s());
+      ds.field("u8s", &self.u8s());
+      ds.field("u16s", &self.u16s());
+      ds.field("u32s", &self.u32s());
+      ds.field("u64s", &self.u64s());
+      ds.field("f32s", &self.f32s());
+      ds.field("f64s", &self.f64s());
+      ds.field("offsets", &self.offsets());
+      ds.field("data", &self.data());
+      ds.finish()
+  }
+}
+pub enum ColumnBatchOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ColumnBatch<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for ColumnBatch<'a> {
+  type Inner = ColumnBatch<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> ColumnBatch<'a> {
+  pub const VT_COLUMNS: flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+    ColumnBatch { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ColumnBatchArgs<'args>
+  ) -> flatbuffers::WIPOffset<ColumnBatch<'bldr>> {
+    let mut builder = ColumnBatchBuilder::new(_fbb);
+    if let Some(x) = args.columns { builder.add_columns(x); }
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn columns(&self) -> Option<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<Column<'a>>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<Column>>>>(ColumnBatch::VT_COLUMNS, None)}
+  }
+}
+
+impl flatbuffers::Verifiable for ColumnBatch<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, flatbuffers::ForwardsUOffset<Column>>>>("columns", Self::VT_COLUMNS, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ColumnBatchArgs<'a> {
+    pub columns: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<Column<'a>>>>>,
+}
+impl<'a> Default for ColumnBatchArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    ColumnBatchArgs {
+      columns: None,
+    }
+  }
+}
+
+pub struct ColumnBatchBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> ColumnBatchBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_columns(&mut self, columns: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<Column<'b >>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(ColumnBatch::VT_COLUMNS, columns);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> ColumnBatchBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ColumnBatchBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<ColumnBatch<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl core::fmt::Debug for ColumnBatch<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let mut ds = f.debug_struct("ColumnBatch");
+      ds.field("columns", &self.columns());
+      ds.finish()
+  }
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a `ColumnBatch`
+/// and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_column_batch_unchecked`.
+pub fn root_as_column_batch(buf: &[u8]) -> Result<ColumnBatch, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::root::<ColumnBatch>(buf)
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a size prefixed
+/// `ColumnBatch` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `size_prefixed_root_as_column_batch_unchecked`.
+pub fn size_prefixed_root_as_column_batch(buf: &[u8]) -> Result<ColumnBatch, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::size_prefixed_root::<ColumnBatch>(buf)
+}
+#[inline]
+/// Verifies, with the given options, that a buffer of bytes
+/// contains a `ColumnBatch` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_column_batch_unchecked`.
+pub fn root_as_column_batch_with_opts<'b, 'o>(
+  opts: &'o flatbuffers::VerifierOptions,
+  buf: &'b [u8],
+) -> Result<ColumnBatch<'b>, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::root_with_opts::<ColumnBatch<'b>>(opts, buf)
+}
+#[inline]
+/// Verifies, with the given verifier options, that a buffer of
+/// bytes contains a size prefixed `ColumnBatch` and returns
+/// it. Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_column_batch_unchecked`.
+pub fn size_prefixed_root_as_column_batch_with_opts<'b, 'o>(
+  opts: &'o flatbuffers::VerifierOptions,
+  buf: &'b [u8],
+) -> Result<ColumnBatch<'b>, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::size_prefixed_root_with_opts::<ColumnBatch<'b>>(opts, buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a ColumnBatch and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid `ColumnBatch`.
+pub unsafe fn root_as_column_batch_unchecked(buf: &[u8]) -> ColumnBatch {
+  unsafe { flatbuffers::root_unchecked::<ColumnBatch>(buf) }
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a size prefixed ColumnBatch and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid size prefixed `ColumnBatch`.
+pub unsafe fn size_prefixed_root_as_column_batch_unchecked(buf: &[u8]) -> ColumnBatch {
+  unsafe { flatbuffers::size_prefixed_root_unchecked::<ColumnBatch>(buf) }
+}
+#[inline]
+pub fn finish_column_batch_buffer<'a, 'b, A: flatbuffers::Allocator + 'a>(
+    fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+    root: flatbuffers::WIPOffset<ColumnBatch<'a>>) {
+  fbb.finish(root, None);
+}
+
+#[inline]
+pub fn finish_size_prefixed_column_batch_buffer<'a, 'b, A: flatbuffers::Allocator + 'a>(fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>, root: flatbuffers::WIPOffset<ColumnBatch<'a>>) {
+  fbb.finish_size_prefixed(root, None);
+}
+}  // pub mod taosx
@@ -1,15 +1,36 @@
 use std::{
+    collections::HashMap,
     fs::{self, File},
     io::{BufReader, Write},
     path::Path,
 };
 
 use avro_rs::{
+    from_avro_datum,
+    schema_compatibility::SchemaCompatibility,
+    to_avro_datum,
     types::{Record, Value},
     Codec, Reader, Schema, Writer,
 };
-use libtaos::Field;
+use bstr::BString;
+use libtaos::{Field, Timestamp, TimestampPrecision};
 use serde_json::{self, json, Map};
+
+use crate::avro_index::{avro_index_filename, avro_index_serialize, avro_serialize_indexed};
+
+fn avro_type(data_type: &str) -> &'static str {
+    match data_type {
+        "tinyint" | "utinyint" | "smallint" | "usmallint" | "int" => "int",
+        "uint" | "bigint" | "timestamp" | "ubigint" => "long",
+        "bool" => "boolean",
+        "float" => "float",
+        "double" => "double",
+        "binary" => "bytes",
+        "nchar" | "json" => "string",
+        _ => unreachable!("unexpected data type, please contact the author to fix!"),
+    }
+}
+
 pub fn generate_avro_schema(data_types: &Vec<&str>) -> Schema {
     let mut raw_json_schema = Map::new();
     raw_json_schema.insert(
@@ -21,17 +42,9 @@ pub fn generate_avro_schema(data_types: &Vec<&str>) -> Schema {
         serde_json::Value::String("m1".to_string()),
     );
     let mut field_json_array: Vec<serde_json::Value> = vec![];
+    // every column takes nulls, as every column of a table but its timestamp does
     for data_type in data_types {
-        let column = json!({ "name": *data_type, "type": match *data_type {
-            "tinyint" | "utinyint" | "smallint" | "usmallint" | "int" => "int",
-            "uint" | "bigint" | "timestamp" | "ubigint" => "long",
-            "bool" => "boolean",
-            "float" => "float",
-            "double" => "double",
-            "binary" => "bytes",
-            "nchar" => "string",
-            _ => unreachable!("unexpected data type, please contact the author to fix!"),
-        }});
+        let column = json!({ "name": *data_type, "type": ["null", avro_type(data_type)] });
         field_json_array.push(column);
     }
     raw_json_schema.insert(
@@ -42,80 +55,195 @@ pub fn generate_avro_schema(data_types: &Vec<&str>) -> Schema {
     Schema::parse_str(serde_json::to_string(&raw_json_schema).unwrap().as_str()).unwrap()
 }
 
+// The container header, which avro-rs only writes along with the first record, for files
+// that have none.
+pub fn avro_header(schema: &Schema, compression: Codec) -> Vec<u8> {
+    let schema = serde_json::to_string(schema).unwrap().into_bytes();
+    let meta = HashMap::from([
+        ("avro.schema".to_string(), Value::Bytes(schema)),
+        ("avro.codec".to_string(), compression.into()),
+    ]);
+    let mut header = b"Obj\x01".to_vec();
+    header.extend(to_avro_datum(&Schema::Map(Box::new(Schema::Bytes)), Value::Map(meta)).unwrap());
+    header.extend(rand::random::<[u8; 16]>());
+    header
+}
+
+pub fn avro_record<'a>(schema: &'a Schema, data_types: &[&str], row: &[Field]) -> Record<'a> {
+    let mut record = Record::new(schema).unwrap();
+    for (index, field) in row.iter().enumerate() {
+        let value = match field.clone() {
+            libtaos::Field::Null => Value::Null,
+            libtaos::Field::Bool(v) => Value::Boolean(v),
+            libtaos::Field::TinyInt(v) => Value::Int(v as i32),
+            libtaos::Field::SmallInt(v) => Value::Int(v as i32),
+            libtaos::Field::Int(v) => Value::Int(v),
+            libtaos::Field::BigInt(v) => Value::Long(v),
+            libtaos::Field::Float(v) => Value::Float(v),
+            libtaos::Field::Double(v) => Value::Double(v),
+            libtaos::Field::Binary(v) => Value::Bytes(v.to_vec()),
+            libtaos::Field::Timestamp(v) => Value::Long(v.as_raw_timestamp()),
+            libtaos::Field::NChar(v) => Value::String(v),
+            libtaos::Field::UTinyInt(v) => Value::Int(v as i32),
+            libtaos::Field::USmallInt(v) => Value::Int(v as i32),
+            libtaos::Field::UInt(v) => Value::Long(v as i64),
+            libtaos::Field::UBigInt(v) => Value::Long(v as i64),
+            libtaos::Field::Json(v) => Value::String(v.to_string()),
+        };
+        record.put(data_types[index], Value::Union(Box::new(value)));
+    }
+    record
+}
+
 pub fn avro_serialize(data_types: &Vec<&str>, rows: &Vec<Vec<Field>>, compression: Codec) {
+    avro_container_serialize(data_types, rows, compression);
+}
+
+// A whole container file, header and all, in memory.
+pub fn avro_container_serialize(
+    data_types: &Vec<&str>,
+    rows: &Vec<Vec<Field>>,
+    compression: Codec,
+) -> Vec<u8> {
     let schema = generate_avro_schema(data_types);
     let mut writer = Writer::with_codec(&schema, Vec::new(), compression);
     for row in rows {
-        let mut record = Record::new(writer.schema()).unwrap();
-        let mut index = 0;
-        for field in row {
-            let field = (*field).clone();
-            match field {
-                libtaos::Field::Null => todo!(),
-                libtaos::Field::Bool(v) => record.put(data_types[index], v),
-                libtaos::Field::TinyInt(v) => record.put(data_types[index], v as i32),
-                libtaos::Field::SmallInt(v) => record.put(data_types[index], v as i32),
-                libtaos::Field::Int(v) => record.put(data_types[index], v),
-                libtaos::Field::BigInt(v) => record.put(data_types[index], v),
-                libtaos::Field::Float(v) => record.put(data_types[index], v),
-                libtaos::Field::Double(v) => record.put(data_types[index], v),
-                libtaos::Field::Binary(v) => record.put(data_types[index], (&*v).to_vec()),
-                libtaos::Field::Timestamp(v) => {
-                    record.put(data_types[index], (v).as_raw_timestamp())
-                }
-                libtaos::Field::NChar(v) => record.put(data_types[index], (v).clone()),
-                libtaos::Field::UTinyInt(v) => record.put(data_types[index], v as i32),
-                libtaos::Field::USmallInt(v) => record.put(data_types[index], v as i32),
-                libtaos::Field::UInt(v) => record.put(data_types[index], v as i64),
-                libtaos::Field::UBigInt(v) => record.put(data_types[index], v as i64),
-            };
-            index += 1;
-        }
+        let record = avro_record(writer.schema(), data_types, row);
         writer.append(record).unwrap();
     }
+    writer.into_inner().unwrap()
 }
 
+// The rows as bare datums one after another, without the container's header and sync
+// markers, for when the schema is known to both sides.
+pub fn avro_datum_serialize(data_types: &Vec<&str>, rows: &[Vec<Field>]) -> Vec<u8> {
+    let schema = generate_avro_schema(data_types);
+    let mut buf = vec![];
+    for row in rows {
+        buf.extend(to_avro_datum(&schema, avro_record(&schema, data_types, row)).unwrap());
+    }
+    buf
+}
+
+pub fn avro_datum_deserialize(data_types: &Vec<&str>, mut buf: &[u8]) -> Vec<Vec<Field>> {
+    let schema = generate_avro_schema(data_types);
+    let mut rows = vec![];
+    while !buf.is_empty() {
+        rows.push(avro_record_to_row(
+            from_avro_datum(&schema, &mut buf, None).unwrap(),
+        ));
+    }
+    rows
+}
+
+// With `block_rows`, a block is written every `block_rows` rows and the index of the
+// blocks next to the file, as `avro_index` lays it out. Without it, avro-rs decides
+// where blocks end and there is no index.
 pub fn avro_serialized_write(
     filename: &str,
     data_types: &Vec<&str>,
     rows: &Vec<Vec<Field>>,
     compression: Codec,
+    block_rows: Option<usize>,
 ) {
-    fs::remove_file(filename).unwrap();
+    fs::remove_file(filename).ok();
     let path = Path::new(filename);
-    let mut file = fs::File::create(&path).unwrap();
-    let schema = generate_avro_schema(data_types);
-    let mut writer = Writer::with_codec(&schema, Vec::new(), compression);
-    for row in rows {
-        let mut record = Record::new(writer.schema()).unwrap();
-        let mut index = 0;
-        for field in row {
-            let field = (*field).clone();
-            match field {
-                libtaos::Field::Null => todo!(),
-                libtaos::Field::Bool(v) => record.put(data_types[index], v),
-                libtaos::Field::TinyInt(v) => record.put(data_types[index], v as i32),
-                libtaos::Field::SmallInt(v) => record.put(data_types[index], v as i32),
-                libtaos::Field::Int(v) => record.put(data_types[index], v),
-                libtaos::Field::BigInt(v) => record.put(data_types[index], v),
-                libtaos::Field::Float(v) => record.put(data_types[index], v),
-                libtaos::Field::Double(v) => record.put(data_types[index], v),
-                libtaos::Field::Binary(v) => record.put(data_types[index], (&*v).to_vec()),
-                libtaos::Field::Timestamp(v) => {
-                    record.put(data_types[index], (v).as_raw_timestamp())
-                }
-                libtaos::Field::NChar(v) => record.put(data_types[index], (v).clone()),
-                libtaos::Field::UTinyInt(v) => record.put(data_types[index], v as i32),
-                libtaos::Field::USmallInt(v) => record.put(data_types[index], v as i32),
-                libtaos::Field::UInt(v) => record.put(data_types[index], v as i64),
-                libtaos::Field::UBigInt(v) => record.put(data_types[index], v as i64),
-            };
-            index += 1;
+    let mut file = fs::File::create(path).unwrap();
+    let input = match block_rows {
+        Some(block_rows) => {
+            let (buf, blocks) = avro_serialize_indexed(data_types, rows, compression, block_rows);
+            fs::write(avro_index_filename(filename), avro_index_serialize(&blocks)).unwrap();
+            buf
         }
-        writer.append(record).unwrap();
+        None => {
+            // an index left from an earlier write would no longer match
+            fs::remove_file(avro_index_filename(filename)).ok();
+            let schema = generate_avro_schema(data_types);
+            let mut writer = Writer::with_codec(&schema, Vec::new(), compression);
+            for row in rows {
+                let record = avro_record(writer.schema(), data_types, row);
+                writer.append(record).unwrap();
+            }
+            writer.into_inner().unwrap()
+        }
+    };
+    file.write_all(&input).unwrap();
+}
+
+// A container file that stays open for batches as they arrive. avro-rs buffers rows and
+// writes a block whenever they pass `block_bytes`, so a reader sees every block written
+// so far but nothing of a block still being filled until `close`.
+pub struct AvroStreamWriter<'a> {
+    writer: Writer<'a, File>,
+    data_types: Vec<&'a str>,
+    compression: Codec,
+    rows: u64,
+    bytes: u64,
+}
+
+impl<'a> AvroStreamWriter<'a> {
+    // `schema` is the one `generate_avro_schema` makes for `data_types`, borrowed because
+    // the avro-rs writer is.
+    pub fn new(
+        filename: &str,
+        schema: &'a Schema,
+        data_types: &[&'a str],
+        compression: Codec,
+        block_bytes: usize,
+    ) -> Self {
+        let writer = Writer::builder()
+            .schema(schema)
+            .writer(File::create(filename).unwrap())
+            .codec(compression)
+            .block_size(block_bytes)
+            .build();
+        Self {
+            writer,
+            data_types: data_types.to_vec(),
+            compression,
+            rows: 0,
+            bytes: 0,
+        }
+    }
+
+    pub fn append_batch(&mut self, rows: &[Vec<Field>]) {
+        let schema = self.writer.schema();
+        for row in rows {
+            let record = avro_record(schema, &self.data_types, row);
+            self.bytes += self.writer.append(record).unwrap() as u64;
+        }
+        self.rows += rows.len() as u64;
+    }
+
+    // Rows appended so far, written or buffered.
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    // Bytes of the header and blocks written so far, not counting buffered rows.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    // Writes the buffered rows as a last block, returning the number of rows in the file.
+    // avro-rs writes the header along with the first row, so with no rows it is written
+    // here.
+    pub fn close(self) -> u64 {
+        let schema = self.writer.schema();
+        let mut file = self.writer.into_inner().unwrap();
+        if self.rows == 0 {
+            file.write_all(&avro_header(schema, self.compression))
+                .unwrap();
+        }
+        self.rows
     }
-    let input = writer.into_inner().unwrap();
-    file.write(&input).unwrap();
+}
+
+pub fn avro_deserialize(buf: &[u8]) -> Vec<Vec<Field>> {
+    Reader::new(buf)
+        .unwrap()
+        .map(|value| avro_record_to_row(value.unwrap()))
+        .collect()
 }
 
 pub fn avro_read(filename: &str) -> u32 {
@@ -136,3 +264,182 @@ pub fn avro_read(filename: &str) -> u32 {
     }
     count
 }
+
+// Columns are named after their data types, so a decoded record maps back by name.
+pub fn avro_value_to_field(data_type: &str, value: Value) -> Field {
+    match (data_type, value) {
+        (_, Value::Null) => Field::Null,
+        (data_type, Value::Union(v)) => avro_value_to_field(data_type, *v),
+        ("bool", Value::Boolean(v)) => Field::Bool(v),
+        ("tinyint", Value::Int(v)) => Field::TinyInt(v as i8),
+        ("smallint", Value::Int(v)) => Field::SmallInt(v as i16),
+        ("int", Value::Int(v)) => Field::Int(v),
+        ("bigint", Value::Long(v)) => Field::BigInt(v),
+        ("float", Value::Float(v)) => Field::Float(v),
+        ("double", Value::Double(v)) => Field::Double(v),
+        ("binary", Value::Bytes(v)) => Field::Binary(BString::from(v)),
+        ("timestamp", Value::Long(v)) => {
+            Field::Timestamp(Timestamp::new(v, TimestampPrecision::Milli))
+        }
+        ("nchar", Value::String(v)) => Field::NChar(v),
+        ("json", Value::String(v)) => Field::Json(serde_json::from_str(&v).unwrap()),
+        ("utinyint", Value::Int(v)) => Field::UTinyInt(v as u8),
+        ("usmallint", Value::Int(v)) => Field::USmallInt(v as u16),
+        ("uint", Value::Long(v)) => Field::UInt(v as u32),
+        ("ubigint", Value::Long(v)) => Field::UBigInt(v as u64),
+        // promotions, for files written with a narrower type
+        ("bigint", Value::Int(v)) => Field::BigInt(v as i64),
+        ("float", Value::Int(v)) => Field::Float(v as f32),
+        ("float", Value::Long(v)) => Field::Float(v as f32),
+        ("double", Value::Int(v)) => Field::Double(v as f64),
+        ("double", Value::Long(v)) => Field::Double(v as f64),
+        ("double", Value::Float(v)) => Field::Double(v as f64),
+        ("binary", Value::String(v)) => Field::Binary(BString::from(v)),
+        ("nchar", Value::Bytes(v)) => Field::NChar(String::from_utf8(v).unwrap()),
+        (data_type, value) => unreachable!("unexpected {:?} for {}", value, data_type),
+    }
+}
+
+pub fn avro_record_to_row(value: Value) -> Vec<Field> {
+    match value {
+        Value::Record(fields) => fields
+            .into_iter()
+            .map(|(name, value)| avro_value_to_field(&name, value))
+            .collect(),
+        _ => unreachable!(),
+    }
+}
+
+// A column of a reader schema, matched to the writer's column of the same `name`, which
+// for files written here is the writer's data type. Like the writer's, every column
+// takes nulls. A column the writer lacks takes `default`.
+pub struct AvroColumn<'a> {
+    pub name: &'a str,
+    pub data_type: &'a str,
+    pub default: Option<Field>,
+}
+
+fn avro_default(field: &Field) -> serde_json::Value {
+    match field {
+        Field::Null => serde_json::Value::Null,
+        Field::Bool(v) => json!(v),
+        Field::TinyInt(v) => json!(v),
+        Field::SmallInt(v) => json!(v),
+        Field::Int(v) => json!(v),
+        Field::BigInt(v) => json!(v),
+        Field::Float(v) => json!(v),
+        Field::Double(v) => json!(v),
+        // Avro takes a bytes default as a string of code points 0 to 255, one per byte
+        Field::Binary(v) => json!(v.iter().map(|&b| b as char).collect::<String>()),
+        Field::Timestamp(v) => json!(v.as_raw_timestamp()),
+        Field::NChar(v) => json!(v),
+        Field::UTinyInt(v) => json!(v),
+        Field::USmallInt(v) => json!(v),
+        Field::UInt(v) => json!(v),
+        Field::UBigInt(v) => json!(*v as i64),
+        Field::Json(v) => json!(v.to_string()),
+    }
+}
+
+fn avro_reader_field(column: &AvroColumn) -> serde_json::Value {
+    match &column.default {
+        None => json!({ "name": column.name, "type": ["null", avro_type(column.data_type)] }),
+        Some(Field::Null) => json!({
+            "name": column.name,
+            "type": ["null", avro_type(column.data_type)],
+            "default": null,
+        }),
+        // a union's default is of its first type
+        Some(default) => json!({
+            "name": column.name,
+            "type": [avro_type(column.data_type), "null"],
+            "default": avro_default(default),
+        }),
+    }
+}
+
+pub fn generate_avro_reader_schema(columns: &[AvroColumn]) -> Schema {
+    let fields: Vec<serde_json::Value> = columns.iter().map(avro_reader_field).collect();
+    let schema = json!({ "type": "record", "name": "m1", "fields": fields });
+    Schema::parse_str(&schema.to_string()).unwrap()
+}
+
+// Reads a file written with an older or newer version of the table: columns are matched
+// by name, dropped columns are skipped and promotions like int to long or float to
+// double are applied. Panics if the file cannot be read with `columns`, rather than
+// letting avro-rs narrow a long to an int.
+pub fn avro_read_with_schema(filename: &str, columns: &[AvroColumn]) -> Vec<Vec<Field>> {
+    let open = || BufReader::new(File::open(filename).unwrap());
+    let writer_schema = Reader::new(open()).unwrap().writer_schema().clone();
+    assert!(
+        SchemaCompatibility::can_read(&writer_schema, &generate_avro_reader_schema(columns)),
+        "{} cannot be read with the reader schema",
+        filename
+    );
+    let (name, writer_fields) = match writer_schema {
+        Schema::Record { name, fields, .. } => (name.fullname(None), fields),
+        _ => unreachable!(),
+    };
+    // avro-rs promotes no value inside a union, which every column is, so the columns
+    // the writer has are resolved as it wrote them and promoted by `avro_value_to_field`.
+    let fields: Vec<serde_json::Value> = columns
+        .iter()
+        .map(
+            |column| match writer_fields.iter().find(|field| field.name == column.name) {
+                Some(field) => json!({ "name": column.name, "type": field.schema }),
+                None => avro_reader_field(column),
+            },
+        )
+        .collect();
+    let schema = json!({ "type": "record", "name": name, "fields": fields });
+    let schema = Schema::parse_str(&schema.to_string()).unwrap();
+    // Columns the writer lacks are their default as given, as avro-rs reads a bytes
+    // default as the UTF-8 of the string rather than one byte per code point.
+    let written: Vec<bool> = columns
+        .iter()
+        .map(|column| writer_fields.iter().any(|field| field.name == column.name))
+        .collect();
+    Reader::with_schema(&schema, open())
+        .unwrap()
+        .map(|value| match value.unwrap() {
+            Value::Record(fields) => fields
+                .into_iter()
+                .zip(columns)
+                .zip(&written)
+                .map(
+                    |(((_, value), column), written)| match (written, &column.default) {
+                        (false, Some(default)) => default.clone(),
+                        _ => avro_value_to_field(column.data_type, value),
+                    },
+                )
+                .collect(),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+// Reads just `columns` of a container file through a reader schema of those columns, the
+// writer's types kept, which avro-rs resolves each record against.
+pub fn avro_read_projected(filename: &str, columns: &[&str]) -> Vec<Vec<Field>> {
+    let open = || BufReader::new(File::open(filename).unwrap());
+    let (name, fields) = match Reader::new(open()).unwrap().writer_schema() {
+        Schema::Record { name, fields, .. } => (name.fullname(None), fields.clone()),
+        schema => panic!("{} holds {:?}, not records", filename, schema),
+    };
+    let fields: Vec<serde_json::Value> = columns
+        .iter()
+        .map(|column| {
+            let field = fields
+                .iter()
+                .find(|field| field.name == *column)
+                .unwrap_or_else(|| panic!("{} has no column {}", filename, column));
+            json!({ "name": field.name, "type": field.schema })
+        })
+        .collect();
+    let schema = json!({ "type": "record", "name": name, "fields": fields });
+    let schema = Schema::parse_str(&schema.to_string()).unwrap();
+    Reader::with_schema(&schema, open())
+        .unwrap()
+        .map(|value| avro_record_to_row(value.unwrap()))
+        .collect()
+}
@@ -0,0 +1,238 @@
+use std::{
+    convert::TryInto,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+
+use avro_rs::{Codec, Reader, Schema, Writer};
+use libtaos::Field;
+
+use crate::avro::{avro_header, avro_record, avro_record_to_row, generate_avro_schema};
+
+// An Avro container file as `avro_serialized_write` writes it given `block_rows`: a
+// block every `block_rows` rows and a sidecar index, `<filename>.idx`, of where each
+// block starts:
+//
+// | block count: u32 | offset: u64 | rows: u32 | min ts: i64 | max ts: i64 | ...
+//
+// The first block's offset is also the length of the container header, which holds the
+// schema, codec and sync marker, so a single block reads back as the header followed by
+// that block: a container of its own.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AvroBlock {
+    pub offset: u64,
+    pub rows: u32,
+    pub min_ts: i64,
+    pub max_ts: i64,
+}
+
+pub fn avro_index_filename(filename: &str) -> String {
+    format!("{}.idx", filename)
+}
+
+fn timestamp(field: &Field) -> i64 {
+    match field {
+        Field::Timestamp(v) => v.as_raw_timestamp(),
+        _ => unreachable!(),
+    }
+}
+
+// At least the length of `row` as an Avro datum: varints take up to 5 or 10 bytes, and
+// every field starts with its union branch.
+fn encoded_len_bound(row: &[Field]) -> usize {
+    row.iter()
+        .map(|field| match field {
+            Field::Bool(_) => 1,
+            Field::Float(_) => 4,
+            Field::Double(_) => 8,
+            Field::TinyInt(_)
+            | Field::SmallInt(_)
+            | Field::Int(_)
+            | Field::UTinyInt(_)
+            | Field::USmallInt(_) => 5,
+            Field::Binary(v) => 10 + v.len(),
+            Field::NChar(v) => 10 + v.len(),
+            _ => 10,
+        } + 1)
+        .sum()
+}
+
+pub fn avro_serialize_indexed(
+    data_types: &Vec<&str>,
+    rows: &[Vec<Field>],
+    compression: Codec,
+    block_rows: usize,
+) -> (Vec<u8>, Vec<AvroBlock>) {
+    assert!(block_rows > 0, "block_rows must be at least 1");
+    let ts = data_types.iter().position(|t| *t == "timestamp").unwrap();
+    let schema = generate_avro_schema(data_types);
+    if rows.is_empty() {
+        return (avro_header(&schema, compression), vec![]);
+    }
+    // blocks are only flushed here, never by avro-rs for growing past its block size
+    let block_size = rows
+        .chunks(block_rows)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|row| encoded_len_bound(row))
+                .sum::<usize>()
+        })
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut writer = Writer::builder()
+        .schema(&schema)
+        .writer(vec![])
+        .codec(compression)
+        .block_size(block_size)
+        .build();
+    let mut blocks = vec![];
+    let mut offset = 0;
+    for chunk in rows.chunks(block_rows) {
+        for row in chunk {
+            offset += writer
+                .append(avro_record(&schema, data_types, row))
+                .unwrap() as u64;
+        }
+        let timestamps = chunk.iter().map(|row| timestamp(&row[ts]));
+        blocks.push(AvroBlock {
+            offset,
+            rows: chunk.len() as u32,
+            min_ts: timestamps.clone().min().unwrap(),
+            max_ts: timestamps.max().unwrap(),
+        });
+        offset += writer.flush().unwrap() as u64;
+    }
+    (writer.into_inner().unwrap(), blocks)
+}
+
+pub fn avro_index_serialize(blocks: &[AvroBlock]) -> Vec<u8> {
+    let mut buf = (blocks.len() as u32).to_le_bytes().to_vec();
+    for block in blocks {
+        buf.extend_from_slice(&block.offset.to_le_bytes());
+        buf.extend_from_slice(&block.rows.to_le_bytes());
+        buf.extend_from_slice(&block.min_ts.to_le_bytes());
+        buf.extend_from_slice(&block.max_ts.to_le_bytes());
+    }
+    buf
+}
+
+pub fn avro_index_deserialize(buf: &[u8]) -> Vec<AvroBlock> {
+    let count = u32::from_le_bytes(buf[..4].try_into().unwrap()) as usize;
+    buf[4..]
+        .chunks(28)
+        .take(count)
+        .map(|entry| AvroBlock {
+            offset: u64::from_le_bytes(entry[..8].try_into().unwrap()),
+            rows: u32::from_le_bytes(entry[8..12].try_into().unwrap()),
+            min_ts: i64::from_le_bytes(entry[12..20].try_into().unwrap()),
+            max_ts: i64::from_le_bytes(entry[20..28].try_into().unwrap()),
+        })
+        .collect()
+}
+
+pub struct AvroIndexedReader {
+    file: File,
+    header: Vec<u8>,
+    blocks: Vec<AvroBlock>,
+    // the row number each block starts at
+    first_rows: Vec<u64>,
+    len: u64,
+    ts_column: usize,
+}
+
+impl AvroIndexedReader {
+    pub fn open(filename: &str) -> Self {
+        let mut buf = vec![];
+        File::open(avro_index_filename(filename))
+            .unwrap()
+            .read_to_end(&mut buf)
+            .unwrap();
+        let blocks = avro_index_deserialize(&buf);
+        let mut file = File::open(filename).unwrap();
+        let len = file.metadata().unwrap().len();
+        let mut header = vec![0; blocks.first().map_or(len, |block| block.offset) as usize];
+        file.read_exact(&mut header).unwrap();
+        let ts_column = match Reader::new(header.as_slice()).unwrap().writer_schema() {
+            Schema::Record { lookup, .. } => lookup["timestamp"],
+            _ => unreachable!(),
+        };
+        let first_rows = blocks
+            .iter()
+            .scan(0, |row, block| {
+                let first = *row;
+                *row += block.rows as u64;
+                Some(first)
+            })
+            .collect();
+        Self {
+            file,
+            header,
+            blocks,
+            first_rows,
+            len,
+            ts_column,
+        }
+    }
+
+    pub fn blocks(&self) -> &[AvroBlock] {
+        &self.blocks
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.blocks.iter().map(|block| block.rows as u64).sum()
+    }
+
+    // Blocks are in write order, so if rows were written in time order so are the
+    // blocks, but nothing here relies on it.
+    pub fn blocks_of_timestamp(&self, ts: i64) -> Vec<usize> {
+        (0..self.blocks.len())
+            .filter(|&i| self.blocks[i].min_ts <= ts && ts <= self.blocks[i].max_ts)
+            .collect()
+    }
+
+    pub fn block_of_row(&self, row: u64) -> Option<usize> {
+        if row >= self.rows() {
+            return None;
+        }
+        Some(self.first_rows.partition_point(|&first| first <= row) - 1)
+    }
+
+    pub fn read_block(&mut self, index: usize) -> Vec<Vec<Field>> {
+        let start = self.blocks[index].offset;
+        let end = self
+            .blocks
+            .get(index + 1)
+            .map_or(self.len, |block| block.offset);
+        let mut buf = self.header.clone();
+        buf.resize(self.header.len() + (end - start) as usize, 0);
+        self.file.seek(SeekFrom::Start(start)).unwrap();
+        self.file.read_exact(&mut buf[self.header.len()..]).unwrap();
+        Reader::new(buf.as_slice())
+            .unwrap()
+            .map(|value| avro_record_to_row(value.unwrap()))
+            .collect()
+    }
+
+    pub fn read_row(&mut self, row: u64) -> Option<Vec<Field>> {
+        let index = self.block_of_row(row)?;
+        let offset = (row - self.first_rows[index]) as usize;
+        Some(self.read_block(index).swap_remove(offset))
+    }
+
+    // Rows with timestamp `ts`, reading only the blocks whose range has it.
+    pub fn read_timestamp(&mut self, ts: i64) -> Vec<Vec<Field>> {
+        let column = self.ts_column;
+        let mut rows = vec![];
+        for index in self.blocks_of_timestamp(ts) {
+            rows.extend(
+                self.read_block(index)
+                    .into_iter()
+                    .filter(|row| timestamp(&row[column]) == ts),
+            );
+        }
+        rows
+    }
+}
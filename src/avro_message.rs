@@ -0,0 +1,115 @@
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    fs::File,
+    io::{Read, Write},
+};
+
+use avro_rs::{from_avro_datum, rabin::Rabin, to_avro_datum, Schema};
+use libtaos::Field;
+
+use crate::avro::{avro_record, avro_record_to_row, generate_avro_schema};
+
+// Avro single object encoding, one row per message instead of a container file with
+// the whole schema in its header:
+//
+// | magic: 0xc3 0x01 | fingerprint: u64 | datum |
+//
+// where the fingerprint is the little endian Rabin fingerprint of the schema's parsing
+// canonical form, resolved back to the schema through a `SchemaStore`.
+
+pub const SINGLE_OBJECT_MAGIC: [u8; 2] = [0xc3, 0x01];
+
+pub fn avro_fingerprint(schema: &Schema) -> u64 {
+    u64::from_le_bytes(schema.fingerprint::<Rabin>().bytes.try_into().unwrap())
+}
+
+#[derive(Default)]
+pub struct SchemaStore {
+    schemas: HashMap<u64, Schema>,
+}
+
+impl SchemaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, schema: Schema) -> u64 {
+        let fingerprint = avro_fingerprint(&schema);
+        self.schemas.insert(fingerprint, schema);
+        fingerprint
+    }
+
+    pub fn get(&self, fingerprint: u64) -> Option<&Schema> {
+        self.schemas.get(&fingerprint)
+    }
+}
+
+// `fingerprint` is taken as is so callers compute it once per schema, not per message.
+pub fn avro_single_object_encode(
+    schema: &Schema,
+    fingerprint: u64,
+    data_types: &[&str],
+    row: &[Field],
+) -> Vec<u8> {
+    let mut buf = SINGLE_OBJECT_MAGIC.to_vec();
+    buf.extend_from_slice(&fingerprint.to_le_bytes());
+    buf.extend(to_avro_datum(schema, avro_record(schema, data_types, row)).unwrap());
+    buf
+}
+
+pub fn avro_single_object_decode(store: &SchemaStore, buf: &[u8]) -> Vec<Field> {
+    assert_eq!(buf[..2], SINGLE_OBJECT_MAGIC, "not an avro single object");
+    let fingerprint = u64::from_le_bytes(buf[2..10].try_into().unwrap());
+    let schema = store
+        .get(fingerprint)
+        .unwrap_or_else(|| panic!("unknown schema fingerprint {:016x}", fingerprint));
+    avro_record_to_row(from_avro_datum(schema, &mut &buf[10..], None).unwrap())
+}
+
+pub fn avro_single_object_serialize(data_types: &Vec<&str>, rows: &[Vec<Field>]) -> Vec<Vec<u8>> {
+    let schema = generate_avro_schema(data_types);
+    let fingerprint = avro_fingerprint(&schema);
+    rows.iter()
+        .map(|row| avro_single_object_encode(&schema, fingerprint, data_types, row))
+        .collect()
+}
+
+pub fn avro_single_object_deserialize(
+    store: &SchemaStore,
+    messages: &[Vec<u8>],
+) -> Vec<Vec<Field>> {
+    messages
+        .iter()
+        .map(|message| avro_single_object_decode(store, message))
+        .collect()
+}
+
+// The messages one after another, each as | length: u32 | message |.
+pub fn avro_single_object_serialized_write(
+    filename: &str,
+    data_types: &Vec<&str>,
+    rows: &[Vec<Field>],
+) {
+    let mut buf = vec![];
+    for message in avro_single_object_serialize(data_types, rows) {
+        buf.extend_from_slice(&(message.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&message);
+    }
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&buf).unwrap();
+}
+
+pub fn avro_single_object_read(filename: &str, store: &SchemaStore) -> u32 {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    let mut count = 0;
+    let mut pos = 0;
+    while pos < buf.len() {
+        let len = u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap()) as usize;
+        avro_single_object_decode(store, &buf[pos + 4..pos + 4 + len]);
+        pos += 4 + len;
+        count += 1;
+    }
+    count
+}
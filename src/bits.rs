@@ -0,0 +1,114 @@
+// Bit and varint helpers shared by the hand written column codecs.
+
+#[derive(Default)]
+pub struct BitWriter {
+    buf: Vec<u8>,
+    acc: u64,
+    nbits: u32,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Writes the lowest `n` bits of `value`, most significant first. `n` is at most 64.
+    pub fn write_bits(&mut self, value: u64, n: u32) {
+        if n > 32 {
+            self.write_bits(value >> 32, n - 32);
+            self.write_bits(value & 0xffff_ffff, 32);
+            return;
+        }
+        let value = if n == 64 {
+            value
+        } else {
+            value & ((1 << n) - 1)
+        };
+        self.acc = (self.acc << n) | value;
+        self.nbits += n;
+        while self.nbits >= 8 {
+            self.nbits -= 8;
+            self.buf.push((self.acc >> self.nbits) as u8);
+        }
+        self.acc &= (1 << self.nbits) - 1;
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        self.write_bits(bit as u64, 1);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.nbits > 0 {
+            self.buf.push((self.acc << (8 - self.nbits)) as u8);
+        }
+        self.buf
+    }
+}
+
+pub struct BitReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    pub fn read_bits(&mut self, n: u32) -> u64 {
+        let mut value = 0;
+        let mut left = n;
+        while left > 0 {
+            let byte = self.buf[self.pos >> 3];
+            let offset = (self.pos & 7) as u32;
+            let take = left.min(8 - offset);
+            let bits = (byte >> (8 - offset - take)) & ((1u16 << take) - 1) as u8;
+            value = (value << take) | bits as u64;
+            left -= take;
+            self.pos += take as usize;
+        }
+        value
+    }
+
+    pub fn read_bit(&mut self) -> bool {
+        self.read_bits(1) == 1
+    }
+}
+
+pub fn zigzag_encode(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+pub fn zigzag_decode(v: u64) -> i64 {
+    ((v >> 1) as i64) ^ -((v & 1) as i64)
+}
+
+pub fn write_uvarint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+pub fn read_uvarint(buf: &[u8], pos: &mut usize) -> u64 {
+    let mut v = 0;
+    let mut shift = 0;
+    loop {
+        let byte = buf[*pos];
+        *pos += 1;
+        v |= ((byte & 0x7f) as u64) << shift;
+        if byte < 0x80 {
+            return v;
+        }
+        shift += 7;
+    }
+}
+
+pub fn write_varint(buf: &mut Vec<u8>, v: i64) {
+    write_uvarint(buf, zigzag_encode(v))
+}
+
+pub fn read_varint(buf: &[u8], pos: &mut usize) -> i64 {
+    zigzag_decode(read_uvarint(buf, pos))
+}
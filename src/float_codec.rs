@@ -0,0 +1,349 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+};
+
+use libtaos::{Field, TaosDataType};
+use parquet::{basic::Compression, compression::create_codec};
+
+use crate::bits::{read_uvarint, write_uvarint, BitReader, BitWriter};
+
+// Gorilla (Pelkonen et al., 2015) and Chimp (Liakos et al., 2022) XOR compression over
+// the bits of f32 and f64 values. Every encoding starts with the value count as a
+// varint followed by the bit stream, the first value stored verbatim.
+
+#[derive(Clone, Copy, Debug)]
+pub enum FloatCodec {
+    Gorilla,
+    Chimp,
+}
+
+fn leading_zeros(xor: u64, width: u32) -> u32 {
+    xor.leading_zeros() - (64 - width)
+}
+
+fn gorilla_encode(bits: impl ExactSizeIterator<Item = u64>, width: u32) -> Vec<u8> {
+    let mut buf = vec![];
+    write_uvarint(&mut buf, bits.len() as u64);
+    let mut writer = BitWriter::new();
+    let mut prev = 0;
+    // no window until the first value that does not repeat
+    let mut window = None;
+    for (index, v) in bits.enumerate() {
+        let xor = v ^ prev;
+        prev = v;
+        if index == 0 {
+            writer.write_bits(v, width);
+            continue;
+        }
+        if xor == 0 {
+            writer.write_bit(false);
+            continue;
+        }
+        writer.write_bit(true);
+        let lead = leading_zeros(xor, width).min(31);
+        let trail = xor.trailing_zeros();
+        match window {
+            Some((prev_lead, prev_trail)) if lead >= prev_lead && trail >= prev_trail => {
+                writer.write_bit(false);
+                writer.write_bits(xor >> prev_trail, width - prev_lead - prev_trail);
+            }
+            _ => {
+                let significant = width - lead - trail;
+                writer.write_bit(true);
+                writer.write_bits(lead as u64, 5);
+                // 1..=64 significant bits stored as 0..=63
+                writer.write_bits(significant as u64 - 1, 6);
+                writer.write_bits(xor >> trail, significant);
+                window = Some((lead, trail));
+            }
+        }
+    }
+    buf.extend(writer.finish());
+    buf
+}
+
+fn gorilla_decode(buf: &[u8], width: u32) -> Vec<u64> {
+    let mut pos = 0;
+    let len = read_uvarint(buf, &mut pos) as usize;
+    let mut reader = BitReader::new(&buf[pos..]);
+    let mut values = Vec::with_capacity(len);
+    let mut prev = 0;
+    let mut lead = 0;
+    let mut trail = 0;
+    for index in 0..len {
+        if index == 0 {
+            prev = reader.read_bits(width);
+        } else if reader.read_bit() {
+            if reader.read_bit() {
+                lead = reader.read_bits(5) as u32;
+                let significant = reader.read_bits(6) as u32 + 1;
+                trail = width - lead - significant;
+            }
+            prev ^= reader.read_bits(width - lead - trail) << trail;
+        }
+        values.push(prev);
+    }
+    values
+}
+
+// Leading zeros are rounded down to one of these and stored as the 3 bit index.
+const CHIMP_LEADING: [u32; 8] = [0, 8, 12, 16, 18, 20, 22, 24];
+
+fn chimp_threshold(width: u32) -> u32 {
+    if width == 64 {
+        6
+    } else {
+        5
+    }
+}
+
+fn chimp_encode(bits: impl ExactSizeIterator<Item = u64>, width: u32) -> Vec<u8> {
+    let mut buf = vec![];
+    write_uvarint(&mut buf, bits.len() as u64);
+    let mut writer = BitWriter::new();
+    let mut prev = 0;
+    let mut prev_lead = None;
+    for (index, v) in bits.enumerate() {
+        let xor = v ^ prev;
+        prev = v;
+        if index == 0 {
+            writer.write_bits(v, width);
+            continue;
+        }
+        if xor == 0 {
+            writer.write_bits(0b00, 2);
+            prev_lead = None;
+            continue;
+        }
+        let code = CHIMP_LEADING
+            .iter()
+            .rposition(|l| *l <= leading_zeros(xor, width))
+            .unwrap();
+        let lead = CHIMP_LEADING[code];
+        let trail = xor.trailing_zeros();
+        if trail > chimp_threshold(width) {
+            let significant = width - lead - trail;
+            writer.write_bits(0b01, 2);
+            writer.write_bits(code as u64, 3);
+            writer.write_bits(significant as u64, 6);
+            writer.write_bits(xor >> trail, significant);
+            prev_lead = None;
+        } else if prev_lead == Some(lead) {
+            writer.write_bits(0b10, 2);
+            writer.write_bits(xor, width - lead);
+        } else {
+            writer.write_bits(0b11, 2);
+            writer.write_bits(code as u64, 3);
+            writer.write_bits(xor, width - lead);
+            prev_lead = Some(lead);
+        }
+    }
+    buf.extend(writer.finish());
+    buf
+}
+
+fn chimp_decode(buf: &[u8], width: u32) -> Vec<u64> {
+    let mut pos = 0;
+    let len = read_uvarint(buf, &mut pos) as usize;
+    let mut reader = BitReader::new(&buf[pos..]);
+    let mut values = Vec::with_capacity(len);
+    let mut prev = 0;
+    let mut lead = 0;
+    for index in 0..len {
+        if index == 0 {
+            prev = reader.read_bits(width);
+            values.push(prev);
+            continue;
+        }
+        match reader.read_bits(2) {
+            0b00 => {}
+            0b01 => {
+                let lead = CHIMP_LEADING[reader.read_bits(3) as usize];
+                let significant = reader.read_bits(6) as u32;
+                let trail = width - lead - significant;
+                prev ^= reader.read_bits(significant) << trail;
+            }
+            0b10 => prev ^= reader.read_bits(width - lead),
+            _ => {
+                lead = CHIMP_LEADING[reader.read_bits(3) as usize];
+                prev ^= reader.read_bits(width - lead);
+            }
+        }
+        values.push(prev);
+    }
+    values
+}
+
+pub fn gorilla_encode_f32(values: &[f32]) -> Vec<u8> {
+    gorilla_encode(values.iter().map(|v| v.to_bits() as u64), 32)
+}
+
+pub fn gorilla_decode_f32(buf: &[u8]) -> Vec<f32> {
+    gorilla_decode(buf, 32)
+        .into_iter()
+        .map(|v| f32::from_bits(v as u32))
+        .collect()
+}
+
+pub fn gorilla_encode_f64(values: &[f64]) -> Vec<u8> {
+    gorilla_encode(values.iter().map(|v| v.to_bits()), 64)
+}
+
+pub fn gorilla_decode_f64(buf: &[u8]) -> Vec<f64> {
+    gorilla_decode(buf, 64)
+        .into_iter()
+        .map(f64::from_bits)
+        .collect()
+}
+
+pub fn chimp_encode_f32(values: &[f32]) -> Vec<u8> {
+    chimp_encode(values.iter().map(|v| v.to_bits() as u64), 32)
+}
+
+pub fn chimp_decode_f32(buf: &[u8]) -> Vec<f32> {
+    chimp_decode(buf, 32)
+        .into_iter()
+        .map(|v| f32::from_bits(v as u32))
+        .collect()
+}
+
+pub fn chimp_encode_f64(values: &[f64]) -> Vec<u8> {
+    chimp_encode(values.iter().map(|v| v.to_bits()), 64)
+}
+
+pub fn chimp_decode_f64(buf: &[u8]) -> Vec<f64> {
+    chimp_decode(buf, 64)
+        .into_iter()
+        .map(f64::from_bits)
+        .collect()
+}
+
+// A float or double column as a type byte and the codec's encoding of its values.
+pub fn float_column_encode(col: &[Field], codec: FloatCodec) -> Vec<u8> {
+    let data_type = col.first().map_or(TaosDataType::Double, Field::data_type);
+    let mut buf = vec![data_type as u8];
+    let encoded = match data_type {
+        TaosDataType::Float => {
+            let values: Vec<_> = col
+                .iter()
+                .map(|field| match field {
+                    Field::Float(v) => *v,
+                    _ => unreachable!(),
+                })
+                .collect();
+            match codec {
+                FloatCodec::Gorilla => gorilla_encode_f32(&values),
+                FloatCodec::Chimp => chimp_encode_f32(&values),
+            }
+        }
+        TaosDataType::Double => {
+            let values: Vec<_> = col
+                .iter()
+                .map(|field| match field {
+                    Field::Double(v) => *v,
+                    _ => unreachable!(),
+                })
+                .collect();
+            match codec {
+                FloatCodec::Gorilla => gorilla_encode_f64(&values),
+                FloatCodec::Chimp => chimp_encode_f64(&values),
+            }
+        }
+        _ => unreachable!(),
+    };
+    buf.extend(encoded);
+    buf
+}
+
+pub fn float_column_decode(buf: &[u8], codec: FloatCodec) -> Vec<Field> {
+    match (TaosDataType::from(buf[0]), codec) {
+        (TaosDataType::Float, FloatCodec::Gorilla) => gorilla_decode_f32(&buf[1..])
+            .into_iter()
+            .map(Field::Float)
+            .collect(),
+        (TaosDataType::Float, FloatCodec::Chimp) => chimp_decode_f32(&buf[1..])
+            .into_iter()
+            .map(Field::Float)
+            .collect(),
+        (TaosDataType::Double, FloatCodec::Gorilla) => gorilla_decode_f64(&buf[1..])
+            .into_iter()
+            .map(Field::Double)
+            .collect(),
+        (TaosDataType::Double, FloatCodec::Chimp) => chimp_decode_f64(&buf[1..])
+            .into_iter()
+            .map(Field::Double)
+            .collect(),
+        _ => unreachable!(),
+    }
+}
+
+// The column encoding followed by one of parquet's general purpose codecs.
+pub fn float_column_serialize(
+    col: &[Field],
+    codec: FloatCodec,
+    compression: Compression,
+) -> Vec<u8> {
+    let buf = float_column_encode(col, codec);
+    match create_codec(compression).unwrap() {
+        Some(mut compressor) => {
+            let mut compressed = vec![];
+            compressor.compress(&buf, &mut compressed).unwrap();
+            compressed
+        }
+        None => buf,
+    }
+}
+
+pub fn float_column_deserialize(
+    buf: &[u8],
+    codec: FloatCodec,
+    compression: Compression,
+) -> Vec<Field> {
+    match create_codec(compression).unwrap() {
+        Some(mut compressor) => {
+            let mut decompressed = vec![];
+            compressor.decompress(buf, &mut decompressed).unwrap();
+            float_column_decode(&decompressed, codec)
+        }
+        None => float_column_decode(buf, codec),
+    }
+}
+
+// Same with one of avro's codecs instead.
+pub fn float_column_avro_serialize(
+    col: &[Field],
+    codec: FloatCodec,
+    avro: avro_rs::Codec,
+) -> Vec<u8> {
+    let mut buf = float_column_encode(col, codec);
+    avro.compress(&mut buf).unwrap();
+    buf
+}
+
+pub fn float_column_avro_deserialize(
+    buf: &[u8],
+    codec: FloatCodec,
+    avro: avro_rs::Codec,
+) -> Vec<Field> {
+    let mut buf = buf.to_vec();
+    avro.decompress(&mut buf).unwrap();
+    float_column_decode(&buf, codec)
+}
+
+pub fn float_column_serialized_write(
+    filename: &str,
+    col: &[Field],
+    codec: FloatCodec,
+    compression: Compression,
+) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&float_column_serialize(col, codec, compression))
+        .unwrap();
+}
+
+pub fn float_column_read(filename: &str, codec: FloatCodec, compression: Compression) -> u32 {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    float_column_deserialize(&buf, codec, compression).len() as u32
+}
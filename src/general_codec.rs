@@ -0,0 +1,159 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{Read, Write},
+};
+
+use libtaos::{Field, TaosDataType};
+
+use crate::raw_block::{encode_fixed_value, type_bytes};
+
+// General purpose codecs applied directly to a plain buffer, without any container, as
+// a baseline for how much of a format's size comes from its codec.
+
+#[derive(Clone, Copy, Debug)]
+pub enum GeneralCodec {
+    Snappy,
+    // 0 for LZ4's default fast mode, otherwise the LZ4 HC level
+    Lz4(i32),
+    Zstd(i32),
+    Gzip(u32),
+    Brotli(u32),
+}
+
+impl fmt::Display for GeneralCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneralCodec::Snappy => write!(f, "SNAPPY"),
+            GeneralCodec::Lz4(0) => write!(f, "LZ4"),
+            GeneralCodec::Lz4(level) => write!(f, "LZ4HC-{}", level),
+            GeneralCodec::Zstd(level) => write!(f, "ZSTD-{}", level),
+            GeneralCodec::Gzip(level) => write!(f, "GZIP-{}", level),
+            GeneralCodec::Brotli(level) => write!(f, "BROTLI-{}", level),
+        }
+    }
+}
+
+// The codecs and levels the benchmarks compare.
+pub fn general_codecs() -> Vec<GeneralCodec> {
+    vec![
+        GeneralCodec::Snappy,
+        GeneralCodec::Lz4(0),
+        GeneralCodec::Lz4(9),
+        GeneralCodec::Zstd(1),
+        GeneralCodec::Zstd(3),
+        GeneralCodec::Zstd(9),
+        GeneralCodec::Zstd(19),
+        GeneralCodec::Gzip(1),
+        GeneralCodec::Gzip(6),
+        GeneralCodec::Gzip(9),
+        GeneralCodec::Brotli(1),
+        GeneralCodec::Brotli(5),
+        GeneralCodec::Brotli(11),
+    ]
+}
+
+impl GeneralCodec {
+    pub fn compress(self, buf: &[u8]) -> Vec<u8> {
+        match self {
+            GeneralCodec::Snappy => snap::raw::Encoder::new().compress_vec(buf).unwrap(),
+            GeneralCodec::Lz4(level) => {
+                let mode = match level {
+                    0 => lz4::block::CompressionMode::DEFAULT,
+                    level => lz4::block::CompressionMode::HIGHCOMPRESSION(level),
+                };
+                lz4::block::compress(buf, Some(mode), true).unwrap()
+            }
+            GeneralCodec::Zstd(level) => zstd::bulk::compress(buf, level).unwrap(),
+            GeneralCodec::Gzip(level) => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(vec![], flate2::Compression::new(level));
+                encoder.write_all(buf).unwrap();
+                encoder.finish().unwrap()
+            }
+            GeneralCodec::Brotli(level) => {
+                let mut compressed = vec![];
+                {
+                    let mut writer =
+                        brotli::CompressorWriter::new(&mut compressed, 4096, level, 22);
+                    writer.write_all(buf).unwrap();
+                }
+                compressed
+            }
+        }
+    }
+
+    pub fn decompress(self, buf: &[u8]) -> Vec<u8> {
+        let mut decompressed = vec![];
+        match self {
+            GeneralCodec::Snappy => {
+                decompressed = snap::raw::Decoder::new().decompress_vec(buf).unwrap();
+            }
+            GeneralCodec::Lz4(_) => decompressed = lz4::block::decompress(buf, None).unwrap(),
+            GeneralCodec::Zstd(_) => decompressed = zstd::stream::decode_all(buf).unwrap(),
+            GeneralCodec::Gzip(_) => {
+                flate2::read::GzDecoder::new(buf)
+                    .read_to_end(&mut decompressed)
+                    .unwrap();
+            }
+            GeneralCodec::Brotli(_) => {
+                brotli::Decompressor::new(buf, 4096)
+                    .read_to_end(&mut decompressed)
+                    .unwrap();
+            }
+        }
+        decompressed
+    }
+}
+
+// The columns one after another in plain little endian. Binary and nchar values are
+// `u32 length + bytes` padded to the widest value of their column, like a TDengine
+// binary(n) column but with room for values of 64 KiB and more. Nulls are zeros of the
+// column's width, as a column is typed by its first value that is not null.
+pub fn fixed_width_buffer(cols: &[Vec<Field>]) -> Vec<u8> {
+    let mut buf = vec![];
+    for col in cols {
+        let data_type = col
+            .iter()
+            .find(|field| !matches!(field, Field::Null))
+            .map_or(TaosDataType::Null, Field::data_type);
+        let bytes = type_bytes(data_type) as usize;
+        if bytes > 0 {
+            for field in col {
+                match field {
+                    Field::Null => buf.resize(buf.len() + bytes, 0),
+                    field => encode_fixed_value(field, &mut buf),
+                }
+            }
+            continue;
+        }
+        let values: Vec<&[u8]> = col
+            .iter()
+            .map(|field| match field {
+                Field::Null => &[],
+                Field::Binary(v) => v.as_slice(),
+                Field::NChar(v) => v.as_bytes(),
+                _ => unreachable!(),
+            })
+            .collect();
+        let width = values.iter().map(|v| v.len()).max().unwrap_or(0);
+        for v in values {
+            buf.extend_from_slice(&(v.len() as u32).to_le_bytes());
+            buf.extend_from_slice(v);
+            buf.resize(buf.len() + width - v.len(), 0);
+        }
+    }
+    buf
+}
+
+pub fn general_codec_serialized_write(filename: &str, buf: &[u8], codec: GeneralCodec) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&codec.compress(buf)).unwrap();
+}
+
+// Returns the decompressed length.
+pub fn general_codec_read(filename: &str, codec: GeneralCodec) -> usize {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    codec.decompress(&buf).len()
+}
@@ -0,0 +1,249 @@
+use std::{
+    convert::TryInto,
+    fs::File,
+    io::{Read, Write},
+};
+
+use libtaos::{Field, TaosDataType};
+
+use crate::bits::{
+    read_uvarint, write_uvarint, zigzag_decode, zigzag_encode, BitReader, BitWriter,
+};
+
+// Integer codecs over u64 values, signed values mapped through zigzag (varint and
+// Simple-8b) or by flipping the sign bit (frame of reference, which needs the order
+// kept). Every encoding starts with the value count as a varint.
+
+#[derive(Clone, Copy, Debug)]
+pub enum IntCodec {
+    Varint,
+    Simple8b,
+    FrameOfReference,
+}
+
+pub fn varint_encode(values: &[u64]) -> Vec<u8> {
+    let mut buf = vec![];
+    write_uvarint(&mut buf, values.len() as u64);
+    for v in values {
+        write_uvarint(&mut buf, *v);
+    }
+    buf
+}
+
+pub fn varint_decode(buf: &[u8]) -> Vec<u64> {
+    let mut pos = 0;
+    let len = read_uvarint(buf, &mut pos) as usize;
+    (0..len).map(|_| read_uvarint(buf, &mut pos)).collect()
+}
+
+// (values, bits) per selector. Selector 0 is used as an escape for a single value of
+// more than 60 bits stored in the following word, so the codec covers all of u64.
+const SIMPLE8B_SELECTORS: [(usize, u32); 16] = [
+    (1, 64),
+    (120, 0),
+    (60, 1),
+    (30, 2),
+    (20, 3),
+    (15, 4),
+    (12, 5),
+    (10, 6),
+    (8, 7),
+    (7, 8),
+    (6, 10),
+    (5, 12),
+    (4, 15),
+    (3, 20),
+    (2, 30),
+    (1, 60),
+];
+
+fn fits(v: u64, bits: u32) -> bool {
+    bits == 64 || v >> bits == 0
+}
+
+pub fn simple8b_encode(values: &[u64]) -> Vec<u8> {
+    let mut buf = vec![];
+    write_uvarint(&mut buf, values.len() as u64);
+    let mut pos = 0;
+    while pos < values.len() {
+        // the last word may hold fewer values than its selector, the count tells
+        let selector = (1..16).find(|s| {
+            let (n, bits) = SIMPLE8B_SELECTORS[*s];
+            let end = values.len().min(pos + n);
+            values[pos..end].iter().all(|v| fits(*v, bits))
+        });
+        match selector {
+            Some(selector) => {
+                let (n, bits) = SIMPLE8B_SELECTORS[selector];
+                let end = values.len().min(pos + n);
+                let mut word = (selector as u64) << 60;
+                for (i, v) in values[pos..end].iter().enumerate() {
+                    word |= v << (i as u32 * bits);
+                }
+                buf.extend_from_slice(&word.to_le_bytes());
+                pos = end;
+            }
+            None => {
+                buf.extend_from_slice(&0u64.to_le_bytes());
+                buf.extend_from_slice(&values[pos].to_le_bytes());
+                pos += 1;
+            }
+        }
+    }
+    buf
+}
+
+pub fn simple8b_decode(buf: &[u8]) -> Vec<u64> {
+    let mut pos = 0;
+    let len = read_uvarint(buf, &mut pos) as usize;
+    let mut values = Vec::with_capacity(len);
+    let mut words = buf[pos..]
+        .chunks(8)
+        .map(|w| u64::from_le_bytes(w.try_into().unwrap()));
+    while values.len() < len {
+        let word = words.next().unwrap();
+        let selector = (word >> 60) as usize;
+        if selector == 0 {
+            values.push(words.next().unwrap());
+            continue;
+        }
+        let (n, bits) = SIMPLE8B_SELECTORS[selector];
+        let mask = (1u64 << bits) - 1;
+        for i in 0..n.min(len - values.len()) {
+            values.push((word >> (i as u32 * bits)) & mask);
+        }
+    }
+    values
+}
+
+const FOR_BLOCK_SIZE: usize = 128;
+
+// Blocks of 128 values as | min: varint | bits: u8 | (value - min) bit packed |.
+pub fn frame_of_reference_encode(values: &[u64]) -> Vec<u8> {
+    let mut buf = vec![];
+    write_uvarint(&mut buf, values.len() as u64);
+    for block in values.chunks(FOR_BLOCK_SIZE) {
+        let min = *block.iter().min().unwrap();
+        let max = *block.iter().max().unwrap();
+        let bits = 64 - (max - min).leading_zeros();
+        write_uvarint(&mut buf, min);
+        buf.push(bits as u8);
+        let mut writer = BitWriter::new();
+        for v in block {
+            writer.write_bits(v - min, bits);
+        }
+        buf.extend(writer.finish());
+    }
+    buf
+}
+
+pub fn frame_of_reference_decode(buf: &[u8]) -> Vec<u64> {
+    let mut pos = 0;
+    let len = read_uvarint(buf, &mut pos) as usize;
+    let mut values = Vec::with_capacity(len);
+    while values.len() < len {
+        let n = FOR_BLOCK_SIZE.min(len - values.len());
+        let min = read_uvarint(buf, &mut pos);
+        let bits = buf[pos] as u32;
+        pos += 1;
+        let mut reader = BitReader::new(&buf[pos..]);
+        for _ in 0..n {
+            values.push(min + reader.read_bits(bits));
+        }
+        pos += (n * bits as usize).div_ceil(8);
+    }
+    values
+}
+
+pub fn int_encode(values: &[u64], codec: IntCodec) -> Vec<u8> {
+    match codec {
+        IntCodec::Varint => varint_encode(values),
+        IntCodec::Simple8b => simple8b_encode(values),
+        IntCodec::FrameOfReference => frame_of_reference_encode(values),
+    }
+}
+
+pub fn int_decode(buf: &[u8], codec: IntCodec) -> Vec<u64> {
+    match codec {
+        IntCodec::Varint => varint_decode(buf),
+        IntCodec::Simple8b => simple8b_decode(buf),
+        IntCodec::FrameOfReference => frame_of_reference_decode(buf),
+    }
+}
+
+fn signed_to_u64(v: i64, codec: IntCodec) -> u64 {
+    match codec {
+        IntCodec::FrameOfReference => v as u64 ^ (1 << 63),
+        _ => zigzag_encode(v),
+    }
+}
+
+fn u64_to_signed(v: u64, codec: IntCodec) -> i64 {
+    match codec {
+        IntCodec::FrameOfReference => (v ^ (1 << 63)) as i64,
+        _ => zigzag_decode(v),
+    }
+}
+
+pub fn int_encode_i64(values: &[i64], codec: IntCodec) -> Vec<u8> {
+    let values: Vec<_> = values.iter().map(|v| signed_to_u64(*v, codec)).collect();
+    int_encode(&values, codec)
+}
+
+pub fn int_decode_i64(buf: &[u8], codec: IntCodec) -> Vec<i64> {
+    int_decode(buf, codec)
+        .into_iter()
+        .map(|v| u64_to_signed(v, codec))
+        .collect()
+}
+
+// An integer column from tinyint to ubigint as a type byte and the codec's encoding.
+pub fn int_column_encode(col: &[Field], codec: IntCodec) -> Vec<u8> {
+    let data_type = col.first().map_or(TaosDataType::BigInt, Field::data_type);
+    let values: Vec<_> = col
+        .iter()
+        .map(|field| match field {
+            Field::TinyInt(v) => signed_to_u64(*v as i64, codec),
+            Field::SmallInt(v) => signed_to_u64(*v as i64, codec),
+            Field::Int(v) => signed_to_u64(*v as i64, codec),
+            Field::BigInt(v) => signed_to_u64(*v, codec),
+            Field::UTinyInt(v) => *v as u64,
+            Field::USmallInt(v) => *v as u64,
+            Field::UInt(v) => *v as u64,
+            Field::UBigInt(v) => *v,
+            _ => unreachable!(),
+        })
+        .collect();
+    let mut buf = vec![data_type as u8];
+    buf.extend(int_encode(&values, codec));
+    buf
+}
+
+pub fn int_column_decode(buf: &[u8], codec: IntCodec) -> Vec<Field> {
+    let data_type = TaosDataType::from(buf[0]);
+    int_decode(&buf[1..], codec)
+        .into_iter()
+        .map(|v| match data_type {
+            TaosDataType::TinyInt => Field::TinyInt(u64_to_signed(v, codec) as i8),
+            TaosDataType::SmallInt => Field::SmallInt(u64_to_signed(v, codec) as i16),
+            TaosDataType::Int => Field::Int(u64_to_signed(v, codec) as i32),
+            TaosDataType::BigInt => Field::BigInt(u64_to_signed(v, codec)),
+            TaosDataType::UTinyInt => Field::UTinyInt(v as u8),
+            TaosDataType::USmallInt => Field::USmallInt(v as u16),
+            TaosDataType::UInt => Field::UInt(v as u32),
+            TaosDataType::UBigInt => Field::UBigInt(v),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+pub fn int_column_serialized_write(filename: &str, col: &[Field], codec: IntCodec) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&int_column_encode(col, codec)).unwrap();
+}
+
+pub fn int_column_read(filename: &str, codec: IntCodec) -> u32 {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    int_column_decode(&buf, codec).len() as u32
+}
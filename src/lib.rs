@@ -1,10 +1,66 @@
+use std::str::FromStr;
+
 use bstr::BString;
 use libtaos::{Field, Timestamp, TimestampPrecision};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 
 pub mod avro;
+pub mod avro_index;
+pub mod avro_message;
+pub mod bits;
+pub mod float_codec;
+pub mod general_codec;
+pub mod int_codec;
+pub mod line_protocol;
+pub mod lossy;
+pub mod manifest;
+pub mod my_flatbuffers;
 pub mod my_parquet;
+pub mod my_rkyv;
+pub mod opentsdb;
+pub mod parquet_index;
+pub mod partition;
+pub mod protobuf;
+pub mod raw_block;
+pub mod rolling_sink;
+pub mod schema_registry;
+pub mod sql;
+pub mod string_codec;
+pub mod transform;
+pub mod tscol;
+pub mod zstd_dict;
+
+// A super table over generated data: columns and tags are named after their data
+// types, tags with a `t_` prefix, e.g. `t_int`. Raw timestamps are read in `precision`.
+pub struct SuperTable<'a> {
+    pub name: &'a str,
+    pub data_types: &'a Vec<&'a str>,
+    pub tag_types: &'a Vec<&'a str>,
+    pub precision: TimestampPrecision,
+}
+
+impl SuperTable<'_> {
+    pub fn tag_names(&self) -> Vec<String> {
+        self.tag_types.iter().map(|t| format!("t_{}", t)).collect()
+    }
+}
 
+pub fn generate_tags(tag_types: &Vec<&str>) -> Vec<Field> {
+    generate_data(tag_types, 1).0.remove(0)
+}
+
+pub fn rows_to_cols(rows: &[Vec<Field>]) -> Vec<Vec<Field>> {
+    let mut cols: Vec<Vec<Field>> = vec![];
+    for row in rows {
+        cols.resize(row.len(), vec![]);
+        for (index, field) in row.iter().enumerate() {
+            cols[index].push(field.clone());
+        }
+    }
+    cols
+}
+
+#[allow(clippy::explicit_counter_loop)]
 pub fn generate_data(data_types: &Vec<&str>, size: u32) -> (Vec<Vec<Field>>, Vec<Vec<Field>>) {
     let mut rows: Vec<Vec<Field>> = vec![];
     let mut cols: Vec<Vec<Field>> = vec![];
@@ -106,3 +162,53 @@ pub fn generate_data(data_types: &Vec<&str>, size: u32) -> (Vec<Vec<Field>>, Vec
     }
     (rows, cols)
 }
+
+pub fn millis(raw: i64, precision: TimestampPrecision) -> i64 {
+    match precision {
+        TimestampPrecision::Milli => raw,
+        TimestampPrecision::Micro => raw.div_euclid(1000),
+        TimestampPrecision::Nano => raw.div_euclid(1_000_000),
+    }
+}
+
+// The precision of `ts`, which libtaos keeps to itself.
+pub fn timestamp_precision(ts: &Timestamp) -> TimestampPrecision {
+    [TimestampPrecision::Micro, TimestampPrecision::Nano]
+        .into_iter()
+        .find(|&precision| *ts == Timestamp::new(ts.as_raw_timestamp(), precision))
+        .unwrap_or(TimestampPrecision::Milli)
+}
+
+pub fn parse_field(data_type: &str, text: &str, precision: TimestampPrecision) -> Field {
+    try_parse_field(data_type, text, precision).unwrap()
+}
+
+// `parse_field` for text from outside, saying what did not parse instead of panicking.
+pub fn try_parse_field(
+    data_type: &str,
+    text: &str,
+    precision: TimestampPrecision,
+) -> Result<Field, String> {
+    fn parse<T: FromStr>(data_type: &str, text: &str) -> Result<T, String> {
+        text.parse()
+            .map_err(|_| format!("{:?} is not a valid {}", text, data_type))
+    }
+    Ok(match data_type {
+        "tinyint" => Field::TinyInt(parse(data_type, text)?),
+        "utinyint" => Field::UTinyInt(parse(data_type, text)?),
+        "smallint" => Field::SmallInt(parse(data_type, text)?),
+        "usmallint" => Field::USmallInt(parse(data_type, text)?),
+        "int" => Field::Int(parse(data_type, text)?),
+        "uint" => Field::UInt(parse(data_type, text)?),
+        "bigint" => Field::BigInt(parse(data_type, text)?),
+        "ubigint" => Field::UBigInt(parse(data_type, text)?),
+        "float" => Field::Float(parse(data_type, text)?),
+        "double" => Field::Double(parse(data_type, text)?),
+        "timestamp" => Field::Timestamp(Timestamp::new(parse(data_type, text)?, precision)),
+        "bool" => Field::Bool(parse(data_type, text)?),
+        "binary" => Field::Binary(BString::from(text)),
+        "nchar" => Field::NChar(text.to_string()),
+        "json" => Field::Json(parse(data_type, text)?),
+        _ => return Err(format!("unknown data type {}", data_type)),
+    })
+}
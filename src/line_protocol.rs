@@ -0,0 +1,254 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+};
+
+use chrono::Utc;
+use libtaos::{Field, Timestamp, TimestampPrecision};
+
+use crate::{try_parse_field, SuperTable};
+
+pub(crate) fn escape(text: &str, specials: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\\' || specials.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+pub(crate) fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+// Splits on `sep` outside of double quotes and backslash escapes.
+pub(crate) fn split_unquoted(text: &str, sep: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            c if c == sep && !quoted => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+// Tag values and raw timestamps as plain text, without line protocol type suffixes.
+// Nulls have none, the formats here leave them out.
+pub(crate) fn field_to_text(field: &Field) -> String {
+    match field {
+        Field::Timestamp(v) => v.as_raw_timestamp().to_string(),
+        Field::Null => unreachable!("nulls are left out"),
+        _ => field.to_string(),
+    }
+}
+
+const TYPE_SUFFIXES: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64", "i", "u",
+];
+
+// Drops a trailing type suffix like `i8`, `u64` or `f32`, or InfluxDB's `i` and `u`.
+pub(crate) fn strip_type_suffix(value: &str) -> &str {
+    TYPE_SUFFIXES
+        .iter()
+        .find_map(|suffix| value.strip_suffix(suffix))
+        .unwrap_or(value)
+}
+
+pub(crate) fn field_to_line_value(field: &Field) -> String {
+    match field {
+        Field::Bool(v) => v.to_string(),
+        Field::TinyInt(v) => format!("{}i8", v),
+        Field::SmallInt(v) => format!("{}i16", v),
+        Field::Int(v) => format!("{}i32", v),
+        Field::BigInt(v) => format!("{}i64", v),
+        Field::Float(v) => format!("{}f32", v),
+        Field::Double(v) => format!("{}f64", v),
+        Field::Binary(v) => format!("\"{}\"", escape(&v.to_string(), &['"'])),
+        Field::Timestamp(v) => format!("{}i64", v.as_raw_timestamp()),
+        Field::NChar(v) => format!("L\"{}\"", escape(v, &['"'])),
+        Field::UTinyInt(v) => format!("{}u8", v),
+        Field::USmallInt(v) => format!("{}u16", v),
+        Field::UInt(v) => format!("{}u32", v),
+        Field::UBigInt(v) => format!("{}u64", v),
+        Field::Null | Field::Json(_) => unreachable!(),
+    }
+}
+
+pub(crate) fn line_value_to_field(
+    stable: &SuperTable,
+    data_type: &str,
+    value: &str,
+) -> Result<Field, String> {
+    match data_type {
+        "bool" => match value {
+            "t" | "T" | "true" | "True" | "TRUE" => Ok(Field::Bool(true)),
+            "f" | "F" | "false" | "False" | "FALSE" => Ok(Field::Bool(false)),
+            _ => Err(format!("{:?} is not a valid bool", value)),
+        },
+        "binary" | "nchar" => {
+            let quoted = value.strip_prefix('L').unwrap_or(value);
+            match quoted.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                Some(v) => try_parse_field(data_type, &unescape(v), stable.precision),
+                None => Err(format!("{:?} is not a quoted string", value)),
+            }
+        }
+        _ => try_parse_field(data_type, strip_type_suffix(value), stable.precision),
+    }
+}
+
+pub(crate) fn timestamp_index(stable: &SuperTable) -> usize {
+    stable
+        .data_types
+        .iter()
+        .position(|t| *t == "timestamp")
+        .unwrap()
+}
+
+// Null tags and fields are left out, and so are rows with no field that is not null, as
+// a line needs at least one. Quotes in tags are escaped too, as the parser takes them
+// to start a string.
+pub fn line_protocol_serialize(stable: &SuperTable, tags: &[Field], rows: &[Vec<Field>]) -> String {
+    let ts_index = timestamp_index(stable);
+    let mut series_key = escape(stable.name, &[',', ' ']);
+    for (name, tag) in stable.tag_names().iter().zip(tags) {
+        if *tag == Field::Null {
+            continue;
+        }
+        series_key.push(',');
+        series_key.push_str(&escape(name, &[',', '=', ' ']));
+        series_key.push('=');
+        series_key.push_str(&escape(&field_to_text(tag), &[',', '=', ' ', '"']));
+    }
+    let mut lines = String::new();
+    for row in rows {
+        let has_fields = row
+            .iter()
+            .enumerate()
+            .any(|(index, field)| index != ts_index && *field != Field::Null);
+        if !has_fields {
+            continue;
+        }
+        lines.push_str(&series_key);
+        let mut sep = ' ';
+        for (index, field) in row.iter().enumerate() {
+            if index == ts_index || *field == Field::Null {
+                continue;
+            }
+            lines.push(sep);
+            lines.push_str(&escape(stable.data_types[index], &[',', '=', ' ']));
+            lines.push('=');
+            lines.push_str(&field_to_line_value(field));
+            sep = ',';
+        }
+        lines.push(' ');
+        lines.push_str(&field_to_text(&row[ts_index]));
+        lines.push('\n');
+    }
+    lines
+}
+
+// The tags and the row of a line, columns in the super table's order.
+pub type Point = (Vec<Field>, Vec<Field>);
+
+fn name_value(pair: &str) -> Result<(String, &str), String> {
+    match split_unquoted(pair, '=')[..] {
+        [name, value] => Ok((unescape(name), value)),
+        _ => Err(format!("{:?} is not a name=value pair", pair)),
+    }
+}
+
+fn column_index(names: &[impl AsRef<str>], name: &str) -> Result<usize, String> {
+    names
+        .iter()
+        .position(|n| n.as_ref() == name)
+        .ok_or_else(|| format!("no column {} in the super table", name))
+}
+
+fn now(precision: TimestampPrecision) -> i64 {
+    let now = Utc::now();
+    match precision {
+        TimestampPrecision::Milli => now.timestamp_millis(),
+        TimestampPrecision::Micro => now.timestamp_micros(),
+        TimestampPrecision::Nano => now.timestamp_nanos_opt().unwrap(),
+    }
+}
+
+fn parse_line(stable: &SuperTable, line: &str) -> Result<Point, String> {
+    let tag_names = stable.tag_names();
+    let parts = split_unquoted(line, ' ');
+    if !(2..=3).contains(&parts.len()) {
+        return Err("expected a series key, fields and an optional timestamp".to_string());
+    }
+    let mut tags = vec![Field::Null; tag_names.len()];
+    for tag in split_unquoted(parts[0], ',').iter().skip(1) {
+        let (name, value) = name_value(tag)?;
+        let index = column_index(&tag_names, &name)?;
+        tags[index] = try_parse_field(stable.tag_types[index], &unescape(value), stable.precision)?;
+    }
+    let mut row = vec![Field::Null; stable.data_types.len()];
+    for field in split_unquoted(parts[1], ',') {
+        let (name, value) = name_value(field)?;
+        let index = column_index(stable.data_types, &name)?;
+        row[index] = line_value_to_field(stable, stable.data_types[index], value)?;
+    }
+    // a line without a timestamp is taken at the time it is read, as InfluxDB does
+    row[timestamp_index(stable)] = match parts.get(2) {
+        Some(ts) => try_parse_field("timestamp", ts, stable.precision)?,
+        None => Field::Timestamp(Timestamp::new(now(stable.precision), stable.precision)),
+    };
+    Ok((tags, row))
+}
+
+// Returns the point of every line, or the first line that does not parse with its number,
+// counting from 1.
+pub fn line_protocol_parse(stable: &SuperTable, lines: &str) -> Result<Vec<Point>, String> {
+    let mut points = vec![];
+    for (number, line) in lines.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        points.push(parse_line(stable, line).map_err(|e| format!("line {}: {}", number + 1, e))?);
+    }
+    Ok(points)
+}
+
+pub fn line_protocol_serialized_write(
+    filename: &str,
+    stable: &SuperTable,
+    tags: &[Field],
+    rows: &[Vec<Field>],
+) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(line_protocol_serialize(stable, tags, rows).as_bytes())
+        .unwrap();
+}
+
+pub fn line_protocol_read(filename: &str, stable: &SuperTable) -> u32 {
+    line_protocol_parse(stable, &fs::read_to_string(filename).unwrap())
+        .unwrap()
+        .len() as u32
+}
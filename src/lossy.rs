@@ -0,0 +1,260 @@
+use std::{
+    convert::TryInto,
+    fmt,
+    fs::File,
+    io::{Read, Write},
+};
+
+use libtaos::{Field, TaosDataType};
+use parquet::{basic::Compression, compression::create_codec};
+
+use crate::bits::{read_uvarint, write_uvarint, zigzag_decode, zigzag_encode};
+
+// Error bounded lossy modes for float and double columns. A relative bound is taken
+// relative to the value range of the column, the way SZ does.
+//
+// `lossy_column` rounds away the mantissa bits the bound allows, so the result is still
+// a float or double column that any format encodes, and compresses, better. The SZ
+// style encoding predicts every value from the previous decoded one and stores the
+// quantized residual as a varint, | count: varint | type: u8 | bound: f64 | codes |,
+// code 0 escaping a value that cannot be predicted within the bound.
+
+#[derive(Clone, Copy, Debug)]
+pub enum ErrorBound {
+    Absolute(f64),
+    Relative(f64),
+}
+
+impl fmt::Display for ErrorBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorBound::Absolute(v) => write!(f, "abs={}", v),
+            ErrorBound::Relative(v) => write!(f, "rel={}", v),
+        }
+    }
+}
+
+impl ErrorBound {
+    pub fn absolute(self, values: &[f64]) -> f64 {
+        match self {
+            ErrorBound::Absolute(v) => v,
+            ErrorBound::Relative(v) => {
+                let finite = values.iter().filter(|v| v.is_finite());
+                let min = finite.clone().fold(f64::INFINITY, |a, b| a.min(*b));
+                let max = finite.fold(f64::NEG_INFINITY, |a, b| a.max(*b));
+                if min > max {
+                    0.0
+                } else {
+                    (max - min) * v
+                }
+            }
+        }
+    }
+}
+
+// Rounds `v` to as few mantissa bits as the bound allows.
+fn round_mantissa(v: f64, bound: f64, mantissa_bits: u32) -> f64 {
+    if !v.is_finite() || v == 0.0 || bound <= 0.0 {
+        return v;
+    }
+    let to_bits = |v: f64| {
+        if mantissa_bits == 52 {
+            v.to_bits()
+        } else {
+            (v as f32).to_bits() as u64
+        }
+    };
+    let from_bits = |bits: u64| {
+        if mantissa_bits == 52 {
+            f64::from_bits(bits)
+        } else {
+            f32::from_bits(bits as u32) as f64
+        }
+    };
+    let bits = to_bits(v);
+    // dropping d bits of a value in [2^e, 2^(e+1)) errs by at most 2^(e - mantissa + d - 1)
+    let e = v.abs().log2().floor() as i64;
+    let mut drop = (bound.log2().floor() as i64 - e + mantissa_bits as i64 + 1)
+        .clamp(0, mantissa_bits as i64) as u32;
+    loop {
+        if drop == 0 {
+            return v;
+        }
+        let rounded = (bits + (1 << (drop - 1))) & !((1 << drop) - 1);
+        let r = from_bits(rounded);
+        if r.is_finite() && (r - v).abs() <= bound {
+            return r;
+        }
+        drop -= 1;
+    }
+}
+
+pub fn lossy_f64(values: &[f64], bound: ErrorBound) -> Vec<f64> {
+    let bound = bound.absolute(values);
+    values
+        .iter()
+        .map(|v| round_mantissa(*v, bound, 52))
+        .collect()
+}
+
+pub fn lossy_f32(values: &[f32], bound: ErrorBound) -> Vec<f32> {
+    let wide: Vec<_> = values.iter().map(|v| *v as f64).collect();
+    let bound = bound.absolute(&wide);
+    wide.iter()
+        .map(|v| round_mantissa(*v, bound, 23) as f32)
+        .collect()
+}
+
+fn column_values(col: &[Field]) -> (TaosDataType, Vec<f64>) {
+    let data_type = col.first().map_or(TaosDataType::Double, Field::data_type);
+    let values = col
+        .iter()
+        .map(|field| match field {
+            Field::Float(v) => *v as f64,
+            Field::Double(v) => *v,
+            _ => unreachable!(),
+        })
+        .collect();
+    (data_type, values)
+}
+
+fn to_field(data_type: TaosDataType, v: f64) -> Field {
+    match data_type {
+        TaosDataType::Float => Field::Float(v as f32),
+        TaosDataType::Double => Field::Double(v),
+        _ => unreachable!(),
+    }
+}
+
+pub fn lossy_column(col: &[Field], bound: ErrorBound) -> Vec<Field> {
+    let (data_type, values) = column_values(col);
+    let bound = bound.absolute(&values);
+    let mantissa_bits = if data_type == TaosDataType::Float {
+        23
+    } else {
+        52
+    };
+    values
+        .into_iter()
+        .map(|v| to_field(data_type, round_mantissa(v, bound, mantissa_bits)))
+        .collect()
+}
+
+pub fn max_error(original: &[Field], lossy: &[Field]) -> f64 {
+    let (_, original) = column_values(original);
+    let (_, lossy) = column_values(lossy);
+    original
+        .iter()
+        .zip(&lossy)
+        .filter(|(a, _)| a.is_finite())
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max)
+}
+
+pub fn sz_column_encode(col: &[Field], bound: ErrorBound) -> Vec<u8> {
+    let (data_type, values) = column_values(col);
+    let bound = bound.absolute(&values);
+    let mut buf = vec![];
+    write_uvarint(&mut buf, values.len() as u64);
+    buf.push(data_type as u8);
+    buf.extend_from_slice(&bound.to_le_bytes());
+    let narrow = |v: f64| match data_type {
+        TaosDataType::Float => v as f32 as f64,
+        _ => v,
+    };
+    let mut prev = 0.0;
+    for v in values {
+        let q = ((v - prev) / (2.0 * bound)).round();
+        // decode in the column's own type so the bound holds for what is read back
+        let decoded = narrow(prev + q * 2.0 * bound);
+        if q.abs() < (1u64 << 52) as f64 && (decoded - v).abs() <= bound {
+            write_uvarint(&mut buf, zigzag_encode(q as i64) + 1);
+            prev = decoded;
+        } else {
+            buf.push(0);
+            match data_type {
+                TaosDataType::Float => buf.extend_from_slice(&(v as f32).to_le_bytes()),
+                _ => buf.extend_from_slice(&v.to_le_bytes()),
+            }
+            prev = v;
+        }
+    }
+    buf
+}
+
+pub fn sz_column_decode(buf: &[u8]) -> Vec<Field> {
+    let mut pos = 0;
+    let len = read_uvarint(buf, &mut pos) as usize;
+    let data_type = TaosDataType::from(buf[pos]);
+    let bound = f64::from_le_bytes(buf[pos + 1..pos + 9].try_into().unwrap());
+    pos += 9;
+    let mut col = Vec::with_capacity(len);
+    let mut prev = 0.0;
+    for _ in 0..len {
+        let code = read_uvarint(buf, &mut pos);
+        prev = if code == 0 {
+            match data_type {
+                TaosDataType::Float => {
+                    let v = f32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap());
+                    pos += 4;
+                    v as f64
+                }
+                _ => {
+                    let v = f64::from_le_bytes(buf[pos..pos + 8].try_into().unwrap());
+                    pos += 8;
+                    v
+                }
+            }
+        } else {
+            let q = zigzag_decode(code - 1) as f64;
+            let v = prev + q * 2.0 * bound;
+            match data_type {
+                TaosDataType::Float => v as f32 as f64,
+                _ => v,
+            }
+        };
+        col.push(to_field(data_type, prev));
+    }
+    col
+}
+
+// The SZ style encoding followed by one of parquet's general purpose codecs.
+pub fn sz_column_serialize(col: &[Field], bound: ErrorBound, compression: Compression) -> Vec<u8> {
+    let buf = sz_column_encode(col, bound);
+    match create_codec(compression).unwrap() {
+        Some(mut codec) => {
+            let mut compressed = vec![];
+            codec.compress(&buf, &mut compressed).unwrap();
+            compressed
+        }
+        None => buf,
+    }
+}
+
+pub fn sz_column_deserialize(buf: &[u8], compression: Compression) -> Vec<Field> {
+    match create_codec(compression).unwrap() {
+        Some(mut codec) => {
+            let mut decompressed = vec![];
+            codec.decompress(buf, &mut decompressed).unwrap();
+            sz_column_decode(&decompressed)
+        }
+        None => sz_column_decode(buf),
+    }
+}
+
+pub fn sz_column_serialized_write(
+    filename: &str,
+    col: &[Field],
+    bound: ErrorBound,
+    compression: Compression,
+) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&sz_column_serialize(col, bound, compression))
+        .unwrap();
+}
+
+pub fn sz_column_read(filename: &str, compression: Compression) -> u32 {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    sz_column_deserialize(&buf, compression).len() as u32
+}
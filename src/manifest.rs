@@ -0,0 +1,294 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::Path,
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use serde_json::json;
+
+// Files become visible only once complete: they are written under a temporary name in
+// the same directory, fsynced and renamed into place, then the directory is fsynced so
+// the rename survives a crash.
+//
+// A dataset directory lists its committed files in `_manifest.json`, replaced the same
+// way on every change. A change holds `_manifest.lock`, created exclusively, and fails
+// if another writer committed since the manifest was opened, so readers of the manifest
+// only ever see whole commits. The lock is released however the commit ends, short of
+// the process dying; a lock older than `STALE_LOCK` whose process is gone is taken to be
+// left by a crash, as a commit only rewrites a small file, and is broken.
+
+pub const MANIFEST: &str = "_manifest.json";
+const MANIFEST_LOCK: &str = "_manifest.lock";
+const STALE_LOCK: Duration = Duration::from_secs(60);
+
+static TEMP_FILES: AtomicU64 = AtomicU64::new(0);
+
+// A name next to `path` for writing it, hidden and unique to this process.
+pub fn temp_path(path: &str) -> String {
+    let path = Path::new(path);
+    let name = path.file_name().unwrap().to_string_lossy();
+    let temp = format!(
+        ".{}.tmp-{}-{}",
+        name,
+        process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::SeqCst)
+    );
+    path.with_file_name(temp).to_string_lossy().to_string()
+}
+
+fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+fn try_commit_file(temp: &str, path: &str) -> io::Result<()> {
+    File::open(temp)?.sync_all()?;
+    fs::rename(temp, path)?;
+    sync_dir(Path::new(path))
+}
+
+// Makes the file written at `temp` durable and moves it to `path`.
+pub fn commit_file(temp: &str, path: &str) {
+    try_commit_file(temp, path).unwrap();
+}
+
+// Runs `write` on a temporary path and commits what it wrote to `path`, for any of the
+// `*_serialized_write` functions.
+pub fn committed_write<T>(path: &str, write: impl FnOnce(&str) -> T) -> T {
+    let temp = temp_path(path);
+    let result = write(&temp);
+    commit_file(&temp, path);
+    result
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestEntry {
+    // relative to the dataset directory
+    pub path: String,
+    pub schema_version: u32,
+    pub rows: u64,
+    pub codec: String,
+    pub min_ts: i64,
+    pub max_ts: i64,
+}
+
+impl ManifestEntry {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "path": self.path,
+            "schema_version": self.schema_version,
+            "rows": self.rows,
+            "codec": self.codec,
+            "min_ts": self.min_ts,
+            "max_ts": self.max_ts,
+        })
+    }
+
+    fn from_json(value: &serde_json::Value) -> Self {
+        Self {
+            path: value["path"].as_str().unwrap().to_string(),
+            schema_version: value["schema_version"].as_u64().unwrap() as u32,
+            rows: value["rows"].as_u64().unwrap(),
+            codec: value["codec"].as_str().unwrap().to_string(),
+            min_ts: value["min_ts"].as_i64().unwrap(),
+            max_ts: value["max_ts"].as_i64().unwrap(),
+        }
+    }
+}
+
+pub struct Manifest {
+    dir: String,
+    version: u64,
+    files: Vec<ManifestEntry>,
+}
+
+fn read_manifest(dir: &str) -> Result<(u64, Vec<ManifestEntry>), String> {
+    match fs::read(format!("{}/{}", dir, MANIFEST)) {
+        Ok(buf) => {
+            let manifest: serde_json::Value = serde_json::from_slice(&buf)
+                .map_err(|e| format!("parsing the manifest of {}: {}", dir, e))?;
+            let files = manifest["files"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(ManifestEntry::from_json)
+                .collect();
+            Ok((manifest["version"].as_u64().unwrap(), files))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok((0, vec![])),
+        Err(e) => Err(format!("reading the manifest of {}: {}", dir, e)),
+    }
+}
+
+// Held for a commit, removing `_manifest.lock` when dropped. The lock holds the pid of
+// the process committing.
+struct ManifestLock(String);
+
+// Whether `lock` was left by a crash: it is older than `STALE_LOCK` and the process it
+// names is gone. Without `/proc` only the age is known.
+fn is_stale(lock: &Path) -> bool {
+    let old = fs::metadata(lock)
+        .and_then(|metadata| metadata.modified())
+        .map(|modified| modified.elapsed().unwrap_or_default() > STALE_LOCK)
+        .unwrap_or(false);
+    let holder = fs::read_to_string(lock)
+        .ok()
+        .and_then(|pid| pid.trim().parse::<u32>().ok());
+    let alive = match holder {
+        Some(pid) => Path::new(&format!("/proc/{}", pid)).exists(),
+        None => false,
+    };
+    old && (!alive || !Path::new("/proc/self").exists())
+}
+
+impl ManifestLock {
+    fn take(dir: &str) -> Result<Self, String> {
+        let lock = format!("{}/{}", dir, MANIFEST_LOCK);
+        let create = || {
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock)?;
+            write!(file, "{}", process::id())
+        };
+        let locked = || format!("{} is locked by another commit", dir);
+        match create() {
+            Ok(()) => Ok(Self(lock)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                if !is_stale(Path::new(&lock)) {
+                    return Err(locked());
+                }
+                // Breaks the lock by moving it aside, which only one writer can do. A writer
+                // that finds it moved a fresh lock, taken since the check, puts it back.
+                let stale = temp_path(&lock);
+                fs::rename(&lock, &stale).map_err(|_| locked())?;
+                if !is_stale(Path::new(&stale)) {
+                    fs::hard_link(&stale, &lock).ok();
+                    fs::remove_file(&stale).ok();
+                    return Err(locked());
+                }
+                fs::remove_file(&stale).ok();
+                create()
+                    .map(|_| Self(lock))
+                    .map_err(|e| format!("{}: {}", locked(), e))
+            }
+            Err(e) => Err(format!("locking {}: {}", lock, e)),
+        }
+    }
+}
+
+impl Drop for ManifestLock {
+    fn drop(&mut self) {
+        fs::remove_file(&self.0).ok();
+    }
+}
+
+impl Manifest {
+    // The dataset in `dir`, empty until its first commit.
+    pub fn open(dir: &str) -> Self {
+        fs::create_dir_all(dir).unwrap();
+        let (version, files) = read_manifest(dir).unwrap();
+        Self {
+            dir: dir.to_string(),
+            version,
+            files,
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn files(&self) -> &[ManifestEntry] {
+        &self.files
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.files.iter().map(|file| file.rows).sum()
+    }
+
+    // Files with rows in [start, end] ms.
+    pub fn files_in_range(&self, start: i64, end: i64) -> Vec<&ManifestEntry> {
+        self.files
+            .iter()
+            .filter(|file| file.min_ts <= end && start <= file.max_ts)
+            .collect()
+    }
+
+    pub fn full_path(&self, entry: &ManifestEntry) -> String {
+        format!("{}/{}", self.dir, entry.path)
+    }
+
+    // Adds `add` and drops the files at `remove` in one new manifest version, returning
+    // it, or an error if another writer got there first; `open` again to retry. Files
+    // removed are deleted once the new manifest is in place.
+    pub fn commit(&mut self, add: Vec<ManifestEntry>, remove: &[&str]) -> Result<u64, String> {
+        for entry in &add {
+            let path = self.full_path(entry);
+            if !Path::new(&path).is_file() {
+                return Err(format!("{} does not exist", path));
+            }
+        }
+        let lock = ManifestLock::take(&self.dir)?;
+        let removed = self.commit_locked(add, remove)?;
+        drop(lock);
+        for entry in removed {
+            fs::remove_file(self.full_path(&entry)).ok();
+        }
+        Ok(self.version)
+    }
+
+    fn commit_locked(
+        &mut self,
+        add: Vec<ManifestEntry>,
+        remove: &[&str],
+    ) -> Result<Vec<ManifestEntry>, String> {
+        let (version, _) = read_manifest(&self.dir)?;
+        if version != self.version {
+            return Err(format!(
+                "{} is at version {}, not {}",
+                self.dir, version, self.version
+            ));
+        }
+        for path in remove {
+            if !self.files.iter().any(|file| file.path == *path) {
+                return Err(format!("{} is not in the manifest", path));
+            }
+        }
+        let (removed, mut files): (Vec<_>, Vec<_>) = self
+            .files
+            .iter()
+            .cloned()
+            .partition(|file| remove.contains(&file.path.as_str()));
+        files.extend(add);
+        let manifest = json!({
+            "version": version + 1,
+            "files": files.iter().map(ManifestEntry::to_json).collect::<Vec<_>>(),
+        });
+        let path = format!("{}/{}", self.dir, MANIFEST);
+        let temp = temp_path(&path);
+        fs::write(&temp, manifest.to_string())
+            .and_then(|_| try_commit_file(&temp, &path))
+            .map_err(|e| {
+                fs::remove_file(&temp).ok();
+                format!("writing the manifest of {}: {}", self.dir, e)
+            })?;
+        self.version = version + 1;
+        self.files = files;
+        Ok(removed)
+    }
+
+    pub fn add(&mut self, add: Vec<ManifestEntry>) -> Result<u64, String> {
+        self.commit(add, &[])
+    }
+
+    pub fn remove(&mut self, remove: &[&str]) -> Result<u64, String> {
+        self.commit(vec![], remove)
+    }
+}
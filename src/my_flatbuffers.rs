@@ -0,0 +1,245 @@
+use bstr::BString;
+use flatbuffers::{FlatBufferBuilder, WIPOffset};
+use libtaos::{Field, TaosDataType, Timestamp, TimestampPrecision};
+
+use crate::timestamp_precision;
+
+// `flatc --rust -o fbs fbs/column_batch.fbs` after changing the schema
+#[allow(warnings, clippy::all)]
+mod generated {
+    include!("../fbs/column_batch_generated.rs");
+}
+
+pub use generated::taosx as fb;
+
+pub use fb::{Column as FbColumn, ColumnBatch as FbColumnBatch};
+
+impl<'a> FbColumn<'a> {
+    pub fn len(&self) -> usize {
+        match TaosDataType::from(self.data_type()) {
+            TaosDataType::Bool => self.bools().unwrap().len(),
+            TaosDataType::TinyInt => self.i8s().unwrap().len(),
+            TaosDataType::SmallInt => self.i16s().unwrap().len(),
+            TaosDataType::Int => self.i32s().unwrap().len(),
+            TaosDataType::BigInt | TaosDataType::Timestamp => self.i64s().unwrap().len(),
+            TaosDataType::UTinyInt => self.u8s().unwrap().len(),
+            TaosDataType::USmallInt => self.u16s().unwrap().len(),
+            TaosDataType::UInt => self.u32s().unwrap().len(),
+            TaosDataType::UBigInt => self.u64s().unwrap().len(),
+            TaosDataType::Float => self.f32s().unwrap().len(),
+            TaosDataType::Double => self.f64s().unwrap().len(),
+            TaosDataType::Binary | TaosDataType::NChar | TaosDataType::Json => {
+                self.offsets().unwrap().len() - 1
+            }
+            _ => self.nulls().map_or(0, |nulls| nulls.len()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_null(&self, index: usize) -> bool {
+        self.nulls().is_some_and(|nulls| {
+            let (mut low, mut high) = (0, nulls.len());
+            while low < high {
+                let mid = (low + high) / 2;
+                match (nulls.get(mid) as usize).cmp(&index) {
+                    std::cmp::Ordering::Less => low = mid + 1,
+                    std::cmp::Ordering::Equal => return true,
+                    std::cmp::Ordering::Greater => high = mid,
+                }
+            }
+            false
+        })
+    }
+
+    fn timestamp_precision(&self) -> TimestampPrecision {
+        TimestampPrecision::from(self.precision() as i32)
+    }
+
+    // Raw bytes of a binary or nchar value, borrowed from the buffer.
+    pub fn bytes(&self, index: usize) -> &'a [u8] {
+        let offsets = self.offsets().unwrap();
+        let data = self.data().unwrap().bytes();
+        &data[offsets.get(index) as usize..offsets.get(index + 1) as usize]
+    }
+
+    fn value(&self, index: usize) -> Field {
+        match TaosDataType::from(self.data_type()) {
+            TaosDataType::Bool => Field::Bool(self.bools().unwrap().get(index)),
+            TaosDataType::TinyInt => Field::TinyInt(self.i8s().unwrap().get(index)),
+            TaosDataType::SmallInt => Field::SmallInt(self.i16s().unwrap().get(index)),
+            TaosDataType::Int => Field::Int(self.i32s().unwrap().get(index)),
+            TaosDataType::BigInt => Field::BigInt(self.i64s().unwrap().get(index)),
+            TaosDataType::Timestamp => Field::Timestamp(Timestamp::new(
+                self.i64s().unwrap().get(index),
+                self.timestamp_precision(),
+            )),
+            TaosDataType::UTinyInt => Field::UTinyInt(self.u8s().unwrap().get(index)),
+            TaosDataType::USmallInt => Field::USmallInt(self.u16s().unwrap().get(index)),
+            TaosDataType::UInt => Field::UInt(self.u32s().unwrap().get(index)),
+            TaosDataType::UBigInt => Field::UBigInt(self.u64s().unwrap().get(index)),
+            TaosDataType::Float => Field::Float(self.f32s().unwrap().get(index)),
+            TaosDataType::Double => Field::Double(self.f64s().unwrap().get(index)),
+            TaosDataType::Binary => Field::Binary(BString::from(self.bytes(index))),
+            TaosDataType::NChar => {
+                Field::NChar(String::from_utf8(self.bytes(index).to_vec()).unwrap())
+            }
+            TaosDataType::Json => Field::Json(serde_json::from_slice(self.bytes(index)).unwrap()),
+            _ => Field::Null,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Field {
+        match self.is_null(index) {
+            true => Field::Null,
+            false => self.value(index),
+        }
+    }
+
+    pub fn to_fields(&self) -> Vec<Field> {
+        macro_rules! collect {
+            ($vector:ident, $variant:ident) => {
+                self.$vector()
+                    .unwrap()
+                    .iter()
+                    .map(Field::$variant)
+                    .collect()
+            };
+        }
+        let mut fields: Vec<Field> = match TaosDataType::from(self.data_type()) {
+            TaosDataType::Bool => collect!(bools, Bool),
+            TaosDataType::TinyInt => collect!(i8s, TinyInt),
+            TaosDataType::SmallInt => collect!(i16s, SmallInt),
+            TaosDataType::Int => collect!(i32s, Int),
+            TaosDataType::BigInt => collect!(i64s, BigInt),
+            TaosDataType::Timestamp => {
+                let precision = self.timestamp_precision();
+                self.i64s()
+                    .unwrap()
+                    .iter()
+                    .map(|v| Field::Timestamp(Timestamp::new(v, precision)))
+                    .collect()
+            }
+            TaosDataType::UTinyInt => collect!(u8s, UTinyInt),
+            TaosDataType::USmallInt => collect!(u16s, USmallInt),
+            TaosDataType::UInt => collect!(u32s, UInt),
+            TaosDataType::UBigInt => collect!(u64s, UBigInt),
+            TaosDataType::Float => collect!(f32s, Float),
+            TaosDataType::Double => collect!(f64s, Double),
+            TaosDataType::Binary | TaosDataType::NChar | TaosDataType::Json => {
+                (0..self.len()).map(|index| self.get(index)).collect()
+            }
+            _ => vec![Field::Null; self.len()],
+        };
+        if let Some(nulls) = self.nulls() {
+            for null in nulls {
+                fields[null as usize] = Field::Null;
+            }
+        }
+        fields
+    }
+}
+
+impl<'a> FbColumnBatch<'a> {
+    pub fn column(&self, index: usize) -> FbColumn<'a> {
+        self.columns().unwrap().get(index)
+    }
+}
+
+fn build_column<'a>(fbb: &mut FlatBufferBuilder<'a>, col: &[Field]) -> WIPOffset<FbColumn<'a>> {
+    // columns are typed by their first value that is not null
+    let first = col.iter().find(|field| !matches!(field, Field::Null));
+    let data_type = first.map_or(TaosDataType::Null, Field::data_type);
+    let precision = match first {
+        Some(Field::Timestamp(v)) => timestamp_precision(v),
+        _ => TimestampPrecision::Milli,
+    };
+    let nulls: Vec<u32> = col
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| matches!(field, Field::Null))
+        .map(|(row, _)| row as u32)
+        .collect();
+    let mut args = fb::ColumnArgs {
+        data_type: data_type as u8,
+        precision: precision as u8,
+        nulls: (!nulls.is_empty()).then(|| fbb.create_vector(&nulls)),
+        ..Default::default()
+    };
+    macro_rules! vector {
+        ($vector:ident, $field:pat => $value:expr) => {{
+            let values: Vec<_> = col
+                .iter()
+                .map(|field| match field {
+                    Field::Null => Default::default(),
+                    $field => $value,
+                    _ => unreachable!(),
+                })
+                .collect();
+            args.$vector = Some(fbb.create_vector(&values));
+        }};
+    }
+    match data_type {
+        TaosDataType::Null => {}
+        TaosDataType::Bool => vector!(bools, Field::Bool(v) => *v),
+        TaosDataType::TinyInt => vector!(i8s, Field::TinyInt(v) => *v),
+        TaosDataType::SmallInt => vector!(i16s, Field::SmallInt(v) => *v),
+        TaosDataType::Int => vector!(i32s, Field::Int(v) => *v),
+        TaosDataType::BigInt => vector!(i64s, Field::BigInt(v) => *v),
+        TaosDataType::Timestamp => vector!(i64s, Field::Timestamp(v) => v.as_raw_timestamp()),
+        TaosDataType::UTinyInt => vector!(u8s, Field::UTinyInt(v) => *v),
+        TaosDataType::USmallInt => vector!(u16s, Field::USmallInt(v) => *v),
+        TaosDataType::UInt => vector!(u32s, Field::UInt(v) => *v),
+        TaosDataType::UBigInt => vector!(u64s, Field::UBigInt(v) => *v),
+        TaosDataType::Float => vector!(f32s, Field::Float(v) => *v),
+        TaosDataType::Double => vector!(f64s, Field::Double(v) => *v),
+        TaosDataType::Binary | TaosDataType::NChar | TaosDataType::Json => {
+            let mut offsets = vec![0u32];
+            let mut data = vec![];
+            for field in col {
+                match field {
+                    Field::Null => {}
+                    Field::Binary(v) => data.extend_from_slice(v),
+                    Field::NChar(v) => data.extend_from_slice(v.as_bytes()),
+                    // as its text, like Avro
+                    Field::Json(v) => data.extend_from_slice(v.to_string().as_bytes()),
+                    _ => unreachable!(),
+                }
+                offsets.push(data.len() as u32);
+            }
+            args.offsets = Some(fbb.create_vector(&offsets));
+            args.data = Some(fbb.create_vector(&data));
+        }
+        _ => unreachable!(),
+    }
+    FbColumn::create(fbb, &args)
+}
+
+pub fn flatbuffers_serialize(cols: &[Vec<Field>]) -> Vec<u8> {
+    let mut fbb = FlatBufferBuilder::new();
+    let columns: Vec<_> = cols.iter().map(|col| build_column(&mut fbb, col)).collect();
+    let columns = fbb.create_vector(&columns);
+    let batch = FbColumnBatch::create(
+        &mut fbb,
+        &fb::ColumnBatchArgs {
+            columns: Some(columns),
+        },
+    );
+    fb::finish_column_batch_buffer(&mut fbb, batch);
+    fbb.finished_data().to_vec()
+}
+
+pub fn flatbuffers_root(buf: &[u8]) -> FbColumnBatch<'_> {
+    fb::root_as_column_batch(buf).unwrap()
+}
+
+pub fn flatbuffers_deserialize(buf: &[u8]) -> Vec<Vec<Field>> {
+    flatbuffers_root(buf)
+        .columns()
+        .unwrap()
+        .iter()
+        .map(|col| col.to_fields())
+        .collect()
+}
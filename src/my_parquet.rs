@@ -1,24 +1,34 @@
-use libtaos::Field;
+use bstr::BString;
+use libtaos::{Field, Timestamp, TimestampPrecision};
 use parquet::{
     basic::{
         Compression, ConvertedType, LogicalType, Repetition, TimeUnit, TimestampType,
         Type as PhysicalType,
     },
-    column::writer::ColumnWriter,
+    column::writer::{ColumnWriter, ColumnWriterImpl},
+    data_type::{ByteArray, DataType},
     file::{
         properties::WriterProperties,
+        reader::FileReader,
         serialized_reader::SerializedFileReader,
-        writer::{FileWriter, InMemoryWriteableCursor, SerializedFileWriter},
+        writer::{FileWriter, InMemoryWriteableCursor, ParquetWriter, SerializedFileWriter},
     },
+    record::{Field as ParquetField, Row},
     schema::types::Type,
 };
 use std::{
     fs::{self},
+    ops::Range,
     path::Path,
     sync::Arc,
     vec,
 };
 
+use crate::{
+    parquet_index::{parquet_read_filtered, Filter},
+    tscol::less,
+};
+
 pub fn generate_parquet_schema(data_types: &Vec<&str>) -> Arc<Type> {
     let mut fields = vec![];
 
@@ -26,54 +36,54 @@ pub fn generate_parquet_schema(data_types: &Vec<&str>) -> Arc<Type> {
         match *data_type {
             "tinyint" => fields.push(Arc::new(
                 Type::primitive_type_builder("tinyint", PhysicalType::INT32)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .with_converted_type(ConvertedType::INT_8)
                     .build()
                     .unwrap(),
             )),
             "utinyint" => fields.push(Arc::new(
                 Type::primitive_type_builder("utinyint", PhysicalType::INT32)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .with_converted_type(ConvertedType::UINT_8)
                     .build()
                     .unwrap(),
             )),
             "smallint" => fields.push(Arc::new(
                 Type::primitive_type_builder("smallint", PhysicalType::INT32)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .with_converted_type(ConvertedType::INT_16)
                     .build()
                     .unwrap(),
             )),
             "usmallint" => fields.push(Arc::new(
                 Type::primitive_type_builder("usmallint", PhysicalType::INT32)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .with_converted_type(ConvertedType::UINT_16)
                     .build()
                     .unwrap(),
             )),
             "int" => fields.push(Arc::new(
                 Type::primitive_type_builder("int", PhysicalType::INT32)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .build()
                     .unwrap(),
             )),
             "uint" => fields.push(Arc::new(
                 Type::primitive_type_builder("uint", PhysicalType::INT32)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .with_converted_type(ConvertedType::UINT_32)
                     .build()
                     .unwrap(),
             )),
             "bigint" => fields.push(Arc::new(
                 Type::primitive_type_builder("bigint", PhysicalType::INT64)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .build()
                     .unwrap(),
             )),
             "timestamp" => fields.push(Arc::new(
                 Type::primitive_type_builder("timestamp", PhysicalType::INT64)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .with_logical_type(Some(LogicalType::TIMESTAMP(TimestampType {
                         is_adjusted_to_u_t_c: false,
                         unit: TimeUnit::MILLIS(Default::default()),
@@ -83,33 +93,33 @@ pub fn generate_parquet_schema(data_types: &Vec<&str>) -> Arc<Type> {
             )),
             "ubigint" => fields.push(Arc::new(
                 Type::primitive_type_builder("ubigint", PhysicalType::INT64)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .with_converted_type(ConvertedType::UINT_64)
                     .build()
                     .unwrap(),
             )),
             "float" => fields.push(Arc::new(
                 Type::primitive_type_builder("float", PhysicalType::FLOAT)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .build()
                     .unwrap(),
             )),
             "double" => fields.push(Arc::new(
                 Type::primitive_type_builder("double", PhysicalType::DOUBLE)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .build()
                     .unwrap(),
             )),
             "binary" => fields.push(Arc::new(
                 Type::primitive_type_builder("binary", PhysicalType::BYTE_ARRAY)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .with_id(8)
                     .build()
                     .unwrap(),
             )),
             "nchar" => fields.push(Arc::new(
                 Type::primitive_type_builder("nchar", PhysicalType::BYTE_ARRAY)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .with_logical_type(Some(LogicalType::STRING(Default::default())))
                     .with_id(8)
                     .build()
@@ -117,7 +127,7 @@ pub fn generate_parquet_schema(data_types: &Vec<&str>) -> Arc<Type> {
             )),
             "bool" => fields.push(Arc::new(
                 Type::primitive_type_builder("bool", PhysicalType::BOOLEAN)
-                    .with_repetition(Repetition::REQUIRED)
+                    .with_repetition(Repetition::OPTIONAL)
                     .build()
                     .unwrap(),
             )),
@@ -133,6 +143,102 @@ pub fn generate_parquet_schema(data_types: &Vec<&str>) -> Arc<Type> {
     )
 }
 
+// The least and greatest values of `col`, leaving out nulls and NaNs as parquet does.
+fn bounds(col: &[Field]) -> Option<(&Field, &Field)> {
+    let mut values = col.iter().filter(|field| match field {
+        Field::Null => false,
+        Field::Float(v) => !v.is_nan(),
+        Field::Double(v) => !v.is_nan(),
+        _ => true,
+    });
+    let first = values.next()?;
+    Some(values.fold((first, first), |(min, max), field| {
+        (
+            if less(field, min) { field } else { min },
+            if less(max, field) { field } else { max },
+        )
+    }))
+}
+
+// parquet 9.1 panics on the statistics of a page of nulls that follows pages of values,
+// so a column with nulls is written with its statistics worked out here. Its pages then
+// have no statistics of their own, and so no column index.
+fn write_typed<T: DataType>(
+    typed: &mut ColumnWriterImpl<T>,
+    col: &[Field],
+    value: impl Fn(&Field) -> T::T,
+) {
+    let def_levels: Vec<i16> = col
+        .iter()
+        .map(|field| !matches!(field, Field::Null) as i16)
+        .collect();
+    let values: Vec<T::T> = col
+        .iter()
+        .filter(|field| !matches!(field, Field::Null))
+        .map(&value)
+        .collect();
+    let nulls = col.len() - values.len();
+    if nulls == 0 {
+        typed.write_batch(&values, Some(&def_levels), None).unwrap();
+    } else {
+        let (min, max) = bounds(col)
+            .map(|(min, max)| (value(min), value(max)))
+            .unzip();
+        typed
+            .write_batch_with_statistics(
+                &values,
+                Some(&def_levels),
+                None,
+                &min,
+                &max,
+                Some(nulls as u64),
+                None,
+            )
+            .unwrap();
+    }
+}
+
+fn write_column(writer: &mut ColumnWriter, col: &[Field]) {
+    match writer {
+        ColumnWriter::BoolColumnWriter(ref mut typed) => {
+            write_typed(typed, col, |field| *field.as_bool().unwrap())
+        }
+        ColumnWriter::Int32ColumnWriter(ref mut typed) => {
+            write_typed(typed, col, |field| match *field {
+                Field::TinyInt(v) => v as i32,
+                Field::SmallInt(v) => v as i32,
+                Field::Int(v) => v,
+                Field::UTinyInt(v) => v as i32,
+                Field::USmallInt(v) => v as i32,
+                Field::UInt(v) => v as i32,
+                _ => unreachable!(),
+            })
+        }
+        ColumnWriter::Int64ColumnWriter(ref mut typed) => {
+            write_typed(typed, col, |field| match field {
+                Field::BigInt(v) => *v,
+                Field::Timestamp(v) => v.as_raw_timestamp(),
+                Field::UBigInt(v) => *v as i64,
+                _ => unreachable!(),
+            })
+        }
+        ColumnWriter::FloatColumnWriter(ref mut typed) => {
+            write_typed(typed, col, |field| *field.as_float().unwrap())
+        }
+        ColumnWriter::DoubleColumnWriter(ref mut typed) => {
+            write_typed(typed, col, |field| *field.as_double().unwrap())
+        }
+        ColumnWriter::ByteArrayColumnWriter(ref mut typed) => {
+            write_typed(typed, col, |field| match field {
+                Field::Binary(v) => ByteArray::from(v.to_vec()),
+                Field::NChar(v) => ByteArray::from(v.as_str()),
+                _ => unreachable!(),
+            })
+        }
+        _ => unreachable!(),
+    }
+}
+
 pub fn parquet_serialize(data_types: &Vec<&str>, cols: &Vec<Vec<Field>>, compression: Compression) {
     let cursor = InMemoryWriteableCursor::default();
     let props = Arc::new(
@@ -146,105 +252,7 @@ pub fn parquet_serialize(data_types: &Vec<&str>, cols: &Vec<Vec<Field>>, compres
     for col in cols {
         let data_writer = row_group_writer.next_column().unwrap();
         if let Some(mut writer) = data_writer {
-            let mut def_level = vec![];
-            match writer {
-                ColumnWriter::BoolColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        values.push(*field.to_owned().as_bool().unwrap());
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::Int32ColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        match *field {
-                            Field::TinyInt(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::SmallInt(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::Int(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::UTinyInt(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::USmallInt(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::UInt(v) => {
-                                values.push(v as i32);
-                            }
-                            _ => unreachable!(),
-                        }
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::Int64ColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        let field = (*field).clone();
-                        match field {
-                            Field::BigInt(v) => values.push(v),
-                            Field::Timestamp(v) => values.push(v.as_raw_timestamp()),
-                            Field::UBigInt(v) => values.push(v as i64),
-                            _ => unreachable!(),
-                        }
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::FloatColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        values.push(*field.as_float().unwrap());
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::DoubleColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        values.push(*field.as_double().unwrap())
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::ByteArrayColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        let field = (*field).clone();
-                        match field {
-                            Field::Binary(v) => {
-                                values.push(parquet::data_type::ByteArray::from(v.to_vec()))
-                            }
-                            Field::NChar(v) => {
-                                values.push(parquet::data_type::ByteArray::from(v.as_str()))
-                            }
-                            _ => unreachable!(),
-                        }
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                _ => unreachable!(),
-            }
+            write_column(&mut writer, col);
             row_group_writer.close_column(writer).unwrap();
         }
     }
@@ -252,13 +260,14 @@ pub fn parquet_serialize(data_types: &Vec<&str>, cols: &Vec<Vec<Field>>, compres
     writer.close().unwrap();
 }
 
+#[allow(clippy::needless_borrows_for_generic_args)]
 pub fn parquet_serialized_write(
     filename: &str,
     data_types: &Vec<&str>,
     cols: &Vec<Vec<Field>>,
     compression: Compression,
 ) {
-    fs::remove_file(filename).unwrap();
+    fs::remove_file(filename).ok();
     let path = Path::new(filename);
     let file = fs::File::create(&path).unwrap();
     let props = Arc::new(
@@ -272,105 +281,7 @@ pub fn parquet_serialized_write(
     for col in cols {
         let data_writer = row_group_writer.next_column().unwrap();
         if let Some(mut writer) = data_writer {
-            let mut def_level = vec![];
-            match writer {
-                ColumnWriter::BoolColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        values.push(*field.to_owned().as_bool().unwrap());
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::Int32ColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        match *field {
-                            Field::TinyInt(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::SmallInt(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::Int(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::UTinyInt(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::USmallInt(v) => {
-                                values.push(v as i32);
-                            }
-                            Field::UInt(v) => {
-                                values.push(v as i32);
-                            }
-                            _ => unreachable!(),
-                        }
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::Int64ColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        let field = (*field).clone();
-                        match field {
-                            Field::BigInt(v) => values.push(v),
-                            Field::Timestamp(v) => values.push(v.as_raw_timestamp()),
-                            Field::UBigInt(v) => values.push(v as i64),
-                            _ => unreachable!(),
-                        }
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::FloatColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        values.push(*field.as_float().unwrap());
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::DoubleColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        values.push(*field.as_double().unwrap())
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                ColumnWriter::ByteArrayColumnWriter(ref mut typed) => {
-                    let mut values = vec![];
-                    for field in col {
-                        def_level.push(1);
-                        let field = (*field).clone();
-                        match field {
-                            Field::Binary(v) => {
-                                values.push(parquet::data_type::ByteArray::from(v.to_vec()))
-                            }
-                            Field::NChar(v) => {
-                                values.push(parquet::data_type::ByteArray::from(v.as_str()))
-                            }
-                            _ => unreachable!(),
-                        }
-                    }
-                    typed
-                        .write_batch(&values[..], Some(&def_level), None)
-                        .unwrap();
-                }
-                _ => unreachable!(),
-            }
+            write_column(&mut writer, col);
             row_group_writer.close_column(writer).unwrap();
         }
     }
@@ -378,6 +289,137 @@ pub fn parquet_serialized_write(
     writer.close().unwrap();
 }
 
+pub(crate) fn write_row_groups<W: ParquetWriter + 'static>(
+    sink: W,
+    props: WriterProperties,
+    data_types: &Vec<&str>,
+    cols: &[Vec<Field>],
+    row_group_rows: usize,
+) {
+    assert!(row_group_rows > 0, "row_group_rows must be at least 1");
+    let schema = generate_parquet_schema(data_types);
+    let mut writer = SerializedFileWriter::new(sink, schema, Arc::new(props)).unwrap();
+    let rows = cols.first().map_or(0, Vec::len);
+    for start in (0..rows).step_by(row_group_rows) {
+        let end = (start + row_group_rows).min(rows);
+        write_row_group(&mut writer, cols, start..end);
+    }
+    writer.close().unwrap();
+}
+
+fn write_row_group<W: ParquetWriter + 'static>(
+    writer: &mut SerializedFileWriter<W>,
+    cols: &[Vec<Field>],
+    rows: Range<usize>,
+) {
+    let mut row_group_writer = writer.next_row_group().unwrap();
+    for col in cols {
+        let mut column_writer = row_group_writer.next_column().unwrap().unwrap();
+        write_column(&mut column_writer, &col[rows.clone()]);
+        row_group_writer.close_column(column_writer).unwrap();
+    }
+    writer.close_row_group(row_group_writer).unwrap();
+}
+
+// Like `parquet_serialized_write`, with a row group every `row_group_rows` rows.
+pub fn parquet_serialized_write_row_groups(
+    filename: &str,
+    data_types: &Vec<&str>,
+    cols: &[Vec<Field>],
+    compression: Compression,
+    row_group_rows: usize,
+) {
+    let file = fs::File::create(filename).unwrap();
+    let props = WriterProperties::builder()
+        .set_compression(compression)
+        .build();
+    write_row_groups(file, props, data_types, cols, row_group_rows);
+}
+
+// A file that stays open for batches as they arrive, buffering columns until there are
+// `row_group_rows` rows for a row group. The footer is only written by `close`, so the
+// file is not readable before then.
+pub struct ParquetStreamWriter {
+    writer: SerializedFileWriter<fs::File>,
+    // another handle on the file, to see how much has been written
+    file: fs::File,
+    buffer: Vec<Vec<Field>>,
+    // rows in `buffer`, which has no column to count them by without a schema
+    buffered: usize,
+    row_group_rows: usize,
+    rows: u64,
+}
+
+impl ParquetStreamWriter {
+    pub fn new(
+        filename: &str,
+        data_types: &Vec<&str>,
+        compression: Compression,
+        row_group_rows: usize,
+    ) -> Self {
+        assert!(row_group_rows > 0, "row_group_rows must be at least 1");
+        let file = fs::File::create(filename).unwrap();
+        let props = WriterProperties::builder()
+            .set_compression(compression)
+            .build();
+        let writer = SerializedFileWriter::new(
+            file.try_clone().unwrap(),
+            generate_parquet_schema(data_types),
+            Arc::new(props),
+        )
+        .unwrap();
+        Self {
+            writer,
+            file,
+            buffer: vec![vec![]; data_types.len()],
+            buffered: 0,
+            row_group_rows,
+            rows: 0,
+        }
+    }
+
+    pub fn append_batch(&mut self, cols: &[Vec<Field>]) {
+        for (buffer, col) in self.buffer.iter_mut().zip(cols) {
+            buffer.extend_from_slice(col);
+        }
+        let rows = cols.first().map_or(0, Vec::len);
+        self.rows += rows as u64;
+        self.buffered += rows;
+        let full = self.buffered - self.buffered % self.row_group_rows;
+        for start in (0..full).step_by(self.row_group_rows) {
+            write_row_group(
+                &mut self.writer,
+                &self.buffer,
+                start..start + self.row_group_rows,
+            );
+        }
+        for buffer in &mut self.buffer {
+            buffer.drain(..full);
+        }
+        self.buffered -= full;
+    }
+
+    // Rows appended so far, written or buffered.
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    // Bytes of the row groups written so far, not counting buffered rows.
+    pub fn bytes_written(&self) -> u64 {
+        self.file.metadata().unwrap().len()
+    }
+
+    // Writes the buffered rows as a last, smaller row group and the footer, returning
+    // the number of rows in the file.
+    pub fn close(mut self) -> u64 {
+        if self.buffered > 0 {
+            write_row_group(&mut self.writer, &self.buffer, 0..self.buffered);
+        }
+        self.writer.close().unwrap();
+        self.rows
+    }
+}
+
 pub fn parquet_read(filename: &str) -> u32 {
     let mut count = 0;
     let parquet_reader = SerializedFileReader::try_from(filename).unwrap();
@@ -387,3 +429,92 @@ pub fn parquet_read(filename: &str) -> u32 {
     }
     count
 }
+
+// Converted and logical types carry the data type, so a row maps back without the schema.
+pub fn parquet_row_to_fields(row: &Row) -> Vec<Field> {
+    row.get_column_iter()
+        .map(|(_, field)| match field {
+            ParquetField::Null => Field::Null,
+            ParquetField::Bool(v) => Field::Bool(*v),
+            ParquetField::Byte(v) => Field::TinyInt(*v),
+            ParquetField::Short(v) => Field::SmallInt(*v),
+            ParquetField::Int(v) => Field::Int(*v),
+            ParquetField::Long(v) => Field::BigInt(*v),
+            ParquetField::UByte(v) => Field::UTinyInt(*v),
+            ParquetField::UShort(v) => Field::USmallInt(*v),
+            ParquetField::UInt(v) => Field::UInt(*v),
+            ParquetField::ULong(v) => Field::UBigInt(*v),
+            ParquetField::Float(v) => Field::Float(*v),
+            ParquetField::Double(v) => Field::Double(*v),
+            ParquetField::Str(v) => Field::NChar(v.clone()),
+            ParquetField::Bytes(v) => Field::Binary(BString::from(v.data())),
+            ParquetField::TimestampMillis(v) => {
+                Field::Timestamp(Timestamp::new(*v as i64, TimestampPrecision::Milli))
+            }
+            field => unreachable!("unexpected parquet field {:?}", field),
+        })
+        .collect()
+}
+
+// Only the column chunks of `columns` are read and decoded.
+pub fn parquet_read_projected(filename: &str, columns: &[&str]) -> Vec<Vec<Field>> {
+    let reader = SerializedFileReader::try_from(filename).unwrap();
+    let schema = reader.metadata().file_metadata().schema();
+    let mut fields: Vec<_> = columns
+        .iter()
+        .map(|name| {
+            schema
+                .get_fields()
+                .iter()
+                .find(|field| field.name() == *name)
+                .unwrap_or_else(|| panic!("no column {} in {}", name, filename))
+                .clone()
+        })
+        .collect();
+    let projection = Type::group_type_builder(schema.name())
+        .with_fields(&mut fields)
+        .build()
+        .unwrap();
+    reader
+        .get_row_iter(Some(projection))
+        .unwrap()
+        .map(|row| parquet_row_to_fields(&row))
+        .collect()
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ScanStats {
+    pub row_groups: usize,
+    pub row_groups_read: usize,
+    pub rows_scanned: usize,
+}
+
+// Rows with the timestamp `column` between `start` and `end` inclusive. Row groups whose
+// min/max statistics for `column` miss the range are skipped without reading them and,
+// in files written with page indexes, so are the pages of a row group whose min/max miss
+// it. `column` is decoded first, and the other columns only for the pages in range.
+pub fn parquet_read_time_range(
+    filename: &str,
+    column: &str,
+    start: i64,
+    end: i64,
+) -> (Vec<Vec<Field>>, ScanStats) {
+    let i64_of = |buf: &[u8]| buf.try_into().ok().map(i64::from_le_bytes);
+    let filter = Filter {
+        may_match: &|min, max| match (i64_of(min), i64_of(max)) {
+            (Some(min), Some(max)) => min <= end && start <= max,
+            _ => true,
+        },
+        matches: &|field| {
+            let timestamp = match field {
+                Field::Timestamp(v) => v.as_raw_timestamp(),
+                Field::BigInt(v) => *v,
+                Field::Null => return false,
+                _ => unreachable!(),
+            };
+            start <= timestamp && timestamp <= end
+        },
+        hash: None,
+    };
+    parquet_read_filtered(filename, column, &filter)
+}
@@ -0,0 +1,165 @@
+use bstr::BString;
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use rkyv::{rancor::Error, util::AlignedVec, Archive, Deserialize, Serialize};
+
+use crate::timestamp_precision;
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+pub enum RkyvValues {
+    // a column of only nulls, with its length
+    Null(u32),
+    Bool(Vec<bool>),
+    TinyInt(Vec<i8>),
+    SmallInt(Vec<i16>),
+    Int(Vec<i32>),
+    BigInt(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    Binary(Vec<Vec<u8>>),
+    // with the precision, 0 ms, 1 us or 2 ns
+    Timestamp(Vec<i64>, u8),
+    NChar(Vec<String>),
+    UTinyInt(Vec<u8>),
+    USmallInt(Vec<u16>),
+    UInt(Vec<u32>),
+    UBigInt(Vec<u64>),
+    // as text, like Avro
+    Json(Vec<String>),
+}
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RkyvColumn {
+    pub values: RkyvValues,
+    // rows of the null values, ascending, which hold 0 or an empty value in `values`
+    pub nulls: Vec<u32>,
+}
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RkyvColumnBatch {
+    pub columns: Vec<RkyvColumn>,
+}
+
+pub fn column_to_rkyv(col: &[Field]) -> RkyvColumn {
+    macro_rules! values {
+        ($field:pat => $value:expr) => {
+            col.iter()
+                .map(|field| match field {
+                    Field::Null => Default::default(),
+                    $field => $value,
+                    _ => unreachable!(),
+                })
+                .collect()
+        };
+    }
+    macro_rules! typed {
+        ($variant:ident, $field:pat => $value:expr) => {
+            RkyvValues::$variant(values!($field => $value))
+        };
+    }
+    // columns are typed by their first value that is not null
+    let values = match col.iter().find(|field| !matches!(field, Field::Null)) {
+        None | Some(Field::Null) => RkyvValues::Null(col.len() as u32),
+        Some(Field::Bool(_)) => typed!(Bool, Field::Bool(v) => *v),
+        Some(Field::TinyInt(_)) => typed!(TinyInt, Field::TinyInt(v) => *v),
+        Some(Field::SmallInt(_)) => typed!(SmallInt, Field::SmallInt(v) => *v),
+        Some(Field::Int(_)) => typed!(Int, Field::Int(v) => *v),
+        Some(Field::BigInt(_)) => typed!(BigInt, Field::BigInt(v) => *v),
+        Some(Field::Float(_)) => typed!(Float, Field::Float(v) => *v),
+        Some(Field::Double(_)) => typed!(Double, Field::Double(v) => *v),
+        Some(Field::Binary(_)) => typed!(Binary, Field::Binary(v) => v.to_vec()),
+        Some(Field::Timestamp(first)) => RkyvValues::Timestamp(
+            values!(Field::Timestamp(v) => v.as_raw_timestamp()),
+            timestamp_precision(first) as u8,
+        ),
+        Some(Field::NChar(_)) => typed!(NChar, Field::NChar(v) => v.clone()),
+        Some(Field::UTinyInt(_)) => typed!(UTinyInt, Field::UTinyInt(v) => *v),
+        Some(Field::USmallInt(_)) => typed!(USmallInt, Field::USmallInt(v) => *v),
+        Some(Field::UInt(_)) => typed!(UInt, Field::UInt(v) => *v),
+        Some(Field::UBigInt(_)) => typed!(UBigInt, Field::UBigInt(v) => *v),
+        Some(Field::Json(_)) => typed!(Json, Field::Json(v) => v.to_string()),
+    };
+    let nulls = col
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| matches!(field, Field::Null))
+        .map(|(row, _)| row as u32)
+        .collect();
+    RkyvColumn { values, nulls }
+}
+
+pub fn archived_column_len(col: &ArchivedRkyvColumn) -> usize {
+    match &col.values {
+        ArchivedRkyvValues::Null(len) => len.to_native() as usize,
+        ArchivedRkyvValues::Bool(v) => v.len(),
+        ArchivedRkyvValues::TinyInt(v) => v.len(),
+        ArchivedRkyvValues::SmallInt(v) => v.len(),
+        ArchivedRkyvValues::Int(v) => v.len(),
+        ArchivedRkyvValues::BigInt(v) => v.len(),
+        ArchivedRkyvValues::Float(v) => v.len(),
+        ArchivedRkyvValues::Double(v) => v.len(),
+        ArchivedRkyvValues::Binary(v) => v.len(),
+        ArchivedRkyvValues::Timestamp(v, _) => v.len(),
+        ArchivedRkyvValues::NChar(v) => v.len(),
+        ArchivedRkyvValues::UTinyInt(v) => v.len(),
+        ArchivedRkyvValues::USmallInt(v) => v.len(),
+        ArchivedRkyvValues::UInt(v) => v.len(),
+        ArchivedRkyvValues::UBigInt(v) => v.len(),
+        ArchivedRkyvValues::Json(v) => v.len(),
+    }
+}
+
+pub fn archived_column_get(col: &ArchivedRkyvColumn, index: usize) -> Field {
+    if col
+        .nulls
+        .binary_search_by_key(&(index as u32), |null| null.to_native())
+        .is_ok()
+    {
+        return Field::Null;
+    }
+    match &col.values {
+        ArchivedRkyvValues::Null(_) => Field::Null,
+        ArchivedRkyvValues::Bool(v) => Field::Bool(v[index]),
+        ArchivedRkyvValues::TinyInt(v) => Field::TinyInt(v[index]),
+        ArchivedRkyvValues::SmallInt(v) => Field::SmallInt(v[index].to_native()),
+        ArchivedRkyvValues::Int(v) => Field::Int(v[index].to_native()),
+        ArchivedRkyvValues::BigInt(v) => Field::BigInt(v[index].to_native()),
+        ArchivedRkyvValues::Float(v) => Field::Float(v[index].to_native()),
+        ArchivedRkyvValues::Double(v) => Field::Double(v[index].to_native()),
+        ArchivedRkyvValues::Binary(v) => Field::Binary(BString::from(v[index].as_slice())),
+        ArchivedRkyvValues::Timestamp(v, precision) => Field::Timestamp(Timestamp::new(
+            v[index].to_native(),
+            TimestampPrecision::from(*precision as i32),
+        )),
+        ArchivedRkyvValues::NChar(v) => Field::NChar(v[index].to_string()),
+        ArchivedRkyvValues::UTinyInt(v) => Field::UTinyInt(v[index]),
+        ArchivedRkyvValues::USmallInt(v) => Field::USmallInt(v[index].to_native()),
+        ArchivedRkyvValues::UInt(v) => Field::UInt(v[index].to_native()),
+        ArchivedRkyvValues::UBigInt(v) => Field::UBigInt(v[index].to_native()),
+        ArchivedRkyvValues::Json(v) => Field::Json(serde_json::from_str(&v[index]).unwrap()),
+    }
+}
+
+pub fn archived_column_to_fields(col: &ArchivedRkyvColumn) -> Vec<Field> {
+    (0..archived_column_len(col))
+        .map(|index| archived_column_get(col, index))
+        .collect()
+}
+
+pub fn rkyv_serialize(cols: &[Vec<Field>]) -> AlignedVec {
+    let batch = RkyvColumnBatch {
+        columns: cols.iter().map(|col| column_to_rkyv(col)).collect(),
+    };
+    rkyv::to_bytes::<Error>(&batch).unwrap()
+}
+
+pub fn rkyv_access(buf: &[u8]) -> &ArchivedRkyvColumnBatch {
+    rkyv::access::<ArchivedRkyvColumnBatch, Error>(buf).unwrap()
+}
+
+pub fn rkyv_deserialize(buf: &[u8]) -> Vec<Vec<Field>> {
+    rkyv_access(buf)
+        .columns
+        .iter()
+        .map(archived_column_to_fields)
+        .collect()
+}
@@ -0,0 +1,250 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+};
+
+use libtaos::{Field, TimestampPrecision};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    line_protocol::{
+        escape, field_to_line_value, field_to_text, line_value_to_field, split_unquoted,
+        timestamp_index, unescape,
+    },
+    parse_field, SuperTable,
+};
+
+// OpenTSDB carries a single metric per point, so every non-timestamp column of a row
+// becomes its own point named `<stable>.<column>`. Parsing folds consecutive points
+// with the same timestamp and tags back into one row. Null tags and columns have no
+// point, so a row of nulls but for the timestamp is not written at all.
+
+fn metric_name(stable: &SuperTable, index: usize) -> String {
+    format!("{}.{}", stable.name, stable.data_types[index])
+}
+
+fn metric_index(stable: &SuperTable, metric: &str) -> usize {
+    let column = metric
+        .strip_prefix(stable.name)
+        .and_then(|m| m.strip_prefix('.'))
+        .unwrap();
+    stable.data_types.iter().position(|t| *t == column).unwrap()
+}
+
+fn push_point(
+    stable: &SuperTable,
+    rows: &mut Vec<(Vec<Field>, Vec<Field>)>,
+    tags: Vec<Field>,
+    timestamp: Field,
+    index: usize,
+    value: Field,
+) {
+    let ts_index = timestamp_index(stable);
+    match rows.last_mut() {
+        Some((last_tags, row))
+            if row[ts_index] == timestamp && *last_tags == tags && row[index] == Field::Null =>
+        {
+            row[index] = value;
+        }
+        _ => {
+            let mut row = vec![Field::Null; stable.data_types.len()];
+            row[ts_index] = timestamp;
+            row[index] = value;
+            rows.push((tags, row));
+        }
+    }
+}
+
+pub fn opentsdb_telnet_serialize(
+    stable: &SuperTable,
+    tags: &[Field],
+    rows: &[Vec<Field>],
+) -> String {
+    let ts_index = timestamp_index(stable);
+    let mut tag_set = String::new();
+    for (name, tag) in stable.tag_names().iter().zip(tags) {
+        if *tag == Field::Null {
+            continue;
+        }
+        tag_set.push(' ');
+        tag_set.push_str(&escape(name, &['=', ' ']));
+        tag_set.push('=');
+        tag_set.push_str(&escape(&field_to_text(tag), &['=', ' ', '"']));
+    }
+    let mut lines = String::new();
+    for row in rows {
+        let timestamp = field_to_text(&row[ts_index]);
+        for (index, field) in row.iter().enumerate() {
+            if index == ts_index || *field == Field::Null {
+                continue;
+            }
+            lines.push_str(&metric_name(stable, index));
+            lines.push(' ');
+            lines.push_str(&timestamp);
+            lines.push(' ');
+            lines.push_str(&field_to_line_value(field));
+            lines.push_str(&tag_set);
+            lines.push('\n');
+        }
+    }
+    lines
+}
+
+pub fn opentsdb_telnet_parse(stable: &SuperTable, lines: &str) -> Vec<(Vec<Field>, Vec<Field>)> {
+    let tag_names = stable.tag_names();
+    let mut rows = vec![];
+    for line in lines.lines() {
+        let line = line.strip_prefix("put ").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let parts = split_unquoted(line, ' ');
+        let index = metric_index(stable, parts[0]);
+        let timestamp = parse_field("timestamp", parts[1], stable.precision);
+        let value = line_value_to_field(stable, stable.data_types[index], parts[2]).unwrap();
+        let mut tags = vec![Field::Null; tag_names.len()];
+        for tag in &parts[3..] {
+            let kv = split_unquoted(tag, '=');
+            let name = unescape(kv[0]);
+            let tag_index = tag_names.iter().position(|t| *t == name).unwrap();
+            tags[tag_index] = parse_field(
+                stable.tag_types[tag_index],
+                &unescape(kv[1]),
+                stable.precision,
+            );
+        }
+        push_point(stable, &mut rows, tags, timestamp, index, value);
+    }
+    rows
+}
+
+fn field_to_json(field: &Field) -> Value {
+    let (value, data_type) = match field {
+        Field::Bool(v) => (json!(v), "bool"),
+        Field::TinyInt(v) => (json!(v), "tinyint"),
+        Field::SmallInt(v) => (json!(v), "smallint"),
+        Field::Int(v) => (json!(v), "int"),
+        Field::BigInt(v) => (json!(v), "bigint"),
+        // JSON numbers have no infinities or NaN
+        Field::Float(v) if !v.is_finite() => (json!(v.to_string()), "float"),
+        Field::Float(v) => (json!(v), "float"),
+        Field::Double(v) if !v.is_finite() => (json!(v.to_string()), "double"),
+        Field::Double(v) => (json!(v), "double"),
+        Field::Binary(v) => (json!(v.to_string()), "binary"),
+        Field::Timestamp(v) => (json!(v.as_raw_timestamp()), "bigint"),
+        Field::NChar(v) => (json!(v), "nchar"),
+        Field::UTinyInt(v) => (json!(v), "utinyint"),
+        Field::USmallInt(v) => (json!(v), "usmallint"),
+        Field::UInt(v) => (json!(v), "uint"),
+        Field::UBigInt(v) => (json!(v), "ubigint"),
+        Field::Json(v) => (json!(v.to_string()), "json"),
+        Field::Null => unreachable!(),
+    };
+    json!({ "value": value, "type": data_type })
+}
+
+fn json_to_field(data_type: &str, value: &Value, precision: TimestampPrecision) -> Field {
+    // values are either plain or wrapped as {"value": .., "type": ..}
+    let value = value.get("value").unwrap_or(value);
+    match value {
+        Value::String(v) => parse_field(data_type, v, precision),
+        v => parse_field(data_type, &v.to_string(), precision),
+    }
+}
+
+fn precision_name(precision: TimestampPrecision) -> &'static str {
+    match precision {
+        TimestampPrecision::Milli => "ms",
+        TimestampPrecision::Micro => "us",
+        TimestampPrecision::Nano => "ns",
+    }
+}
+
+pub fn opentsdb_json_serialize(stable: &SuperTable, tags: &[Field], rows: &[Vec<Field>]) -> String {
+    let ts_index = timestamp_index(stable);
+    let mut tag_map = Map::new();
+    for (name, tag) in stable.tag_names().into_iter().zip(tags) {
+        if *tag == Field::Null {
+            continue;
+        }
+        tag_map.insert(name, field_to_json(tag));
+    }
+    let tag_map = Value::Object(tag_map);
+    let mut points = vec![];
+    for row in rows {
+        let timestamp = json!({
+            "value": row[ts_index].as_raw_timestamp().unwrap(),
+            "type": precision_name(stable.precision),
+        });
+        for (index, field) in row.iter().enumerate() {
+            if index == ts_index || *field == Field::Null {
+                continue;
+            }
+            points.push(json!({
+                "metric": metric_name(stable, index),
+                "timestamp": timestamp,
+                "value": field_to_json(field),
+                "tags": tag_map,
+            }));
+        }
+    }
+    serde_json::to_string(&points).unwrap()
+}
+
+pub fn opentsdb_json_parse(stable: &SuperTable, text: &str) -> Vec<(Vec<Field>, Vec<Field>)> {
+    let tag_names = stable.tag_names();
+    let points: Vec<Value> = serde_json::from_str(text).unwrap();
+    let mut rows = vec![];
+    for point in points {
+        let index = metric_index(stable, point["metric"].as_str().unwrap());
+        let timestamp = json_to_field("timestamp", &point["timestamp"], stable.precision);
+        let value = json_to_field(stable.data_types[index], &point["value"], stable.precision);
+        let mut tags = vec![Field::Null; tag_names.len()];
+        for (name, tag) in point["tags"].as_object().unwrap() {
+            let tag_index = tag_names.iter().position(|t| t == name).unwrap();
+            tags[tag_index] = json_to_field(stable.tag_types[tag_index], tag, stable.precision);
+        }
+        push_point(stable, &mut rows, tags, timestamp, index, value);
+    }
+    rows
+}
+
+pub fn opentsdb_telnet_serialized_write(
+    filename: &str,
+    stable: &SuperTable,
+    tags: &[Field],
+    rows: &[Vec<Field>],
+) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(opentsdb_telnet_serialize(stable, tags, rows).as_bytes())
+        .unwrap();
+}
+
+pub fn opentsdb_telnet_read(filename: &str, stable: &SuperTable) -> u32 {
+    let mut lines = String::new();
+    File::open(filename)
+        .unwrap()
+        .read_to_string(&mut lines)
+        .unwrap();
+    opentsdb_telnet_parse(stable, &lines).len() as u32
+}
+
+pub fn opentsdb_json_serialized_write(
+    filename: &str,
+    stable: &SuperTable,
+    tags: &[Field],
+    rows: &[Vec<Field>],
+) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(opentsdb_json_serialize(stable, tags, rows).as_bytes())
+        .unwrap();
+}
+
+pub fn opentsdb_json_read(filename: &str, stable: &SuperTable) -> u32 {
+    let mut text = String::new();
+    File::open(filename)
+        .unwrap()
+        .read_to_string(&mut text)
+        .unwrap();
+    opentsdb_json_parse(stable, &text).len() as u32
+}
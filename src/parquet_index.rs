@@ -0,0 +1,773 @@
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    fs::File,
+    io::{BufReader, Cursor, Read, Seek, SeekFrom, Write},
+    ops::Range,
+};
+
+use bstr::BString;
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use parquet::{
+    basic::{Compression, ConvertedType},
+    column::reader::{get_column_reader, ColumnReader},
+    data_type::ByteArray,
+    file::{
+        metadata::ColumnChunkMetaData,
+        properties::WriterProperties,
+        reader::FileReader,
+        serialized_reader::{SerializedFileReader, SerializedPageReader},
+        writer::InMemoryWriteableCursor,
+    },
+    schema::types::ColumnDescriptor,
+};
+use parquet_format::{
+    BloomFilterAlgorithm, BloomFilterCompression, BloomFilterHash, BloomFilterHeader,
+    BoundaryOrder, ColumnChunk, ColumnIndex, FileMetaData, OffsetIndex, PageHeader, PageLocation,
+    PageType, SplitBlockAlgorithm, Statistics, Uncompressed, XxHash,
+};
+use thrift::protocol::{TCompactInputProtocol, TCompactOutputProtocol};
+
+use crate::{
+    my_parquet::{write_row_groups, ScanStats},
+    tscol::less,
+};
+
+// Page indexes and split block bloom filters as the Parquet spec lays them out. parquet
+// 9.1 writes neither, so the file is written as usual and its footer rewritten: the
+// pages of every column chunk are walked to build its offset index and, from the min/max
+// statistics in the page headers, its column index; bloom filters are built from the
+// values of the chosen columns. All three go between the last row group and the footer.
+//
+// On read, bloom filters rule out whole row groups, and the offset and column indexes
+// the pages of a row group whose min/max miss the value. The pages that are left are
+// fed to parquet's page reader on their own, after the dictionary page.
+
+pub struct ParquetIndexOptions<'a> {
+    pub page_index: bool,
+    pub bloom_filter_columns: &'a [&'a str],
+    pub bloom_filter_fpp: f64,
+    // rows per data page, so a row group has pages to index
+    pub page_rows: usize,
+}
+
+const P1: u64 = 0x9e3779b185ebca87;
+const P2: u64 = 0xc2b2ae3d27d4eb4f;
+const P3: u64 = 0x165667b19e3779f9;
+const P4: u64 = 0x85ebca77c2b2ae63;
+const P5: u64 = 0x27d4eb2f165667c5;
+
+fn xxhash64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(P2))
+        .rotate_left(31)
+        .wrapping_mul(P1)
+}
+
+fn xxhash64_merge(acc: u64, v: u64) -> u64 {
+    (acc ^ xxhash64_round(0, v))
+        .wrapping_mul(P1)
+        .wrapping_add(P4)
+}
+
+// XXH64, the hash Parquet bloom filters are specified with.
+pub fn xxhash64(data: &[u8], seed: u64) -> u64 {
+    let read64 = |i: usize| u64::from_le_bytes(data[i..i + 8].try_into().unwrap());
+    let len = data.len();
+    let mut i = 0;
+    let mut h = if len >= 32 {
+        let mut v = [
+            seed.wrapping_add(P1).wrapping_add(P2),
+            seed.wrapping_add(P2),
+            seed,
+            seed.wrapping_sub(P1),
+        ];
+        while i + 32 <= len {
+            for (lane, v) in v.iter_mut().enumerate() {
+                *v = xxhash64_round(*v, read64(i + lane * 8));
+            }
+            i += 32;
+        }
+        let mut h = v[0]
+            .rotate_left(1)
+            .wrapping_add(v[1].rotate_left(7))
+            .wrapping_add(v[2].rotate_left(12))
+            .wrapping_add(v[3].rotate_left(18));
+        for v in v {
+            h = xxhash64_merge(h, v);
+        }
+        h
+    } else {
+        seed.wrapping_add(P5)
+    };
+    h = h.wrapping_add(len as u64);
+    while i + 8 <= len {
+        h ^= xxhash64_round(0, read64(i));
+        h = h.rotate_left(27).wrapping_mul(P1).wrapping_add(P4);
+        i += 8;
+    }
+    if i + 4 <= len {
+        h ^= (u32::from_le_bytes(data[i..i + 4].try_into().unwrap()) as u64).wrapping_mul(P1);
+        h = h.rotate_left(23).wrapping_mul(P2).wrapping_add(P3);
+        i += 4;
+    }
+    while i < len {
+        h ^= (data[i] as u64).wrapping_mul(P5);
+        h = h.rotate_left(11).wrapping_mul(P1);
+        i += 1;
+    }
+    h ^= h >> 33;
+    h = h.wrapping_mul(P2);
+    h ^= h >> 29;
+    h = h.wrapping_mul(P3);
+    h ^ (h >> 32)
+}
+
+const SALT: [u32; 8] = [
+    0x47b6137b, 0x44974d91, 0x8824ad5b, 0xa2b7289d, 0x705495c7, 0x2df1424b, 0x9efc4947, 0x5c6bfb31,
+];
+
+// A split block bloom filter: blocks of eight 32 bit words, one bit set in each.
+pub struct BloomFilter {
+    blocks: Vec<[u32; 8]>,
+}
+
+impl BloomFilter {
+    // Sized for `ndv` distinct values at a false positive rate of `fpp`, as a power of
+    // two between 32 bytes and 128MB like other writers.
+    pub fn new(ndv: usize, fpp: f64) -> Self {
+        let bits = -8.0 * ndv as f64 / (1.0 - fpp.powf(1.0 / 8.0)).ln();
+        let bytes = ((bits / 8.0) as usize)
+            .next_power_of_two()
+            .clamp(32, 128 * 1024 * 1024);
+        Self {
+            blocks: vec![[0; 8]; bytes / 32],
+        }
+    }
+
+    fn block(&self, hash: u64) -> usize {
+        (((hash >> 32) * self.blocks.len() as u64) >> 32) as usize
+    }
+
+    pub fn insert(&mut self, hash: u64) {
+        let block = self.block(hash);
+        for (word, salt) in self.blocks[block].iter_mut().zip(SALT) {
+            *word |= 1 << ((hash as u32).wrapping_mul(salt) >> 27);
+        }
+    }
+
+    pub fn contains(&self, hash: u64) -> bool {
+        let block = &self.blocks[self.block(hash)];
+        block
+            .iter()
+            .zip(SALT)
+            .all(|(word, salt)| word & (1 << ((hash as u32).wrapping_mul(salt) >> 27)) != 0)
+    }
+
+    pub fn num_bytes(&self) -> usize {
+        self.blocks.len() * 32
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.blocks
+            .iter()
+            .flatten()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    fn from_bytes(buf: &[u8]) -> Self {
+        let blocks = buf
+            .chunks(32)
+            .map(|block| {
+                let mut words = [0; 8];
+                for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+                    *word = u32::from_le_bytes(bytes.try_into().unwrap());
+                }
+                words
+            })
+            .collect();
+        Self { blocks }
+    }
+}
+
+// The plain encoding of a value, which is what bloom filters hash and column indexes
+// store bounds in.
+pub fn plain_bytes(field: &Field) -> Vec<u8> {
+    match field {
+        Field::Bool(v) => vec![*v as u8],
+        Field::TinyInt(v) => (*v as i32).to_le_bytes().to_vec(),
+        Field::SmallInt(v) => (*v as i32).to_le_bytes().to_vec(),
+        Field::Int(v) => v.to_le_bytes().to_vec(),
+        Field::BigInt(v) => v.to_le_bytes().to_vec(),
+        Field::Float(v) => v.to_le_bytes().to_vec(),
+        Field::Double(v) => v.to_le_bytes().to_vec(),
+        Field::Binary(v) => v.to_vec(),
+        Field::Timestamp(v) => v.as_raw_timestamp().to_le_bytes().to_vec(),
+        Field::NChar(v) => v.as_bytes().to_vec(),
+        Field::UTinyInt(v) => (*v as i32).to_le_bytes().to_vec(),
+        Field::USmallInt(v) => (*v as i32).to_le_bytes().to_vec(),
+        Field::UInt(v) => v.to_le_bytes().to_vec(),
+        Field::UBigInt(v) => v.to_le_bytes().to_vec(),
+        _ => unreachable!(),
+    }
+}
+
+// Reads plain encoded `buf` as a value of the same type as `like`, if it is long enough
+// to hold one.
+fn from_plain_bytes(like: &Field, buf: &[u8]) -> Option<Field> {
+    let width = match like {
+        Field::Bool(_) => 1,
+        Field::BigInt(_) | Field::Double(_) | Field::Timestamp(_) | Field::UBigInt(_) => 8,
+        Field::Binary(_) | Field::NChar(_) => 0,
+        _ => 4,
+    };
+    if buf.len() < width {
+        return None;
+    }
+    let i32_at = || i32::from_le_bytes(buf[..4].try_into().unwrap());
+    let i64_at = || i64::from_le_bytes(buf[..8].try_into().unwrap());
+    Some(match like {
+        Field::Bool(_) => Field::Bool(buf[0] != 0),
+        Field::TinyInt(_) => Field::TinyInt(i32_at() as i8),
+        Field::SmallInt(_) => Field::SmallInt(i32_at() as i16),
+        Field::Int(_) => Field::Int(i32_at()),
+        Field::BigInt(_) => Field::BigInt(i64_at()),
+        Field::Float(_) => Field::Float(f32::from_le_bytes(buf[..4].try_into().unwrap())),
+        Field::Double(_) => Field::Double(f64::from_le_bytes(buf[..8].try_into().unwrap())),
+        Field::Binary(_) => Field::Binary(BString::from(buf)),
+        Field::Timestamp(_) => {
+            Field::Timestamp(Timestamp::new(i64_at(), TimestampPrecision::Milli))
+        }
+        Field::NChar(_) => Field::NChar(String::from_utf8_lossy(buf).to_string()),
+        Field::UTinyInt(_) => Field::UTinyInt(i32_at() as u8),
+        Field::USmallInt(_) => Field::USmallInt(i32_at() as u16),
+        Field::UInt(_) => Field::UInt(i32_at() as u32),
+        Field::UBigInt(_) => Field::UBigInt(i64_at() as u64),
+        _ => unreachable!(),
+    })
+}
+
+// Whether `value` may be within `min` and `max`. Bounds that do not hold a value of
+// `value`'s type, or are out of order for it like unsigned values ordered as signed, rule
+// nothing out.
+fn may_contain(value: &Field, min: &[u8], max: &[u8]) -> bool {
+    match (from_plain_bytes(value, min), from_plain_bytes(value, max)) {
+        (Some(min), Some(max)) => less(&max, &min) || !(less(value, &min) || less(&max, value)),
+        _ => true,
+    }
+}
+
+fn read_page_header(buf: &[u8]) -> (PageHeader, usize) {
+    let mut reader = buf;
+    let header =
+        PageHeader::read_from_in_protocol(&mut TCompactInputProtocol::new(&mut reader)).unwrap();
+    (header, buf.len() - reader.len())
+}
+
+fn write_thrift(buf: &mut Vec<u8>, write: impl FnOnce(&mut TCompactOutputProtocol<&mut Vec<u8>>)) {
+    write(&mut TCompactOutputProtocol::new(buf));
+}
+
+// The offset and column indexes of the column chunk starting at `start`. A column index
+// needs the bounds of every page that has values, so there is none if a page header
+// lacks them.
+fn page_indexes(buf: &[u8], start: usize, len: usize) -> (OffsetIndex, Option<ColumnIndex>) {
+    let mut page_locations = vec![];
+    let mut null_pages = vec![];
+    let mut min_values = vec![];
+    let mut max_values = vec![];
+    let mut null_counts = vec![];
+    let mut bounded = true;
+    let mut first_row_index = 0;
+    let mut pos = start;
+    while pos < start + len {
+        let (header, header_len) = read_page_header(&buf[pos..start + len]);
+        let page_len = header_len + header.compressed_page_size as usize;
+        let (rows, values, statistics) = match header.type_ {
+            PageType::DataPage => {
+                let data = header.data_page_header.unwrap();
+                (
+                    data.num_values as i64,
+                    data.num_values as i64,
+                    data.statistics,
+                )
+            }
+            PageType::DataPageV2 => {
+                let data = header.data_page_header_v2.unwrap();
+                (
+                    data.num_rows as i64,
+                    data.num_values as i64,
+                    data.statistics,
+                )
+            }
+            _ => {
+                pos += page_len;
+                continue;
+            }
+        };
+        // not `unwrap_or_default`: the thrift default has empty bounds and no nulls
+        let statistics =
+            statistics.unwrap_or_else(|| Statistics::new(None, None, None, None, None, None));
+        let min = statistics.min_value.or(statistics.min);
+        let max = statistics.max_value.or(statistics.max);
+        let null_page = statistics.null_count == Some(values);
+        match (min, max) {
+            (Some(min), Some(max)) if !null_page => {
+                min_values.push(min);
+                max_values.push(max);
+            }
+            // null pages have empty bounds
+            _ if null_page => {
+                min_values.push(vec![]);
+                max_values.push(vec![]);
+            }
+            _ => bounded = false,
+        }
+        null_pages.push(null_page);
+        null_counts.push(statistics.null_count);
+        page_locations.push(PageLocation::new(
+            pos as i64,
+            page_len as i32,
+            first_row_index,
+        ));
+        first_row_index += rows;
+        pos += page_len;
+    }
+    let column_index = bounded.then(|| {
+        ColumnIndex::new(
+            null_pages,
+            min_values,
+            max_values,
+            BoundaryOrder::Unordered,
+            null_counts.into_iter().collect::<Option<Vec<_>>>(),
+        )
+    });
+    (OffsetIndex::new(page_locations), column_index)
+}
+
+fn footer(buf: &[u8]) -> (FileMetaData, usize) {
+    let len = u32::from_le_bytes(buf[buf.len() - 8..buf.len() - 4].try_into().unwrap()) as usize;
+    let start = buf.len() - 8 - len;
+    let mut reader = &buf[start..buf.len() - 8];
+    let metadata =
+        FileMetaData::read_from_in_protocol(&mut TCompactInputProtocol::new(&mut reader)).unwrap();
+    (metadata, start)
+}
+
+// Rewrites the footer of the parquet file in `buf` with the indexes `options` asks for.
+fn add_indexes(
+    mut buf: Vec<u8>,
+    cols: &[Vec<Field>],
+    row_group_rows: usize,
+    options: &ParquetIndexOptions,
+) -> Vec<u8> {
+    let (mut metadata, footer_start) = footer(&buf);
+    let mut page_indexes_of = vec![];
+    for row_group in &metadata.row_groups {
+        for chunk in &row_group.columns {
+            let column = chunk.meta_data.as_ref().unwrap();
+            let start = column
+                .dictionary_page_offset
+                .unwrap_or(column.data_page_offset)
+                .min(column.data_page_offset) as usize;
+            page_indexes_of.push(page_indexes(
+                &buf,
+                start,
+                column.total_compressed_size as usize,
+            ));
+        }
+    }
+    buf.truncate(footer_start);
+    for (i, row_group) in metadata.row_groups.iter_mut().enumerate() {
+        let rows = i * row_group_rows..((i + 1) * row_group_rows).min(cols[0].len());
+        for (j, chunk) in row_group.columns.iter_mut().enumerate() {
+            // the schema root is followed by the columns
+            if !options
+                .bloom_filter_columns
+                .contains(&metadata.schema[j + 1].name.as_str())
+            {
+                continue;
+            }
+            let values = &cols[j][rows.clone()];
+            let distinct: HashSet<Vec<u8>> = values
+                .iter()
+                .filter(|value| !matches!(value, Field::Null))
+                .map(plain_bytes)
+                .collect();
+            let mut filter = BloomFilter::new(distinct.len(), options.bloom_filter_fpp);
+            for value in distinct {
+                filter.insert(xxhash64(&value, 0));
+            }
+            chunk.meta_data.as_mut().unwrap().bloom_filter_offset = Some(buf.len() as i64);
+            let header = BloomFilterHeader::new(
+                filter.num_bytes() as i32,
+                BloomFilterAlgorithm::BLOCK(SplitBlockAlgorithm::new()),
+                BloomFilterHash::XXHASH(XxHash::new()),
+                BloomFilterCompression::UNCOMPRESSED(Uncompressed::new()),
+            );
+            write_thrift(&mut buf, |protocol| {
+                header.write_to_out_protocol(protocol).unwrap()
+            });
+            buf.extend(filter.to_bytes());
+        }
+    }
+    if options.page_index {
+        let chunks = metadata
+            .row_groups
+            .iter_mut()
+            .flat_map(|row_group| row_group.columns.iter_mut());
+        for (chunk, (_, column_index)) in chunks.zip(&page_indexes_of) {
+            let column_index = match column_index {
+                Some(column_index) => column_index,
+                None => continue,
+            };
+            let start = buf.len();
+            write_thrift(&mut buf, |protocol| {
+                column_index.write_to_out_protocol(protocol).unwrap()
+            });
+            chunk.column_index_offset = Some(start as i64);
+            chunk.column_index_length = Some((buf.len() - start) as i32);
+        }
+        let chunks = metadata
+            .row_groups
+            .iter_mut()
+            .flat_map(|row_group| row_group.columns.iter_mut());
+        for (chunk, (offset_index, _)) in chunks.zip(&page_indexes_of) {
+            let start = buf.len();
+            write_thrift(&mut buf, |protocol| {
+                offset_index.write_to_out_protocol(protocol).unwrap()
+            });
+            chunk.offset_index_offset = Some(start as i64);
+            chunk.offset_index_length = Some((buf.len() - start) as i32);
+        }
+    }
+    let start = buf.len();
+    write_thrift(&mut buf, |protocol| {
+        metadata.write_to_out_protocol(protocol).unwrap()
+    });
+    let len = (buf.len() - start) as u32;
+    buf.extend_from_slice(&len.to_le_bytes());
+    buf.extend_from_slice(b"PAR1");
+    buf
+}
+
+pub fn parquet_serialized_write_indexed(
+    filename: &str,
+    data_types: &Vec<&str>,
+    cols: &[Vec<Field>],
+    compression: Compression,
+    row_group_rows: usize,
+    options: &ParquetIndexOptions,
+) {
+    let cursor = InMemoryWriteableCursor::default();
+    // a page for every `page_rows` rows: each write batch goes over any page size limit
+    let props = WriterProperties::builder()
+        .set_compression(compression)
+        .set_write_batch_size(options.page_rows)
+        .set_data_pagesize_limit(1)
+        .build();
+    write_row_groups(cursor.clone(), props, data_types, cols, row_group_rows);
+    let buf = add_indexes(cursor.data(), cols, row_group_rows, options);
+    File::create(filename).unwrap().write_all(&buf).unwrap();
+}
+
+fn read_at(file: &File, offset: u64) -> BufReader<&File> {
+    let mut reader = BufReader::new(file);
+    reader.seek(SeekFrom::Start(offset)).unwrap();
+    reader
+}
+
+fn bloom_filter_at(file: &File, offset: u64) -> BloomFilter {
+    let mut reader = read_at(file, offset);
+    let header =
+        BloomFilterHeader::read_from_in_protocol(&mut TCompactInputProtocol::new(&mut reader))
+            .unwrap();
+    let mut bitset = vec![0; header.num_bytes as usize];
+    reader.read_exact(&mut bitset).unwrap();
+    BloomFilter::from_bytes(&bitset)
+}
+
+fn column_index_at(file: &File, offset: u64) -> ColumnIndex {
+    let mut reader = read_at(file, offset);
+    ColumnIndex::read_from_in_protocol(&mut TCompactInputProtocol::new(&mut reader)).unwrap()
+}
+
+fn read_footer(file: &File) -> FileMetaData {
+    let mut tail = vec![];
+    // the footer and its length are all that is needed of the tail
+    let len = file.metadata().unwrap().len();
+    let mut footer_len = [0; 8];
+    read_at(file, len - 8).read_exact(&mut footer_len).unwrap();
+    let footer_len = u32::from_le_bytes(footer_len[..4].try_into().unwrap()) as u64 + 8;
+    read_at(file, len - footer_len)
+        .read_to_end(&mut tail)
+        .unwrap();
+    footer(&tail).0
+}
+
+fn read_bytes(file: &File, offset: u64, len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    read_at(file, offset).read_exact(&mut buf).unwrap();
+    buf
+}
+
+struct PageRange {
+    offset: u64,
+    len: usize,
+    rows: Range<usize>,
+}
+
+// The pages of a column chunk and the rows of its row group each one holds, with where
+// its dictionary page is. Without an offset index the chunk is one page of all the rows.
+fn page_ranges(
+    file: &File,
+    chunk: &ColumnChunk,
+    num_rows: usize,
+) -> (Option<PageRange>, Vec<PageRange>) {
+    let column = chunk.meta_data.as_ref().unwrap();
+    let offset_index = match chunk.offset_index_offset {
+        Some(offset) => {
+            let mut reader = read_at(file, offset as u64);
+            OffsetIndex::read_from_in_protocol(&mut TCompactInputProtocol::new(&mut reader))
+                .unwrap()
+        }
+        None => {
+            let start = column
+                .dictionary_page_offset
+                .unwrap_or(column.data_page_offset)
+                .min(column.data_page_offset);
+            let page = PageRange {
+                offset: start as u64,
+                len: column.total_compressed_size as usize,
+                rows: 0..num_rows,
+            };
+            return (None, vec![page]);
+        }
+    };
+    let locations = &offset_index.page_locations;
+    let dictionary = column.dictionary_page_offset.map(|offset| PageRange {
+        offset: offset as u64,
+        len: (locations[0].offset - offset) as usize,
+        rows: 0..0,
+    });
+    let pages = locations
+        .iter()
+        .enumerate()
+        .map(|(i, location)| {
+            let end = locations
+                .get(i + 1)
+                .map_or(num_rows, |next| next.first_row_index as usize);
+            PageRange {
+                offset: location.offset as u64,
+                len: location.compressed_page_size as usize,
+                rows: location.first_row_index as usize..end,
+            }
+        })
+        .collect();
+    (dictionary, pages)
+}
+
+// Decodes `count` values of a flat column, nulls included.
+fn decode_column(reader: ColumnReader, descr: &ColumnDescriptor, count: usize) -> Vec<Field> {
+    macro_rules! decode {
+        ($reader:expr, $to_field:expr) => {{
+            let mut reader = $reader;
+            let mut values = vec![Default::default(); count];
+            let mut def_levels = vec![0; count];
+            let (values_read, levels_read) = reader
+                .read_batch(count, Some(&mut def_levels), None, &mut values)
+                .unwrap();
+            let mut values = values.into_iter().take(values_read).map($to_field);
+            if descr.max_def_level() == 0 {
+                values.collect()
+            } else {
+                def_levels[..levels_read]
+                    .iter()
+                    .map(|level| match *level == descr.max_def_level() {
+                        true => values.next().unwrap(),
+                        false => Field::Null,
+                    })
+                    .collect::<Vec<_>>()
+            }
+        }};
+    }
+    let converted = descr.converted_type();
+    match reader {
+        ColumnReader::BoolColumnReader(reader) => decode!(reader, Field::Bool),
+        ColumnReader::Int32ColumnReader(reader) => decode!(reader, |v: i32| match converted {
+            ConvertedType::INT_8 => Field::TinyInt(v as i8),
+            ConvertedType::INT_16 => Field::SmallInt(v as i16),
+            ConvertedType::UINT_8 => Field::UTinyInt(v as u8),
+            ConvertedType::UINT_16 => Field::USmallInt(v as u16),
+            ConvertedType::UINT_32 => Field::UInt(v as u32),
+            _ => Field::Int(v),
+        }),
+        ColumnReader::Int64ColumnReader(reader) => decode!(reader, |v: i64| match converted {
+            ConvertedType::TIMESTAMP_MILLIS => {
+                Field::Timestamp(Timestamp::new(v, TimestampPrecision::Milli))
+            }
+            ConvertedType::UINT_64 => Field::UBigInt(v as u64),
+            _ => Field::BigInt(v),
+        }),
+        ColumnReader::FloatColumnReader(reader) => decode!(reader, Field::Float),
+        ColumnReader::DoubleColumnReader(reader) => decode!(reader, Field::Double),
+        ColumnReader::ByteArrayColumnReader(reader) => {
+            decode!(reader, |v: ByteArray| match converted {
+                ConvertedType::UTF8 => Field::NChar(String::from_utf8(v.data().to_vec()).unwrap()),
+                _ => Field::Binary(BString::from(v.data())),
+            })
+        }
+        _ => unreachable!("unexpected parquet column {:?}", descr.physical_type()),
+    }
+}
+
+// Reads `pages` of a column chunk alone: their bytes, after the dictionary page, go
+// through parquet's page reader as if they were the whole chunk.
+fn read_pages(
+    file: &File,
+    column: &ColumnChunkMetaData,
+    dictionary: &Option<PageRange>,
+    pages: &[&PageRange],
+) -> Vec<(usize, Field)> {
+    let mut buf = vec![];
+    for page in dictionary.iter().chain(pages.iter().copied()) {
+        buf.extend(read_bytes(file, page.offset, page.len));
+    }
+    let count = pages.iter().map(|page| page.rows.len()).sum::<usize>();
+    let page_reader = SerializedPageReader::new(
+        Cursor::new(buf),
+        count as i64,
+        column.compression(),
+        column.column_type(),
+    )
+    .unwrap();
+    let descr = column.column_descr_ptr();
+    let reader = get_column_reader(descr.clone(), Box::new(page_reader));
+    let rows = pages.iter().flat_map(|page| page.rows.clone());
+    rows.zip(decode_column(reader, &descr, count)).collect()
+}
+
+// What a filtered read looks for in its column.
+pub(crate) struct Filter<'a> {
+    // whether a row group or page with these plain encoded bounds may hold a match
+    pub may_match: &'a dyn Fn(&[u8], &[u8]) -> bool,
+    pub matches: &'a dyn Fn(&Field) -> bool,
+    // the hash of the value an equality looks for, to probe bloom filters with
+    pub hash: Option<u64>,
+}
+
+// Rows whose `column` passes `filter`. Row groups are skipped by chunk statistics and
+// bloom filter; within a row group, when the file has page indexes, only the pages of
+// `column` whose bounds may match are decoded, and of the other columns only the pages
+// holding a match. `rows_scanned` counts the rows of `column` decoded.
+pub(crate) fn parquet_read_filtered(
+    filename: &str,
+    column: &str,
+    filter: &Filter,
+) -> (Vec<Vec<Field>>, ScanStats) {
+    let file = File::open(filename).unwrap();
+    let metadata = read_footer(&file);
+    let index = metadata.schema[1..]
+        .iter()
+        .position(|element| element.name == column)
+        .unwrap_or_else(|| panic!("no column {} in {}", column, filename));
+    let reader = SerializedFileReader::new(file.try_clone().unwrap()).unwrap();
+    let mut stats = ScanStats {
+        row_groups: metadata.row_groups.len(),
+        ..Default::default()
+    };
+    let mut rows = vec![];
+    for (i, row_group) in metadata.row_groups.iter().enumerate() {
+        let chunk = &row_group.columns[index];
+        let column = chunk.meta_data.as_ref().unwrap();
+        if let Some(statistics) = &column.statistics {
+            let min = statistics.min_value.as_ref().or(statistics.min.as_ref());
+            let max = statistics.max_value.as_ref().or(statistics.max.as_ref());
+            if let (Some(min), Some(max)) = (min, max) {
+                if !(filter.may_match)(min, max) {
+                    continue;
+                }
+            }
+        }
+        if let (Some(hash), Some(offset)) = (filter.hash, column.bloom_filter_offset) {
+            if !bloom_filter_at(&file, offset as u64).contains(hash) {
+                continue;
+            }
+        }
+        let num_rows = row_group.num_rows as usize;
+        let (dictionary, pages) = page_ranges(&file, chunk, num_rows);
+        let candidates: Vec<_> = match chunk.column_index_offset {
+            Some(offset) => {
+                let column_index = column_index_at(&file, offset as u64);
+                pages
+                    .iter()
+                    .zip(&column_index.null_pages)
+                    .zip(column_index.min_values.iter().zip(&column_index.max_values))
+                    .filter(|((_, null_page), (min, max))| {
+                        !**null_page && (filter.may_match)(min, max)
+                    })
+                    .map(|((page, _), _)| page)
+                    .collect()
+            }
+            None => pages.iter().collect(),
+        };
+        if candidates.is_empty() {
+            continue;
+        }
+        stats.row_groups_read += 1;
+        stats.rows_scanned += candidates.iter().map(|page| page.rows.len()).sum::<usize>();
+        let metadata = reader.metadata().row_group(i);
+        let values = read_pages(&file, metadata.column(index), &dictionary, &candidates);
+        let matches: Vec<_> = values
+            .into_iter()
+            .filter(|(_, value)| (filter.matches)(value))
+            .collect();
+        if matches.is_empty() {
+            continue;
+        }
+        let mut matched_rows: Vec<_> = matches
+            .iter()
+            .map(|_| vec![Field::Null; row_group.columns.len()])
+            .collect();
+        for (j, chunk) in row_group.columns.iter().enumerate() {
+            if j == index {
+                for (row, (_, value)) in matched_rows.iter_mut().zip(&matches) {
+                    row[j] = value.clone();
+                }
+                continue;
+            }
+            let (dictionary, pages) = page_ranges(&file, chunk, num_rows);
+            let holding: Vec<_> = pages
+                .iter()
+                .filter(|page| {
+                    let k = matches.partition_point(|(row, _)| *row < page.rows.start);
+                    k < matches.len() && matches[k].0 < page.rows.end
+                })
+                .collect();
+            let mut values = read_pages(&file, metadata.column(j), &dictionary, &holding)
+                .into_iter()
+                .peekable();
+            for (row, (k, _)) in matched_rows.iter_mut().zip(&matches) {
+                while values.next_if(|(l, _)| l < k).is_some() {}
+                row[j] = values.next().unwrap().1;
+            }
+        }
+        rows.extend(matched_rows);
+    }
+    (rows, stats)
+}
+
+// Rows whose `column` equals `value`, skipping row groups by chunk statistics and bloom
+// filter and, within a row group, pages by column index.
+pub fn parquet_read_eq(
+    filename: &str,
+    column: &str,
+    value: &Field,
+) -> (Vec<Vec<Field>>, ScanStats) {
+    let filter = Filter {
+        may_match: &|min, max| may_contain(value, min, max),
+        matches: &|field| field == value,
+        hash: Some(xxhash64(&plain_bytes(value), 0)),
+    };
+    parquet_read_filtered(filename, column, &filter)
+}
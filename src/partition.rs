@@ -0,0 +1,326 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use avro_rs::Codec;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Timelike};
+use libtaos::Field;
+use parquet::basic::Compression;
+
+use crate::{
+    avro::{avro_deserialize, avro_serialized_write},
+    manifest::committed_write,
+    millis,
+    my_parquet::{parquet_read_projected, parquet_serialized_write_row_groups},
+    rows_to_cols, SuperTable,
+};
+
+// Hive style partitions of a super table, one directory per time partition and, below
+// it, per value of each partitioning tag:
+//
+// <root>/stable=meters/date=2026-10-18/hour=13/t_binary=abc/part-0001.parquet
+//
+// with `month=2026-10` for monthly partitions. Times are UTC. Files hold the data
+// columns only, so a tag survives only if it partitions the table. Each write adds a
+// part to the partitions it touches, committed so readers never see it half written.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Granularity {
+    Hour,
+    Day,
+    Month,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum PartitionFormat {
+    Avro(Codec),
+    Parquet(Compression),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Partition {
+    pub path: String,
+    // the `key=value` pairs of the path below the super table
+    pub values: Vec<(String, String)>,
+    // [start, end) in ms
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct PruneStats {
+    pub partitions: usize,
+    pub partitions_read: usize,
+    pub files_read: usize,
+}
+
+fn datetime(ms: i64) -> NaiveDateTime {
+    DateTime::from_timestamp_millis(ms).unwrap().naive_utc()
+}
+
+fn time_values(granularity: Granularity, ms: i64) -> Vec<(String, String)> {
+    let t = datetime(ms);
+    let date = format!("{:04}-{:02}-{:02}", t.year(), t.month(), t.day());
+    match granularity {
+        Granularity::Hour => vec![
+            ("date".to_string(), date),
+            ("hour".to_string(), format!("{:02}", t.hour())),
+        ],
+        Granularity::Day => vec![("date".to_string(), date)],
+        Granularity::Month => vec![(
+            "month".to_string(),
+            format!("{:04}-{:02}", t.year(), t.month()),
+        )],
+    }
+}
+
+fn millis_of(date: NaiveDate, hour: u32) -> Option<i64> {
+    Some(date.and_hms_opt(hour, 0, 0)?.and_utc().timestamp_millis())
+}
+
+// The time range of a partition from its `date`, `hour` and `month` values, or all time
+// if it has none. None if a value is not a time.
+fn time_range(values: &[(String, String)]) -> Option<(i64, i64)> {
+    let value = |key: &str| {
+        values
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    match (value("date"), value("hour"), value("month")) {
+        (Some(date), hour, _) => {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            match hour {
+                Some(hour) => {
+                    let start = millis_of(date, hour.parse().ok()?)?;
+                    Some((start, start + 3_600_000))
+                }
+                None => Some((millis_of(date, 0)?, millis_of(date.succ_opt()?, 0)?)),
+            }
+        }
+        (None, _, Some(month)) => {
+            let start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+            let end = match start.month() {
+                12 => NaiveDate::from_ymd_opt(start.year() + 1, 1, 1),
+                month => NaiveDate::from_ymd_opt(start.year(), month + 1, 1),
+            };
+            Some((millis_of(start, 0)?, millis_of(end?, 0)?))
+        }
+        _ => Some((i64::MIN, i64::MAX)),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('/', "%2F")
+        .replace('=', "%3D")
+}
+
+fn tag_value(field: &Field) -> String {
+    match field {
+        Field::Null => "__NULL__".to_string(),
+        Field::Bool(v) => v.to_string(),
+        Field::TinyInt(v) => v.to_string(),
+        Field::SmallInt(v) => v.to_string(),
+        Field::Int(v) => v.to_string(),
+        Field::BigInt(v) => v.to_string(),
+        Field::Float(v) => v.to_string(),
+        Field::Double(v) => v.to_string(),
+        Field::Binary(v) => escape(&v.to_string()),
+        Field::Timestamp(v) => v.as_raw_timestamp().to_string(),
+        Field::NChar(v) => escape(v),
+        Field::UTinyInt(v) => v.to_string(),
+        Field::USmallInt(v) => v.to_string(),
+        Field::UInt(v) => v.to_string(),
+        Field::UBigInt(v) => v.to_string(),
+        Field::Json(v) => escape(&v.to_string()),
+    }
+}
+
+fn stable_dir(root: &str, stable: &SuperTable) -> String {
+    format!("{}/stable={}", root, stable.name)
+}
+
+// The next part after the highest numbered one in `dir`, so parts that were removed
+// leave gaps rather than get written over.
+fn part_path(dir: &str, extension: &str) -> String {
+    let last = fs::read_dir(dir)
+        .unwrap()
+        .filter_map(|entry| {
+            let name = entry.unwrap().file_name().to_string_lossy().to_string();
+            let number = name.strip_prefix("part-")?.split('.').next()?;
+            number.parse::<u32>().ok()
+        })
+        .max()
+        .unwrap_or(0);
+    let path = format!("{}/part-{:04}.{}", dir, last + 1, extension);
+    assert!(!Path::new(&path).exists(), "{} already exists", path);
+    path
+}
+
+// Writes the rows of each sub table, given with its tags, to the partitions of their
+// timestamps and of the values of `partition_tags`, names from `stable.tag_names()`.
+// Returns the files written.
+pub fn partitioned_write(
+    root: &str,
+    stable: &SuperTable,
+    granularity: Granularity,
+    partition_tags: &[&str],
+    format: PartitionFormat,
+    tables: &[(Vec<Field>, Vec<Vec<Field>>)],
+) -> Vec<String> {
+    let ts_column = stable
+        .data_types
+        .iter()
+        .position(|t| *t == "timestamp")
+        .unwrap();
+    let tag_names = stable.tag_names();
+    let tag_columns: Vec<usize> = partition_tags
+        .iter()
+        .map(|name| {
+            tag_names
+                .iter()
+                .position(|t| t == name)
+                .unwrap_or_else(|| panic!("no tag {} in {}", name, stable.name))
+        })
+        .collect();
+    let mut partitions: BTreeMap<String, Vec<Vec<Field>>> = BTreeMap::new();
+    for (tags, rows) in tables {
+        let tag_path: String = partition_tags
+            .iter()
+            .zip(&tag_columns)
+            .map(|(name, &column)| format!("/{}={}", name, tag_value(&tags[column])))
+            .collect();
+        for row in rows {
+            let ts = match &row[ts_column] {
+                Field::Timestamp(v) => millis(v.as_raw_timestamp(), stable.precision),
+                _ => unreachable!(),
+            };
+            let time_path: String = time_values(granularity, ts)
+                .iter()
+                .map(|(key, value)| format!("/{}={}", key, value))
+                .collect();
+            let dir = format!("{}{}{}", stable_dir(root, stable), time_path, tag_path);
+            partitions.entry(dir).or_default().push(row.clone());
+        }
+    }
+    let mut files = vec![];
+    for (dir, rows) in partitions {
+        fs::create_dir_all(&dir).unwrap();
+        let path = match format {
+            PartitionFormat::Avro(codec) => {
+                let path = part_path(&dir, "avro");
+                committed_write(&path, |temp| {
+                    avro_serialized_write(temp, stable.data_types, &rows, codec, None)
+                });
+                path
+            }
+            PartitionFormat::Parquet(compression) => {
+                let path = part_path(&dir, "parquet");
+                committed_write(&path, |temp| {
+                    parquet_serialized_write_row_groups(
+                        temp,
+                        stable.data_types,
+                        &rows_to_cols(&rows),
+                        compression,
+                        rows.len(),
+                    )
+                });
+                path
+            }
+        };
+        files.push(path);
+    }
+    files
+}
+
+fn discover(dir: &Path, values: &mut Vec<(String, String)>, partitions: &mut Vec<Partition>) {
+    let mut has_files = false;
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        match name.split_once('=') {
+            Some((key, value)) if path.is_dir() => {
+                values.push((key.to_string(), value.to_string()));
+                // a stray directory whose time values are not times is no partition
+                if time_range(values).is_some() {
+                    discover(&path, values, partitions);
+                }
+                values.pop();
+            }
+            _ => has_files |= name.starts_with("part-"),
+        }
+    }
+    if has_files {
+        let (start, end) = time_range(values).unwrap();
+        partitions.push(Partition {
+            path: dir.to_string_lossy().to_string(),
+            values: values.clone(),
+            start,
+            end,
+        });
+    }
+}
+
+// The partitions of `stable` under `root` with any files, in path order.
+pub fn partition_discover(root: &str, stable: &SuperTable) -> Vec<Partition> {
+    let mut partitions = vec![];
+    let dir = stable_dir(root, stable);
+    if Path::new(&dir).is_dir() {
+        discover(Path::new(&dir), &mut vec![], &mut partitions);
+    }
+    partitions
+}
+
+// Rows of `stable` with timestamps in [start, end] ms, reading only the partitions
+// whose time range overlaps it.
+pub fn partitioned_read(
+    root: &str,
+    stable: &SuperTable,
+    start: i64,
+    end: i64,
+) -> (Vec<Vec<Field>>, PruneStats) {
+    let ts_column = stable
+        .data_types
+        .iter()
+        .position(|t| *t == "timestamp")
+        .unwrap();
+    let partitions = partition_discover(root, stable);
+    let mut stats = PruneStats {
+        partitions: partitions.len(),
+        ..Default::default()
+    };
+    let mut rows = vec![];
+    for partition in partitions {
+        if partition.end <= start || partition.start > end {
+            continue;
+        }
+        stats.partitions_read += 1;
+        let mut files: Vec<_> = fs::read_dir(&partition.path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file())
+            .collect();
+        files.sort();
+        for file in files {
+            let filename = file.to_string_lossy().to_string();
+            let read = match file.extension().and_then(|e| e.to_str()) {
+                Some("avro") => avro_deserialize(&fs::read(&file).unwrap()),
+                Some("parquet") => parquet_read_projected(&filename, stable.data_types),
+                _ => continue,
+            };
+            stats.files_read += 1;
+            rows.extend(read.into_iter().filter(|row| match &row[ts_column] {
+                Field::Timestamp(v) => {
+                    let ts = millis(v.as_raw_timestamp(), stable.precision);
+                    start <= ts && ts <= end
+                }
+                _ => unreachable!(),
+            }));
+        }
+    }
+    (rows, stats)
+}
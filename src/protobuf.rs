@@ -0,0 +1,235 @@
+use bstr::BString;
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use prost::Message;
+
+use crate::timestamp_precision;
+
+pub mod pb {
+    include!(concat!(env!("OUT_DIR"), "/taosx.rs"));
+}
+
+use pb::{column, value};
+
+pub fn field_to_protobuf_value(field: &Field) -> pb::Value {
+    let value = match field {
+        Field::Null => None,
+        Field::Bool(v) => Some(value::Value::Bool(*v)),
+        Field::TinyInt(v) => Some(value::Value::Tinyint(*v as i32)),
+        Field::SmallInt(v) => Some(value::Value::Smallint(*v as i32)),
+        Field::Int(v) => Some(value::Value::Int(*v)),
+        Field::BigInt(v) => Some(value::Value::Bigint(*v)),
+        Field::Float(v) => Some(value::Value::Float(*v)),
+        Field::Double(v) => Some(value::Value::Double(*v)),
+        Field::Binary(v) => Some(value::Value::Binary(v.to_vec())),
+        Field::Timestamp(v) => Some(value::Value::Timestamp(v.as_raw_timestamp())),
+        Field::NChar(v) => Some(value::Value::Nchar(v.clone())),
+        Field::UTinyInt(v) => Some(value::Value::Utinyint(*v as u32)),
+        Field::USmallInt(v) => Some(value::Value::Usmallint(*v as u32)),
+        Field::UInt(v) => Some(value::Value::Uint(*v)),
+        Field::UBigInt(v) => Some(value::Value::Ubigint(*v)),
+        Field::Json(v) => Some(value::Value::Json(v.to_string())),
+    };
+    pb::Value { value }
+}
+
+pub fn protobuf_value_to_field(value: &pb::Value, precision: TimestampPrecision) -> Field {
+    match &value.value {
+        None => Field::Null,
+        Some(value::Value::Bool(v)) => Field::Bool(*v),
+        Some(value::Value::Tinyint(v)) => Field::TinyInt(*v as i8),
+        Some(value::Value::Smallint(v)) => Field::SmallInt(*v as i16),
+        Some(value::Value::Int(v)) => Field::Int(*v),
+        Some(value::Value::Bigint(v)) => Field::BigInt(*v),
+        Some(value::Value::Float(v)) => Field::Float(*v),
+        Some(value::Value::Double(v)) => Field::Double(*v),
+        Some(value::Value::Binary(v)) => Field::Binary(BString::from(v.clone())),
+        Some(value::Value::Timestamp(v)) => Field::Timestamp(Timestamp::new(*v, precision)),
+        Some(value::Value::Nchar(v)) => Field::NChar(v.clone()),
+        Some(value::Value::Utinyint(v)) => Field::UTinyInt(*v as u8),
+        Some(value::Value::Usmallint(v)) => Field::USmallInt(*v as u16),
+        Some(value::Value::Uint(v)) => Field::UInt(*v),
+        Some(value::Value::Ubigint(v)) => Field::UBigInt(*v),
+        Some(value::Value::Json(v)) => Field::Json(serde_json::from_str(v).unwrap()),
+    }
+}
+
+// The precision of the first timestamp, as a batch has only one.
+fn precision_of<'a>(fields: impl IntoIterator<Item = &'a Field>) -> TimestampPrecision {
+    fields
+        .into_iter()
+        .find_map(|field| match field {
+            Field::Timestamp(v) => Some(timestamp_precision(v)),
+            _ => None,
+        })
+        .unwrap_or(TimestampPrecision::Milli)
+}
+
+pub fn rows_to_protobuf(rows: &[Vec<Field>]) -> pb::RowBatch {
+    let precision = precision_of(rows.iter().flatten());
+    let rows = rows
+        .iter()
+        .map(|row| pb::Row {
+            values: row.iter().map(field_to_protobuf_value).collect(),
+        })
+        .collect();
+    pb::RowBatch {
+        rows,
+        precision: precision as i32,
+    }
+}
+
+pub fn protobuf_to_rows(batch: &pb::RowBatch) -> Vec<Vec<Field>> {
+    let precision = TimestampPrecision::from(batch.precision);
+    batch
+        .rows
+        .iter()
+        .map(|row| {
+            row.values
+                .iter()
+                .map(|value| protobuf_value_to_field(value, precision))
+                .collect()
+        })
+        .collect()
+}
+
+pub fn column_to_protobuf(col: &[Field]) -> pb::Column {
+    macro_rules! values {
+        ($field:pat => $value:expr) => {
+            col.iter()
+                .filter_map(|field| match field {
+                    Field::Null => None,
+                    $field => Some($value),
+                    _ => unreachable!(),
+                })
+                .collect()
+        };
+    }
+    macro_rules! typed {
+        ($variant:ident, $values:ident, $field:pat => $value:expr) => {
+            Some(column::Values::$variant(pb::$values {
+                values: values!($field => $value),
+            }))
+        };
+    }
+    // columns are typed by their first value that is not null
+    let values = match col.iter().find(|field| !matches!(field, Field::Null)) {
+        None | Some(Field::Null) => None,
+        Some(Field::Bool(_)) => typed!(Bool, BoolValues, Field::Bool(v) => *v),
+        Some(Field::TinyInt(_)) => typed!(Tinyint, Int32Values, Field::TinyInt(v) => *v as i32),
+        Some(Field::SmallInt(_)) => {
+            typed!(Smallint, Int32Values, Field::SmallInt(v) => *v as i32)
+        }
+        Some(Field::Int(_)) => typed!(Int, Int32Values, Field::Int(v) => *v),
+        Some(Field::BigInt(_)) => typed!(Bigint, Int64Values, Field::BigInt(v) => *v),
+        Some(Field::Float(_)) => typed!(Float, FloatValues, Field::Float(v) => *v),
+        Some(Field::Double(_)) => typed!(Double, DoubleValues, Field::Double(v) => *v),
+        Some(Field::Binary(_)) => typed!(Binary, BytesValues, Field::Binary(v) => v.to_vec()),
+        Some(Field::Timestamp(first)) => Some(column::Values::Timestamp(pb::TimestampValues {
+            values: values!(Field::Timestamp(v) => v.as_raw_timestamp()),
+            precision: timestamp_precision(first) as i32,
+        })),
+        Some(Field::NChar(_)) => typed!(Nchar, StringValues, Field::NChar(v) => v.clone()),
+        Some(Field::UTinyInt(_)) => {
+            typed!(Utinyint, UInt32Values, Field::UTinyInt(v) => *v as u32)
+        }
+        Some(Field::USmallInt(_)) => {
+            typed!(Usmallint, UInt32Values, Field::USmallInt(v) => *v as u32)
+        }
+        Some(Field::UInt(_)) => typed!(Uint, UInt32Values, Field::UInt(v) => *v),
+        Some(Field::UBigInt(_)) => typed!(Ubigint, UInt64Values, Field::UBigInt(v) => *v),
+        Some(Field::Json(_)) => typed!(Json, StringValues, Field::Json(v) => v.to_string()),
+    };
+    let nulls = col
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| matches!(field, Field::Null))
+        .map(|(row, _)| row as u32)
+        .collect();
+    pb::Column { values, nulls }
+}
+
+pub fn protobuf_to_column(col: &pb::Column) -> Vec<Field> {
+    let values: Vec<Field> = match &col.values {
+        None => vec![],
+        Some(column::Values::Bool(v)) => v.values.iter().map(|v| Field::Bool(*v)).collect(),
+        Some(column::Values::Tinyint(v)) => {
+            v.values.iter().map(|v| Field::TinyInt(*v as i8)).collect()
+        }
+        Some(column::Values::Smallint(v)) => v
+            .values
+            .iter()
+            .map(|v| Field::SmallInt(*v as i16))
+            .collect(),
+        Some(column::Values::Int(v)) => v.values.iter().map(|v| Field::Int(*v)).collect(),
+        Some(column::Values::Bigint(v)) => v.values.iter().map(|v| Field::BigInt(*v)).collect(),
+        Some(column::Values::Float(v)) => v.values.iter().map(|v| Field::Float(*v)).collect(),
+        Some(column::Values::Double(v)) => v.values.iter().map(|v| Field::Double(*v)).collect(),
+        Some(column::Values::Binary(v)) => v
+            .values
+            .iter()
+            .map(|v| Field::Binary(BString::from(v.clone())))
+            .collect(),
+        Some(column::Values::Timestamp(v)) => {
+            let precision = TimestampPrecision::from(v.precision);
+            v.values
+                .iter()
+                .map(|v| Field::Timestamp(Timestamp::new(*v, precision)))
+                .collect()
+        }
+        Some(column::Values::Nchar(v)) => {
+            v.values.iter().map(|v| Field::NChar(v.clone())).collect()
+        }
+        Some(column::Values::Utinyint(v)) => {
+            v.values.iter().map(|v| Field::UTinyInt(*v as u8)).collect()
+        }
+        Some(column::Values::Usmallint(v)) => v
+            .values
+            .iter()
+            .map(|v| Field::USmallInt(*v as u16))
+            .collect(),
+        Some(column::Values::Uint(v)) => v.values.iter().map(|v| Field::UInt(*v)).collect(),
+        Some(column::Values::Ubigint(v)) => v.values.iter().map(|v| Field::UBigInt(*v)).collect(),
+        Some(column::Values::Json(v)) => v
+            .values
+            .iter()
+            .map(|v| Field::Json(serde_json::from_str(v).unwrap()))
+            .collect(),
+    };
+    if col.nulls.is_empty() {
+        return values;
+    }
+    let mut values = values.into_iter();
+    let mut nulls = col.nulls.iter().peekable();
+    (0..values.len() + col.nulls.len())
+        .map(|row| match nulls.next_if(|&&null| null as usize == row) {
+            Some(_) => Field::Null,
+            None => values.next().unwrap(),
+        })
+        .collect()
+}
+
+pub fn cols_to_protobuf(cols: &[Vec<Field>]) -> pb::ColumnBatch {
+    pb::ColumnBatch {
+        columns: cols.iter().map(|col| column_to_protobuf(col)).collect(),
+    }
+}
+
+pub fn protobuf_to_cols(batch: &pb::ColumnBatch) -> Vec<Vec<Field>> {
+    batch.columns.iter().map(protobuf_to_column).collect()
+}
+
+pub fn protobuf_row_serialize(rows: &[Vec<Field>]) -> Vec<u8> {
+    rows_to_protobuf(rows).encode_to_vec()
+}
+
+pub fn protobuf_row_deserialize(buf: &[u8]) -> Vec<Vec<Field>> {
+    protobuf_to_rows(&pb::RowBatch::decode(buf).unwrap())
+}
+
+pub fn protobuf_column_serialize(cols: &[Vec<Field>]) -> Vec<u8> {
+    cols_to_protobuf(cols).encode_to_vec()
+}
+
+pub fn protobuf_column_deserialize(buf: &[u8]) -> Vec<Vec<Field>> {
+    protobuf_to_cols(&pb::ColumnBatch::decode(buf).unwrap())
+}
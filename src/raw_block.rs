@@ -0,0 +1,268 @@
+use std::{
+    convert::TryInto,
+    fs::File,
+    io::{Read, Write},
+};
+
+use bstr::BString;
+use libtaos::{Field, TaosDataType, Timestamp, TimestampPrecision};
+use parquet::{basic::Compression, compression::create_codec};
+
+// TDengine's raw block layout, little endian:
+//
+// | version: i32 | length: i32 | rows: i32 | cols: i32 | flag: i32 | group_id: u64 |
+// | (type: i8, bytes: i32) * cols | data length: i32 * cols | column data * cols |
+//
+// Fixed width columns start with a null bitmap of (rows + 7) / 8 bytes, var length
+// columns with one i32 offset per row (-1 for null) into `u16 length + bytes` entries,
+// so a value is at most 65535 bytes and encoding a longer one panics.
+// NChar values are stored as UCS-4 the way TDengine does.
+
+const RAW_BLOCK_VERSION: i32 = 1;
+const HEADER_SIZE: usize = 4 * 5 + 8;
+
+fn is_var_type(data_type: TaosDataType) -> bool {
+    matches!(data_type, TaosDataType::Binary | TaosDataType::NChar)
+}
+
+pub(crate) fn type_bytes(data_type: TaosDataType) -> i32 {
+    match data_type {
+        TaosDataType::Bool | TaosDataType::TinyInt | TaosDataType::UTinyInt => 1,
+        TaosDataType::SmallInt | TaosDataType::USmallInt => 2,
+        TaosDataType::Int | TaosDataType::UInt | TaosDataType::Float => 4,
+        TaosDataType::BigInt
+        | TaosDataType::UBigInt
+        | TaosDataType::Double
+        | TaosDataType::Timestamp => 8,
+        _ => 0,
+    }
+}
+
+fn bitmap_len(rows: usize) -> usize {
+    (rows + 7) >> 3
+}
+
+fn encode_var_value(field: &Field) -> Vec<u8> {
+    match field {
+        Field::Binary(v) => v.to_vec(),
+        Field::NChar(v) => v.chars().flat_map(|c| (c as u32).to_le_bytes()).collect(),
+        _ => unreachable!(),
+    }
+}
+
+pub(crate) fn encode_fixed_value(field: &Field, data: &mut Vec<u8>) {
+    match field {
+        Field::Bool(v) => data.push(*v as u8),
+        Field::TinyInt(v) => data.extend_from_slice(&v.to_le_bytes()),
+        Field::SmallInt(v) => data.extend_from_slice(&v.to_le_bytes()),
+        Field::Int(v) => data.extend_from_slice(&v.to_le_bytes()),
+        Field::BigInt(v) => data.extend_from_slice(&v.to_le_bytes()),
+        Field::Float(v) => data.extend_from_slice(&v.to_le_bytes()),
+        Field::Double(v) => data.extend_from_slice(&v.to_le_bytes()),
+        Field::Timestamp(v) => data.extend_from_slice(&v.as_raw_timestamp().to_le_bytes()),
+        Field::UTinyInt(v) => data.push(*v),
+        Field::USmallInt(v) => data.extend_from_slice(&v.to_le_bytes()),
+        Field::UInt(v) => data.extend_from_slice(&v.to_le_bytes()),
+        Field::UBigInt(v) => data.extend_from_slice(&v.to_le_bytes()),
+        _ => unreachable!(),
+    }
+}
+
+fn encode_column(data_type: TaosDataType, col: &[Field]) -> (i32, Vec<u8>) {
+    let mut data = vec![];
+    if is_var_type(data_type) {
+        let mut offsets = Vec::with_capacity(col.len() * 4);
+        let mut values = vec![];
+        let mut max_len = 0;
+        for field in col {
+            if *field == Field::Null {
+                offsets.extend_from_slice(&(-1i32).to_le_bytes());
+                continue;
+            }
+            let value = encode_var_value(field);
+            max_len = max_len.max(value.len());
+            offsets.extend_from_slice(&(values.len() as i32).to_le_bytes());
+            let len = u16::try_from(value.len()).expect("raw block values are at most 65535 bytes");
+            values.extend_from_slice(&len.to_le_bytes());
+            values.extend_from_slice(&value);
+        }
+        data.extend_from_slice(&offsets);
+        data.extend_from_slice(&values);
+        (max_len as i32 + 2, data)
+    } else {
+        let bytes = type_bytes(data_type);
+        data.resize(bitmap_len(col.len()), 0);
+        for (row, field) in col.iter().enumerate() {
+            if *field == Field::Null {
+                data[row >> 3] |= 1 << (7 - (row & 7));
+                data.resize(data.len() + bytes as usize, 0);
+            } else {
+                encode_fixed_value(field, &mut data);
+            }
+        }
+        (bytes, data)
+    }
+}
+
+pub fn raw_block_encode(cols: &[Vec<Field>]) -> Vec<u8> {
+    let rows = cols.first().map_or(0, |col| col.len());
+    let mut schema = vec![];
+    let mut lengths = vec![];
+    let mut payload = vec![];
+    for col in cols {
+        let data_type = col
+            .iter()
+            .find(|field| **field != Field::Null)
+            .map_or(TaosDataType::Null, Field::data_type);
+        let (bytes, data) = encode_column(data_type, col);
+        schema.push(data_type as u8);
+        schema.extend_from_slice(&bytes.to_le_bytes());
+        lengths.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        payload.extend_from_slice(&data);
+    }
+    let length = HEADER_SIZE + schema.len() + lengths.len() + payload.len();
+    let mut block = Vec::with_capacity(length);
+    block.extend_from_slice(&RAW_BLOCK_VERSION.to_le_bytes());
+    block.extend_from_slice(&(length as i32).to_le_bytes());
+    block.extend_from_slice(&(rows as i32).to_le_bytes());
+    block.extend_from_slice(&(cols.len() as i32).to_le_bytes());
+    block.extend_from_slice(&0i32.to_le_bytes());
+    block.extend_from_slice(&0u64.to_le_bytes());
+    block.extend_from_slice(&schema);
+    block.extend_from_slice(&lengths);
+    block.extend_from_slice(&payload);
+    block
+}
+
+fn read_i32(buf: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+pub(crate) fn decode_fixed_value(data_type: TaosDataType, v: &[u8]) -> Field {
+    match data_type {
+        TaosDataType::Bool => Field::Bool(v[0] != 0),
+        TaosDataType::TinyInt => Field::TinyInt(v[0] as i8),
+        TaosDataType::SmallInt => Field::SmallInt(i16::from_le_bytes(v.try_into().unwrap())),
+        TaosDataType::Int => Field::Int(i32::from_le_bytes(v.try_into().unwrap())),
+        TaosDataType::BigInt => Field::BigInt(i64::from_le_bytes(v.try_into().unwrap())),
+        TaosDataType::Float => Field::Float(f32::from_le_bytes(v.try_into().unwrap())),
+        TaosDataType::Double => Field::Double(f64::from_le_bytes(v.try_into().unwrap())),
+        TaosDataType::Timestamp => Field::Timestamp(Timestamp::new(
+            i64::from_le_bytes(v.try_into().unwrap()),
+            TimestampPrecision::Milli,
+        )),
+        TaosDataType::UTinyInt => Field::UTinyInt(v[0]),
+        TaosDataType::USmallInt => Field::USmallInt(u16::from_le_bytes(v.try_into().unwrap())),
+        TaosDataType::UInt => Field::UInt(u32::from_le_bytes(v.try_into().unwrap())),
+        TaosDataType::UBigInt => Field::UBigInt(u64::from_le_bytes(v.try_into().unwrap())),
+        _ => unreachable!(),
+    }
+}
+
+fn decode_var_value(data_type: TaosDataType, v: &[u8]) -> Field {
+    match data_type {
+        TaosDataType::Binary => Field::Binary(BString::from(v)),
+        TaosDataType::NChar => Field::NChar(
+            v.chunks(4)
+                .map(|c| char::from_u32(u32::from_le_bytes(c.try_into().unwrap())).unwrap())
+                .collect(),
+        ),
+        _ => unreachable!(),
+    }
+}
+
+fn decode_column(data_type: TaosDataType, rows: usize, data: &[u8]) -> Vec<Field> {
+    let mut col = Vec::with_capacity(rows);
+    if data_type == TaosDataType::Null {
+        col.resize(rows, Field::Null);
+    } else if is_var_type(data_type) {
+        let values = &data[rows * 4..];
+        for row in 0..rows {
+            let offset = read_i32(data, row * 4);
+            if offset < 0 {
+                col.push(Field::Null);
+                continue;
+            }
+            let offset = offset as usize;
+            let len = u16::from_le_bytes([values[offset], values[offset + 1]]) as usize;
+            col.push(decode_var_value(
+                data_type,
+                &values[offset + 2..offset + 2 + len],
+            ));
+        }
+    } else {
+        let bytes = type_bytes(data_type) as usize;
+        let values = &data[bitmap_len(rows)..];
+        for row in 0..rows {
+            if data[row >> 3] & (1 << (7 - (row & 7))) != 0 {
+                col.push(Field::Null);
+            } else {
+                col.push(decode_fixed_value(
+                    data_type,
+                    &values[row * bytes..(row + 1) * bytes],
+                ));
+            }
+        }
+    }
+    col
+}
+
+pub fn raw_block_decode(block: &[u8]) -> Vec<Vec<Field>> {
+    assert_eq!(read_i32(block, 0), RAW_BLOCK_VERSION);
+    let rows = read_i32(block, 8) as usize;
+    let ncols = read_i32(block, 12) as usize;
+    let mut schema_offset = HEADER_SIZE;
+    let mut length_offset = HEADER_SIZE + ncols * 5;
+    let mut data_offset = length_offset + ncols * 4;
+    let mut cols = Vec::with_capacity(ncols);
+    for _ in 0..ncols {
+        let data_type = TaosDataType::from(block[schema_offset]);
+        let length = read_i32(block, length_offset) as usize;
+        cols.push(decode_column(
+            data_type,
+            rows,
+            &block[data_offset..data_offset + length],
+        ));
+        schema_offset += 5;
+        length_offset += 4;
+        data_offset += length;
+    }
+    cols
+}
+
+// The raw block optionally followed by one of parquet's general purpose codecs.
+pub fn raw_block_serialize(cols: &[Vec<Field>], compression: Compression) -> Vec<u8> {
+    let block = raw_block_encode(cols);
+    match create_codec(compression).unwrap() {
+        Some(mut codec) => {
+            let mut compressed = vec![];
+            codec.compress(&block, &mut compressed).unwrap();
+            compressed
+        }
+        None => block,
+    }
+}
+
+pub fn raw_block_deserialize(buf: &[u8], compression: Compression) -> Vec<Vec<Field>> {
+    match create_codec(compression).unwrap() {
+        Some(mut codec) => {
+            let mut block = vec![];
+            codec.decompress(buf, &mut block).unwrap();
+            raw_block_decode(&block)
+        }
+        None => raw_block_decode(buf),
+    }
+}
+
+pub fn raw_block_serialized_write(filename: &str, cols: &[Vec<Field>], compression: Compression) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&raw_block_serialize(cols, compression))
+        .unwrap();
+}
+
+pub fn raw_block_read(filename: &str, compression: Compression) -> u32 {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    let cols = raw_block_deserialize(&buf, compression);
+    cols.first().map_or(0, |col| col.len() as u32)
+}
@@ -0,0 +1,282 @@
+use std::{fs, path::Path};
+
+use avro_rs::{Codec, Schema};
+use libtaos::Field;
+use parquet::basic::Compression;
+
+use crate::{
+    avro::AvroStreamWriter,
+    manifest::{commit_file, temp_path, ManifestEntry},
+    millis,
+    my_parquet::ParquetStreamWriter,
+    rows_to_cols, timestamp_precision,
+};
+
+// Splits a stream of batches into files, starting a new one when the current file has
+// `max_rows` rows, has written `max_bytes` or gets a row from another `time_bucket`.
+// Files are named by filling in `template`:
+//
+// {seq}       the file's number from 1, zero padded to 4 digits
+// {bucket}    the start of the time bucket in ms, or of the file's first row
+// {first_ts}  the timestamp of the file's first row
+//
+// Each file is written under a temporary name and only renamed to its own once closed.
+//
+// Sizes are checked after each batch and only count what the writer has flushed, so a
+// file can go over `max_bytes` by a batch and a block or row group before it is closed.
+
+pub enum SinkFormat<'a> {
+    Avro {
+        schema: &'a Schema,
+        compression: Codec,
+        block_bytes: usize,
+    },
+    Parquet {
+        compression: Compression,
+        row_group_rows: usize,
+    },
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RollingPolicy {
+    pub max_rows: Option<u64>,
+    pub max_bytes: Option<u64>,
+    // bucket width in ms, whatever the precision of the timestamps
+    pub time_bucket: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileSummary {
+    pub path: String,
+    pub rows: u64,
+    pub bytes: u64,
+    pub codec: String,
+    // in ms, whatever the precision of the timestamps
+    pub min_ts: i64,
+    pub max_ts: i64,
+}
+
+impl FileSummary {
+    // The entry to commit the file with to the manifest of `dir`, which it must be in.
+    pub fn manifest_entry(&self, dir: &str, schema_version: u32) -> ManifestEntry {
+        let path = Path::new(&self.path)
+            .strip_prefix(dir)
+            .unwrap_or_else(|_| panic!("{} is not in {}", self.path, dir));
+        ManifestEntry {
+            path: path.to_string_lossy().to_string(),
+            schema_version,
+            rows: self.rows,
+            codec: self.codec.clone(),
+            min_ts: self.min_ts,
+            max_ts: self.max_ts,
+        }
+    }
+}
+
+enum StreamWriter<'a> {
+    Avro(AvroStreamWriter<'a>),
+    Parquet(ParquetStreamWriter),
+}
+
+struct OpenFile<'a> {
+    writer: StreamWriter<'a>,
+    path: String,
+    temp: String,
+    bucket: i64,
+    min_ts: i64,
+    max_ts: i64,
+}
+
+pub struct RollingSink<'a> {
+    format: SinkFormat<'a>,
+    data_types: Vec<&'a str>,
+    ts_column: usize,
+    policy: RollingPolicy,
+    template: String,
+    seq: u32,
+    file: Option<OpenFile<'a>>,
+}
+
+fn timestamp(field: &Field) -> i64 {
+    match field {
+        Field::Timestamp(v) => v.as_raw_timestamp(),
+        _ => unreachable!(),
+    }
+}
+
+fn timestamp_millis(field: &Field) -> i64 {
+    match field {
+        Field::Timestamp(v) => millis(v.as_raw_timestamp(), timestamp_precision(v)),
+        _ => unreachable!(),
+    }
+}
+
+impl<'a> RollingSink<'a> {
+    pub fn new(
+        format: SinkFormat<'a>,
+        data_types: &[&'a str],
+        policy: RollingPolicy,
+        template: &str,
+    ) -> Self {
+        assert!(policy.max_rows != Some(0), "max_rows must be at least 1");
+        assert!(
+            policy.time_bucket.is_none_or(|width| width > 0),
+            "time_bucket must be at least 1ms"
+        );
+        let ts_column = data_types.iter().position(|t| *t == "timestamp").unwrap();
+        Self {
+            format,
+            data_types: data_types.to_vec(),
+            ts_column,
+            policy,
+            template: template.to_string(),
+            seq: 0,
+            file: None,
+        }
+    }
+
+    // The start of the bucket of `row` in ms.
+    fn bucket(&self, row: &[Field]) -> i64 {
+        let ts = timestamp_millis(&row[self.ts_column]);
+        match self.policy.time_bucket {
+            Some(width) => ts.div_euclid(width) * width,
+            None => i64::MIN,
+        }
+    }
+
+    fn open(&mut self, row: &[Field]) {
+        self.seq += 1;
+        let ts = timestamp(&row[self.ts_column]);
+        let ms = timestamp_millis(&row[self.ts_column]);
+        let bucket = self.bucket(row);
+        let path = self
+            .template
+            .replace("{seq}", &format!("{:04}", self.seq))
+            .replace(
+                "{bucket}",
+                &(if bucket == i64::MIN { ts } else { bucket }).to_string(),
+            )
+            .replace("{first_ts}", &ts.to_string());
+        let temp = temp_path(&path);
+        let writer = match &self.format {
+            SinkFormat::Avro {
+                schema,
+                compression,
+                block_bytes,
+            } => StreamWriter::Avro(AvroStreamWriter::new(
+                &temp,
+                schema,
+                &self.data_types,
+                *compression,
+                *block_bytes,
+            )),
+            SinkFormat::Parquet {
+                compression,
+                row_group_rows,
+            } => StreamWriter::Parquet(ParquetStreamWriter::new(
+                &temp,
+                &self.data_types,
+                *compression,
+                *row_group_rows,
+            )),
+        };
+        self.file = Some(OpenFile {
+            writer,
+            path,
+            temp,
+            bucket,
+            min_ts: ms,
+            max_ts: ms,
+        });
+    }
+
+    fn close_file(&mut self) -> Option<FileSummary> {
+        let file = self.file.take()?;
+        let rows = match file.writer {
+            StreamWriter::Avro(writer) => writer.close(),
+            StreamWriter::Parquet(writer) => writer.close(),
+        };
+        commit_file(&file.temp, &file.path);
+        let codec = match &self.format {
+            SinkFormat::Avro { compression, .. } => <&str>::from(*compression).to_string(),
+            SinkFormat::Parquet { compression, .. } => format!("{:?}", compression).to_lowercase(),
+        };
+        Some(FileSummary {
+            bytes: fs::metadata(&file.path).unwrap().len(),
+            path: file.path,
+            rows,
+            codec,
+            min_ts: file.min_ts,
+            max_ts: file.max_ts,
+        })
+    }
+
+    fn rows(&self) -> u64 {
+        match &self.file.as_ref().unwrap().writer {
+            StreamWriter::Avro(writer) => writer.rows(),
+            StreamWriter::Parquet(writer) => writer.rows(),
+        }
+    }
+
+    fn bytes_written(&self) -> u64 {
+        match &self.file.as_ref().unwrap().writer {
+            StreamWriter::Avro(writer) => writer.bytes_written(),
+            StreamWriter::Parquet(writer) => writer.bytes_written(),
+        }
+    }
+
+    // Appends `rows` to the current file, which must have room for all of them.
+    fn append(&mut self, rows: &[Vec<Field>]) {
+        let ts_column = self.ts_column;
+        let file = self.file.as_mut().unwrap();
+        for row in rows {
+            let ts = timestamp_millis(&row[ts_column]);
+            file.min_ts = file.min_ts.min(ts);
+            file.max_ts = file.max_ts.max(ts);
+        }
+        match &mut file.writer {
+            StreamWriter::Avro(writer) => writer.append_batch(rows),
+            StreamWriter::Parquet(writer) => writer.append_batch(&rows_to_cols(rows)),
+        }
+    }
+
+    // Returns the summaries of the files this batch closed.
+    pub fn append_batch(&mut self, rows: &[Vec<Field>]) -> Vec<FileSummary> {
+        let mut closed = vec![];
+        let mut start = 0;
+        while start < rows.len() {
+            if self.file.is_none() {
+                self.open(&rows[start]);
+            }
+            let bucket = self.file.as_ref().unwrap().bucket;
+            let room = match self.policy.max_rows {
+                Some(max_rows) => (max_rows - self.rows()) as usize,
+                None => rows.len(),
+            };
+            // the rows up to the first one of another bucket or past `max_rows`
+            let end = rows[start..]
+                .iter()
+                .take(room)
+                .position(|row| self.bucket(row) != bucket)
+                .map_or((start + room).min(rows.len()), |len| start + len);
+            if end > start {
+                self.append(&rows[start..end]);
+            }
+            start = end;
+            let full = self.policy.max_rows.is_some_and(|max| self.rows() >= max)
+                || self
+                    .policy
+                    .max_bytes
+                    .is_some_and(|max| self.bytes_written() >= max);
+            if full || start < rows.len() {
+                closed.extend(self.close_file());
+            }
+        }
+        closed
+    }
+
+    // Closes the current file, if any, returning its summary.
+    pub fn close(mut self) -> Option<FileSummary> {
+        self.close_file()
+    }
+}
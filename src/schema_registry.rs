@@ -0,0 +1,311 @@
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use avro_rs::{from_avro_datum, to_avro_datum, Schema};
+use libtaos::Field;
+use serde_json::json;
+
+use crate::avro::{avro_record, avro_record_to_row, generate_avro_schema};
+
+// Avro in the Confluent wire format Kafka consumers expect,
+//
+// | magic: 0x00 | schema id: u32 big endian | datum |
+//
+// with schema ids from a schema registry's REST API: `POST /subjects/<subject>/versions`
+// to register, `GET /schemas/ids/<id>` to look up. Plain HTTP/1.1 over one connection
+// per request is all the registry needs, and `MockSchemaRegistry` serves the same two
+// endpoints in process so nothing here needs a real registry.
+
+pub const CONFLUENT_MAGIC: u8 = 0;
+
+const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+// Returns the status code and body.
+fn http_request(url: &str, method: &str, path: &str, body: &str) -> (u16, String) {
+    let authority = url.trim_start_matches("http://").trim_end_matches('/');
+    let (host, base) = match authority.find('/') {
+        Some(index) => authority.split_at(index),
+        None => (authority, ""),
+    };
+    let mut stream = TcpStream::connect(host).unwrap();
+    write!(
+        stream,
+        "{} {}{} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        base,
+        path,
+        host,
+        CONTENT_TYPE,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+// Caches ids by subject and schema and schemas by id, so encoding and decoding batches
+// only goes to the registry the first time.
+pub struct SchemaRegistryClient {
+    url: String,
+    ids: HashMap<(String, String), u32>,
+    schemas: HashMap<u32, Schema>,
+}
+
+impl SchemaRegistryClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            ids: HashMap::new(),
+            schemas: HashMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, subject: &str, schema: &Schema) -> u32 {
+        let text = serde_json::to_string(schema).unwrap();
+        let key = (subject.to_string(), text);
+        if let Some(id) = self.ids.get(&key) {
+            return *id;
+        }
+        let path = format!("/subjects/{}/versions", subject);
+        let body = json!({ "schema": key.1 }).to_string();
+        let (status, body) = http_request(&self.url, "POST", &path, &body);
+        assert_eq!(status, 200, "registering {} failed: {}", subject, body);
+        let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let id = response["id"].as_u64().unwrap() as u32;
+        self.ids.insert(key, id);
+        self.schemas.insert(id, schema.clone());
+        id
+    }
+
+    pub fn schema(&mut self, id: u32) -> &Schema {
+        let url = &self.url;
+        self.schemas.entry(id).or_insert_with(|| {
+            let (status, body) = http_request(url, "GET", &format!("/schemas/ids/{}", id), "");
+            assert_eq!(status, 200, "fetching schema {} failed: {}", id, body);
+            let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+            Schema::parse_str(response["schema"].as_str().unwrap()).unwrap()
+        })
+    }
+}
+
+fn error(status: u16, error_code: u32, message: &str) -> (u16, serde_json::Value) {
+    (
+        status,
+        json!({ "error_code": error_code, "message": message }),
+    )
+}
+
+#[derive(Default)]
+struct Registry {
+    // schema texts, the id of each being its index + 1
+    schemas: Vec<String>,
+    subjects: HashMap<String, Vec<u32>>,
+}
+
+impl Registry {
+    fn handle(&mut self, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("POST", ["subjects", subject, "versions"]) => {
+                let request: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+                let text = match request["schema"].as_str() {
+                    Some(text) if Schema::parse_str(text).is_ok() => text.to_string(),
+                    _ => return error(422, 42201, "Invalid schema"),
+                };
+                let id = match self.schemas.iter().position(|s| *s == text) {
+                    Some(index) => index as u32 + 1,
+                    None => {
+                        self.schemas.push(text);
+                        self.schemas.len() as u32
+                    }
+                };
+                let versions = self.subjects.entry(subject.to_string()).or_default();
+                if !versions.contains(&id) {
+                    versions.push(id);
+                }
+                (200, json!({ "id": id }))
+            }
+            ("GET", ["schemas", "ids", id]) => {
+                match id
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|id| self.schemas.get(id.wrapping_sub(1)))
+                {
+                    Some(text) => (200, json!({ "schema": text })),
+                    None => error(404, 40403, "Schema not found"),
+                }
+            }
+            ("GET", ["subjects"]) => (200, json!(self.subjects.keys().collect::<Vec<_>>())),
+            ("GET", ["subjects", subject, "versions"]) => match self.subjects.get(*subject) {
+                Some(versions) => (200, json!((1..=versions.len()).collect::<Vec<_>>())),
+                None => error(404, 40401, "Subject not found"),
+            },
+            _ => error(404, 404, "HTTP 404 Not Found"),
+        }
+    }
+}
+
+// The method, path and body of a request, or None when it is malformed.
+fn read_request(stream: &mut TcpStream) -> io::Result<Option<(String, String, String)>> {
+    let mut request = vec![];
+    let mut buf = [0; 4096];
+    loop {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        request.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&request).to_string();
+        let Some((head, body)) = text.split_once("\r\n\r\n") else {
+            continue;
+        };
+        let length = head
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .map_or(Some(0), |(_, v)| v.trim().parse().ok());
+        let Some(length) = length else {
+            return Ok(None);
+        };
+        if body.len() < length {
+            continue;
+        }
+        let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+        return Ok(
+            match (request_line.next(), request_line.next(), body.get(..length)) {
+                (Some(method), Some(path), Some(body)) if path.starts_with('/') => {
+                    Some((method.to_string(), path.to_string(), body.to_string()))
+                }
+                _ => None,
+            },
+        );
+    }
+}
+
+fn serve(stream: &mut TcpStream, registry: &Mutex<Registry>) {
+    let (status, response) = match read_request(stream) {
+        Ok(Some((method, path, body))) => registry.lock().unwrap().handle(&method, &path, &body),
+        Ok(None) => error(400, 400, "Bad Request"),
+        // the client is gone, there is no one to answer
+        Err(_) => return,
+    };
+    let response = response.to_string();
+    // the client may be gone by now as well
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        if status == 200 { "OK" } else { "Error" },
+        CONTENT_TYPE,
+        response.len(),
+        response
+    );
+}
+
+// A schema registry on a local port, serving until dropped.
+pub struct MockSchemaRegistry {
+    url: String,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockSchemaRegistry {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let stop = Arc::new(AtomicBool::new(false));
+        let registry = Mutex::new(Registry::default());
+        let handle = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(mut stream) = stream {
+                        serve(&mut stream, &registry);
+                    }
+                }
+            })
+        };
+        Self {
+            url,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Drop for MockSchemaRegistry {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // wakes up the blocking accept
+        let _ = TcpStream::connect(self.url.trim_start_matches("http://"));
+        // a panic on the server thread already failed whatever used the registry, a
+        // second one here would only abort
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+pub fn avro_confluent_encode(
+    schema: &Schema,
+    id: u32,
+    data_types: &[&str],
+    row: &[Field],
+) -> Vec<u8> {
+    let mut buf = vec![CONFLUENT_MAGIC];
+    buf.extend_from_slice(&id.to_be_bytes());
+    buf.extend(to_avro_datum(schema, avro_record(schema, data_types, row)).unwrap());
+    buf
+}
+
+pub fn avro_confluent_decode(client: &mut SchemaRegistryClient, buf: &[u8]) -> Vec<Field> {
+    assert_eq!(buf[0], CONFLUENT_MAGIC, "not in the confluent wire format");
+    let id = u32::from_be_bytes(buf[1..5].try_into().unwrap());
+    let schema = client.schema(id);
+    avro_record_to_row(from_avro_datum(schema, &mut &buf[5..], None).unwrap())
+}
+
+// Registers the schema of `data_types` under `subject` and frames every row as its own
+// message, like a Kafka producer with the Confluent Avro serializer.
+pub fn avro_confluent_serialize(
+    client: &mut SchemaRegistryClient,
+    subject: &str,
+    data_types: &Vec<&str>,
+    rows: &[Vec<Field>],
+) -> Vec<Vec<u8>> {
+    let schema = generate_avro_schema(data_types);
+    let id = client.register(subject, &schema);
+    rows.iter()
+        .map(|row| avro_confluent_encode(&schema, id, data_types, row))
+        .collect()
+}
+
+pub fn avro_confluent_deserialize(
+    client: &mut SchemaRegistryClient,
+    messages: &[Vec<u8>],
+) -> Vec<Vec<Field>> {
+    messages
+        .iter()
+        .map(|message| avro_confluent_decode(client, message))
+        .collect()
+}
@@ -0,0 +1,183 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+};
+
+use libtaos::Field;
+
+use crate::{
+    line_protocol::{escape, field_to_text},
+    parse_field, SuperTable,
+};
+
+// Strings in single quotes with quotes and backslashes escaped, as taosdump writes them.
+fn field_to_sql(field: &Field) -> String {
+    match field {
+        Field::Null => "NULL".to_string(),
+        Field::Binary(v) => format!("'{}'", escape(&v.to_string(), &['\''])),
+        Field::NChar(v) => format!("'{}'", escape(v, &['\''])),
+        Field::Json(v) => format!("'{}'", escape(&v.to_string(), &['\''])),
+        field => field_to_text(field),
+    }
+}
+
+// A table or super table name in backquotes, which TDengine does not allow in names.
+fn name_to_sql(name: &str) -> String {
+    format!("`{}`", name)
+}
+
+fn push_tuple(sql: &mut String, fields: &[Field]) {
+    sql.push('(');
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            sql.push_str(", ");
+        }
+        sql.push_str(&field_to_sql(field));
+    }
+    sql.push(')');
+}
+
+// Renders `rows` as `INSERT INTO <table> USING <stable> TAGS (..) VALUES (..) (..);`
+// statements of at most `batch_size` rows each, like taosdump does.
+pub fn sql_serialize(
+    stable: &SuperTable,
+    table: &str,
+    tags: &[Field],
+    rows: &[Vec<Field>],
+    batch_size: usize,
+) -> String {
+    assert!(batch_size > 0, "batch_size must be at least 1");
+    let mut header = format!(
+        "INSERT INTO {} USING {} TAGS ",
+        name_to_sql(table),
+        name_to_sql(stable.name)
+    );
+    push_tuple(&mut header, tags);
+    header.push_str(" VALUES");
+    let mut sql = String::new();
+    for batch in rows.chunks(batch_size) {
+        sql.push_str(&header);
+        for row in batch {
+            sql.push(' ');
+            push_tuple(&mut sql, row);
+        }
+        sql.push_str(";\n");
+    }
+    sql
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Name(String),
+    LParen,
+    RParen,
+    Comma,
+    Semicolon,
+}
+
+fn tokenize(sql: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::LParen),
+            ')' => tokens.push(Token::RParen),
+            ',' => tokens.push(Token::Comma),
+            ';' => tokens.push(Token::Semicolon),
+            '\'' | '"' => {
+                let mut s = String::new();
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => s.extend(chars.next()),
+                        next if next == c => break,
+                        next => s.push(next),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            '`' => tokens.push(Token::Name(
+                chars.by_ref().take_while(|&c| c != '`').collect(),
+            )),
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "(),;'\"`".contains(next) {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    tokens
+}
+
+fn parse_tuple(
+    stable: &SuperTable,
+    tokens: &mut std::iter::Peekable<std::vec::IntoIter<Token>>,
+    data_types: &[&str],
+) -> Vec<Field> {
+    assert_eq!(tokens.next(), Some(Token::LParen));
+    let mut fields = vec![];
+    loop {
+        let data_type = data_types[fields.len()];
+        let field = match tokens.next().unwrap() {
+            Token::Word(w) if w.eq_ignore_ascii_case("null") => Field::Null,
+            Token::Word(w) => parse_field(data_type, &w, stable.precision),
+            Token::Str(s) => parse_field(data_type, &s, stable.precision),
+            _ => unreachable!(),
+        };
+        fields.push(field);
+        match tokens.next().unwrap() {
+            Token::Comma => {}
+            Token::RParen => break,
+            _ => unreachable!(),
+        }
+    }
+    fields
+}
+
+pub fn sql_parse(stable: &SuperTable, sql: &str) -> Vec<(Vec<Field>, Vec<Field>)> {
+    let mut rows = vec![];
+    let mut tokens = tokenize(sql).into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        // skip to the TAGS of the next INSERT INTO .. USING .. statement
+        if !matches!(&token, Token::Word(w) if w.eq_ignore_ascii_case("tags")) {
+            continue;
+        }
+        let tags = parse_tuple(stable, &mut tokens, stable.tag_types);
+        assert!(matches!(tokens.next(), Some(Token::Word(w)) if w.eq_ignore_ascii_case("values")));
+        while tokens.peek() == Some(&Token::LParen) {
+            let row = parse_tuple(stable, &mut tokens, stable.data_types);
+            rows.push((tags.clone(), row));
+        }
+    }
+    rows
+}
+
+pub fn sql_serialized_write(
+    filename: &str,
+    stable: &SuperTable,
+    table: &str,
+    tags: &[Field],
+    rows: &[Vec<Field>],
+    batch_size: usize,
+) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(sql_serialize(stable, table, tags, rows, batch_size).as_bytes())
+        .unwrap();
+}
+
+pub fn sql_read(filename: &str, stable: &SuperTable) -> u32 {
+    let mut sql = String::new();
+    File::open(filename)
+        .unwrap()
+        .read_to_string(&mut sql)
+        .unwrap();
+    sql_parse(stable, &sql).len() as u32
+}
@@ -0,0 +1,373 @@
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    fs::File,
+    io::{Read, Write},
+};
+
+use bstr::BString;
+use libtaos::{Field, TaosDataType};
+
+use crate::bits::{read_uvarint, write_uvarint};
+
+// String codecs over binary and nchar values (nchar as UTF-8), both with random access
+// to single values:
+//
+// dictionary: | count: varint | entries: varint | (length: varint | bytes) * entries |
+//             | code width: u8 | code * count |
+// fsst:       | count: varint | symbols: u8 | (length: u8 | bytes) * symbols |
+//             | offset: u32 * (count + 1) | compressed values |
+//
+// FSST (Boncz et al., 2020) replaces substrings of up to 8 bytes with one byte codes
+// into a symbol table trained on the column, code 255 escapes a literal byte.
+
+#[derive(Clone, Copy, Debug)]
+pub enum StringCodec {
+    Dictionary,
+    Fsst,
+}
+
+pub fn dictionary_encode(values: &[&[u8]]) -> Vec<u8> {
+    let mut entries: Vec<&[u8]> = vec![];
+    let mut index = HashMap::new();
+    let codes: Vec<usize> = values
+        .iter()
+        .map(|v| {
+            *index.entry(*v).or_insert_with(|| {
+                entries.push(v);
+                entries.len() - 1
+            })
+        })
+        .collect();
+    let mut buf = vec![];
+    write_uvarint(&mut buf, values.len() as u64);
+    write_uvarint(&mut buf, entries.len() as u64);
+    for entry in &entries {
+        write_uvarint(&mut buf, entry.len() as u64);
+        buf.extend_from_slice(entry);
+    }
+    let width = match entries.len() {
+        0..=0x100 => 1,
+        0x101..=0x10000 => 2,
+        _ => 4,
+    };
+    buf.push(width as u8);
+    for code in codes {
+        buf.extend_from_slice(&code.to_le_bytes()[..width]);
+    }
+    buf
+}
+
+pub struct DictionaryColumn<'a> {
+    entries: Vec<&'a [u8]>,
+    width: usize,
+    codes: &'a [u8],
+}
+
+impl<'a> DictionaryColumn<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        let mut pos = 0;
+        let len = read_uvarint(buf, &mut pos) as usize;
+        let count = read_uvarint(buf, &mut pos) as usize;
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            let entry_len = read_uvarint(buf, &mut pos) as usize;
+            entries.push(&buf[pos..pos + entry_len]);
+            pos += entry_len;
+        }
+        let width = buf[pos] as usize;
+        pos += 1;
+        Self {
+            entries,
+            width,
+            codes: &buf[pos..pos + len * width],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.codes.len() / self.width
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> &'a [u8] {
+        let mut code = [0; 8];
+        code[..self.width]
+            .copy_from_slice(&self.codes[index * self.width..(index + 1) * self.width]);
+        self.entries[usize::from_le_bytes(code)]
+    }
+}
+
+pub fn dictionary_decode(buf: &[u8]) -> Vec<Vec<u8>> {
+    let col = DictionaryColumn::new(buf);
+    (0..col.len()).map(|i| col.get(i).to_vec()).collect()
+}
+
+const FSST_ESCAPE: u8 = 255;
+const FSST_MAX_SYMBOLS: usize = 255;
+const FSST_MAX_SYMBOL_LEN: usize = 8;
+const FSST_SAMPLE_BYTES: usize = 1 << 14;
+const FSST_GENERATIONS: usize = 5;
+
+pub struct SymbolTable {
+    symbols: Vec<Vec<u8>>,
+    index: HashMap<Vec<u8>, u8>,
+}
+
+impl SymbolTable {
+    fn new(symbols: Vec<Vec<u8>>) -> Self {
+        let index = symbols
+            .iter()
+            .enumerate()
+            .map(|(code, symbol)| (symbol.clone(), code as u8))
+            .collect();
+        Self { symbols, index }
+    }
+
+    // The longest symbol at the start of `text`, if any.
+    fn find(&self, text: &[u8]) -> Option<(u8, usize)> {
+        (1..=text.len().min(FSST_MAX_SYMBOL_LEN))
+            .rev()
+            .find_map(|len| self.index.get(&text[..len]).map(|code| (*code, len)))
+    }
+
+    // Trains the table over a sample of the values: every generation counts how often
+    // the current symbols, and pairs of adjacent ones, show up when compressing the
+    // sample and keeps the candidates that save the most bytes.
+    pub fn train(values: &[&[u8]]) -> Self {
+        let mut sample = vec![];
+        let mut sample_bytes = 0;
+        for v in values {
+            if sample_bytes >= FSST_SAMPLE_BYTES {
+                break;
+            }
+            sample.push(*v);
+            sample_bytes += v.len();
+        }
+        let mut table = Self::new(vec![]);
+        for _ in 0..FSST_GENERATIONS {
+            let mut counts: HashMap<Vec<u8>, usize> = HashMap::new();
+            for v in &sample {
+                let mut pos = 0;
+                let mut prev: Option<&[u8]> = None;
+                while pos < v.len() {
+                    let len = table.find(&v[pos..]).map_or(1, |(_, len)| len);
+                    let symbol = &v[pos..pos + len];
+                    *counts.entry(symbol.to_vec()).or_default() += 1;
+                    if let Some(prev) = prev.filter(|p| p.len() + len <= FSST_MAX_SYMBOL_LEN) {
+                        *counts.entry([prev, symbol].concat()).or_default() += 1;
+                    }
+                    prev = Some(symbol);
+                    pos += len;
+                }
+            }
+            let mut candidates: Vec<_> = counts.into_iter().collect();
+            candidates.sort_by(|(a, a_count), (b, b_count)| {
+                (b_count * b.len())
+                    .cmp(&(a_count * a.len()))
+                    .then_with(|| a.cmp(b))
+            });
+            candidates.truncate(FSST_MAX_SYMBOLS);
+            table = Self::new(candidates.into_iter().map(|(symbol, _)| symbol).collect());
+        }
+        table
+    }
+
+    pub fn compress(&self, text: &[u8], buf: &mut Vec<u8>) {
+        let mut pos = 0;
+        while pos < text.len() {
+            match self.find(&text[pos..]) {
+                Some((code, len)) => {
+                    buf.push(code);
+                    pos += len;
+                }
+                None => {
+                    buf.push(FSST_ESCAPE);
+                    buf.push(text[pos]);
+                    pos += 1;
+                }
+            }
+        }
+    }
+}
+
+fn fsst_decompress(symbols: &[&[u8]], compressed: &[u8]) -> Vec<u8> {
+    let mut text = Vec::with_capacity(compressed.len() * 2);
+    let mut codes = compressed.iter();
+    while let Some(code) = codes.next() {
+        if *code == FSST_ESCAPE {
+            text.push(*codes.next().unwrap());
+        } else {
+            text.extend_from_slice(symbols[*code as usize]);
+        }
+    }
+    text
+}
+
+pub fn fsst_encode(values: &[&[u8]]) -> Vec<u8> {
+    let table = SymbolTable::train(values);
+    let mut buf = vec![];
+    write_uvarint(&mut buf, values.len() as u64);
+    buf.push(table.symbols.len() as u8);
+    for symbol in &table.symbols {
+        buf.push(symbol.len() as u8);
+        buf.extend_from_slice(symbol);
+    }
+    let mut offsets = Vec::with_capacity((values.len() + 1) * 4);
+    let mut data = vec![];
+    for v in values {
+        offsets.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        table.compress(v, &mut data);
+    }
+    offsets.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend(offsets);
+    buf.extend(data);
+    buf
+}
+
+pub struct FsstColumn<'a> {
+    symbols: Vec<&'a [u8]>,
+    offsets: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> FsstColumn<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        let mut pos = 0;
+        let len = read_uvarint(buf, &mut pos) as usize;
+        let count = buf[pos] as usize;
+        pos += 1;
+        let mut symbols = Vec::with_capacity(count);
+        for _ in 0..count {
+            let symbol_len = buf[pos] as usize;
+            symbols.push(&buf[pos + 1..pos + 1 + symbol_len]);
+            pos += 1 + symbol_len;
+        }
+        let offsets = &buf[pos..pos + (len + 1) * 4];
+        Self {
+            symbols,
+            offsets,
+            data: &buf[pos + offsets.len()..],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.offsets.len() / 4 - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn offset(&self, index: usize) -> usize {
+        u32::from_le_bytes(self.offsets[index * 4..index * 4 + 4].try_into().unwrap()) as usize
+    }
+
+    pub fn get(&self, index: usize) -> Vec<u8> {
+        let compressed = &self.data[self.offset(index)..self.offset(index + 1)];
+        fsst_decompress(&self.symbols, compressed)
+    }
+}
+
+pub fn fsst_decode(buf: &[u8]) -> Vec<Vec<u8>> {
+    let col = FsstColumn::new(buf);
+    (0..col.len()).map(|i| col.get(i)).collect()
+}
+
+fn field_bytes(field: &Field) -> &[u8] {
+    match field {
+        Field::Binary(v) => v,
+        Field::NChar(v) => v.as_bytes(),
+        _ => unreachable!(),
+    }
+}
+
+fn bytes_to_field(data_type: TaosDataType, v: Vec<u8>) -> Field {
+    match data_type {
+        TaosDataType::Binary => Field::Binary(BString::from(v)),
+        TaosDataType::NChar => Field::NChar(String::from_utf8(v).unwrap()),
+        _ => unreachable!(),
+    }
+}
+
+// A binary or nchar column as a type byte and the codec's encoding.
+pub fn string_column_encode(col: &[Field], codec: StringCodec) -> Vec<u8> {
+    let data_type = col.first().map_or(TaosDataType::Binary, Field::data_type);
+    let values: Vec<_> = col.iter().map(field_bytes).collect();
+    let mut buf = vec![data_type as u8];
+    buf.extend(match codec {
+        StringCodec::Dictionary => dictionary_encode(&values),
+        StringCodec::Fsst => fsst_encode(&values),
+    });
+    buf
+}
+
+pub fn string_column_decode(buf: &[u8], codec: StringCodec) -> Vec<Field> {
+    let data_type = TaosDataType::from(buf[0]);
+    let values = match codec {
+        StringCodec::Dictionary => dictionary_decode(&buf[1..]),
+        StringCodec::Fsst => fsst_decode(&buf[1..]),
+    };
+    values
+        .into_iter()
+        .map(|v| bytes_to_field(data_type, v))
+        .collect()
+}
+
+enum StringValues<'a> {
+    Dictionary(DictionaryColumn<'a>),
+    Fsst(FsstColumn<'a>),
+}
+
+// An encoded column with its dictionary or symbol table parsed once, for reading
+// single values out of it.
+pub struct StringColumn<'a> {
+    data_type: TaosDataType,
+    values: StringValues<'a>,
+}
+
+impl<'a> StringColumn<'a> {
+    pub fn new(buf: &'a [u8], codec: StringCodec) -> Self {
+        let values = match codec {
+            StringCodec::Dictionary => StringValues::Dictionary(DictionaryColumn::new(&buf[1..])),
+            StringCodec::Fsst => StringValues::Fsst(FsstColumn::new(&buf[1..])),
+        };
+        Self {
+            data_type: TaosDataType::from(buf[0]),
+            values,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.values {
+            StringValues::Dictionary(col) => col.len(),
+            StringValues::Fsst(col) => col.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Decodes the value at `index` only.
+    pub fn get(&self, index: usize) -> Field {
+        let v = match &self.values {
+            StringValues::Dictionary(col) => col.get(index).to_vec(),
+            StringValues::Fsst(col) => col.get(index),
+        };
+        bytes_to_field(self.data_type, v)
+    }
+}
+
+pub fn string_column_serialized_write(filename: &str, col: &[Field], codec: StringCodec) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&string_column_encode(col, codec)).unwrap();
+}
+
+pub fn string_column_read(filename: &str, codec: StringCodec) -> u32 {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    string_column_decode(&buf, codec).len() as u32
+}
@@ -0,0 +1,249 @@
+use std::{
+    convert::TryInto,
+    fs::File,
+    io::{Read, Write},
+};
+
+use libtaos::{Field, TaosDataType};
+use parquet::{basic::Compression, compression::create_codec};
+
+use crate::raw_block::{decode_fixed_value, encode_fixed_value, type_bytes};
+
+// Reversible transforms over buffers of fixed width little endian values, chained in a
+// pipeline that ends in one of parquet's general purpose codecs. Delta and XOR work
+// modulo the value width, so they invert exactly for signed, unsigned and float bits.
+
+#[derive(Clone, Copy, Debug)]
+pub enum Transform {
+    Delta,
+    DeltaOfDelta,
+    Xor,
+    ByteShuffle,
+    BitShuffle,
+}
+
+fn read_value(buf: &[u8], width: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes[..width].copy_from_slice(buf);
+    u64::from_le_bytes(bytes)
+}
+
+fn write_value(v: u64, width: usize, out: &mut Vec<u8>) {
+    out.extend_from_slice(&v.to_le_bytes()[..width]);
+}
+
+fn delta(buf: &[u8], width: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len());
+    let mut prev = 0u64;
+    for value in buf.chunks(width) {
+        let v = read_value(value, width);
+        write_value(v.wrapping_sub(prev), width, &mut out);
+        prev = v;
+    }
+    out
+}
+
+fn undelta(buf: &[u8], width: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len());
+    let mut prev = 0u64;
+    for value in buf.chunks(width) {
+        prev = prev.wrapping_add(read_value(value, width));
+        write_value(prev, width, &mut out);
+    }
+    out
+}
+
+fn xor(buf: &[u8], width: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len());
+    let mut prev = 0u64;
+    for value in buf.chunks(width) {
+        let v = read_value(value, width);
+        write_value(v ^ prev, width, &mut out);
+        prev = v;
+    }
+    out
+}
+
+fn unxor(buf: &[u8], width: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len());
+    let mut prev = 0u64;
+    for value in buf.chunks(width) {
+        prev ^= read_value(value, width);
+        write_value(prev, width, &mut out);
+    }
+    out
+}
+
+// Groups the n-th byte of every value together.
+fn byte_shuffle(buf: &[u8], width: usize) -> Vec<u8> {
+    let rows = buf.len() / width;
+    let mut out = vec![0; buf.len()];
+    for (row, value) in buf.chunks(width).enumerate() {
+        for (byte, v) in value.iter().enumerate() {
+            out[byte * rows + row] = *v;
+        }
+    }
+    out
+}
+
+fn byte_unshuffle(buf: &[u8], width: usize) -> Vec<u8> {
+    let rows = buf.len() / width;
+    let mut out = vec![0; buf.len()];
+    for (byte, plane) in buf.chunks(rows.max(1)).enumerate() {
+        for (row, v) in plane.iter().enumerate() {
+            out[row * width + byte] = *v;
+        }
+    }
+    out
+}
+
+// Groups the n-th bit of every value together, one plane of (rows + 7) / 8 bytes per bit.
+fn bit_shuffle(buf: &[u8], width: usize) -> Vec<u8> {
+    let rows = buf.len() / width;
+    let plane = rows.div_ceil(8);
+    let mut out = vec![0; plane * width * 8];
+    for (row, value) in buf.chunks(width).enumerate() {
+        let v = read_value(value, width);
+        for bit in 0..width * 8 {
+            if v >> bit & 1 == 1 {
+                out[bit * plane + row / 8] |= 1 << (row % 8);
+            }
+        }
+    }
+    out
+}
+
+// Restores the padding rows too, the pipeline drops them in the end.
+fn bit_unshuffle(buf: &[u8], width: usize) -> Vec<u8> {
+    let plane = buf.len() / width / 8;
+    let rows = plane * 8;
+    let mut out = Vec::with_capacity(rows * width);
+    for row in 0..rows {
+        let mut v = 0u64;
+        for bit in 0..width * 8 {
+            if buf[bit * plane + row / 8] >> (row % 8) & 1 == 1 {
+                v |= 1 << bit;
+            }
+        }
+        write_value(v, width, &mut out);
+    }
+    out
+}
+
+impl Transform {
+    pub fn apply(self, buf: &[u8], width: usize) -> Vec<u8> {
+        match self {
+            Transform::Delta => delta(buf, width),
+            Transform::DeltaOfDelta => delta(&delta(buf, width), width),
+            Transform::Xor => xor(buf, width),
+            Transform::ByteShuffle => byte_shuffle(buf, width),
+            Transform::BitShuffle => bit_shuffle(buf, width),
+        }
+    }
+
+    pub fn invert(self, buf: &[u8], width: usize) -> Vec<u8> {
+        match self {
+            Transform::Delta => undelta(buf, width),
+            Transform::DeltaOfDelta => undelta(&undelta(buf, width), width),
+            Transform::Xor => unxor(buf, width),
+            Transform::ByteShuffle => byte_unshuffle(buf, width),
+            Transform::BitShuffle => bit_unshuffle(buf, width),
+        }
+    }
+}
+
+pub struct Pipeline {
+    pub transforms: Vec<Transform>,
+    pub compression: Compression,
+}
+
+impl Pipeline {
+    pub fn new(transforms: Vec<Transform>, compression: Compression) -> Self {
+        Self {
+            transforms,
+            compression,
+        }
+    }
+
+    // e.g. `Delta+ByteShuffle-LZ4`, `None-ZSTD` without transforms.
+    pub fn name(&self) -> String {
+        let transforms: Vec<_> = self.transforms.iter().map(|t| format!("{:?}", t)).collect();
+        let transforms = if transforms.is_empty() {
+            "None".to_string()
+        } else {
+            transforms.join("+")
+        };
+        format!("{}-{}", transforms, self.compression)
+    }
+
+    pub fn encode(&self, buf: &[u8], width: usize) -> Vec<u8> {
+        let mut buf = buf.to_vec();
+        for transform in &self.transforms {
+            buf = transform.apply(&buf, width);
+        }
+        match create_codec(self.compression).unwrap() {
+            Some(mut codec) => {
+                let mut compressed = vec![];
+                codec.compress(&buf, &mut compressed).unwrap();
+                compressed
+            }
+            None => buf,
+        }
+    }
+
+    pub fn decode(&self, buf: &[u8], width: usize, rows: usize) -> Vec<u8> {
+        let mut buf = match create_codec(self.compression).unwrap() {
+            Some(mut codec) => {
+                let mut decompressed = vec![];
+                codec.decompress(buf, &mut decompressed).unwrap();
+                decompressed
+            }
+            None => buf.to_vec(),
+        };
+        for transform in self.transforms.iter().rev() {
+            buf = transform.invert(&buf, width);
+        }
+        // bit shuffling pads the planes to whole bytes
+        buf.truncate(rows * width);
+        buf
+    }
+}
+
+// A fixed width column as | type: u8 | rows: u32 | pipeline output |. Binary and nchar
+// columns have no fixed width and are not supported.
+pub fn transform_column_serialize(col: &[Field], pipeline: &Pipeline) -> Vec<u8> {
+    let data_type = col.first().map_or(TaosDataType::BigInt, Field::data_type);
+    let width = type_bytes(data_type) as usize;
+    assert!(width > 0);
+    let mut values = Vec::with_capacity(col.len() * width);
+    for field in col {
+        encode_fixed_value(field, &mut values);
+    }
+    let mut buf = vec![data_type as u8];
+    buf.extend_from_slice(&(col.len() as u32).to_le_bytes());
+    buf.extend(pipeline.encode(&values, width));
+    buf
+}
+
+pub fn transform_column_deserialize(buf: &[u8], pipeline: &Pipeline) -> Vec<Field> {
+    let data_type = TaosDataType::from(buf[0]);
+    let width = type_bytes(data_type) as usize;
+    let rows = u32::from_le_bytes(buf[1..5].try_into().unwrap()) as usize;
+    pipeline
+        .decode(&buf[5..], width, rows)
+        .chunks(width)
+        .map(|v| decode_fixed_value(data_type, v))
+        .collect()
+}
+
+pub fn transform_column_serialized_write(filename: &str, col: &[Field], pipeline: &Pipeline) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&transform_column_serialize(col, pipeline))
+        .unwrap();
+}
+
+pub fn transform_column_read(filename: &str, pipeline: &Pipeline) -> u32 {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    transform_column_deserialize(&buf, pipeline).len() as u32
+}
@@ -0,0 +1,413 @@
+use std::{
+    borrow::Cow,
+    convert::TryInto,
+    fs::File,
+    io::{Read, Write},
+};
+
+use bstr::BString;
+use libtaos::{Field, TaosDataType, Timestamp, TimestampPrecision};
+
+use crate::bits::{read_uvarint, read_varint, write_uvarint, write_varint, BitReader, BitWriter};
+
+// A columnar container for time series with a codec per data type, little endian:
+//
+// | magic: "TSCL" | version: u8 | cols: u16 | type: u8 * cols | blocks: u32 | block * blocks |
+//
+// where every block of up to `block_rows` rows is
+//
+// | rows: u32 | (nulls: uvarint | null bitmap | min | max | length: u32 | column data) * cols |
+//
+// The null bitmap has a bit per row and is only there when `nulls` is not 0, min, max
+// and the column data only cover the values that are not null, so min and max are left
+// out when every value is. A column's type is that of its first value that is not null.
+//
+// Timestamps are delta-of-delta varints, floats and doubles are XORed with the previous
+// value keeping only the non-zero bytes, bools are bit packed and everything else,
+// min and max included, is written plain with integers as (zigzag) varints.
+
+const TSCOL_MAGIC: &[u8; 4] = b"TSCL";
+const TSCOL_VERSION: u8 = 2;
+
+fn write_value(field: &Field, buf: &mut Vec<u8>) {
+    match field {
+        Field::Bool(v) => buf.push(*v as u8),
+        Field::TinyInt(v) => write_varint(buf, *v as i64),
+        Field::SmallInt(v) => write_varint(buf, *v as i64),
+        Field::Int(v) => write_varint(buf, *v as i64),
+        Field::BigInt(v) => write_varint(buf, *v),
+        Field::Float(v) => buf.extend_from_slice(&v.to_le_bytes()),
+        Field::Double(v) => buf.extend_from_slice(&v.to_le_bytes()),
+        Field::Binary(v) => {
+            write_uvarint(buf, v.len() as u64);
+            buf.extend_from_slice(v);
+        }
+        Field::Timestamp(v) => write_varint(buf, v.as_raw_timestamp()),
+        Field::NChar(v) => {
+            write_uvarint(buf, v.len() as u64);
+            buf.extend_from_slice(v.as_bytes());
+        }
+        Field::UTinyInt(v) => write_uvarint(buf, *v as u64),
+        Field::USmallInt(v) => write_uvarint(buf, *v as u64),
+        Field::UInt(v) => write_uvarint(buf, *v as u64),
+        Field::UBigInt(v) => write_uvarint(buf, *v),
+        Field::Null | Field::Json(_) => unreachable!(),
+    }
+}
+
+fn read_value(data_type: TaosDataType, buf: &[u8], pos: &mut usize) -> Field {
+    let field = match data_type {
+        TaosDataType::Bool => Field::Bool(buf[*pos] != 0),
+        TaosDataType::TinyInt => Field::TinyInt(read_varint(buf, pos) as i8),
+        TaosDataType::SmallInt => Field::SmallInt(read_varint(buf, pos) as i16),
+        TaosDataType::Int => Field::Int(read_varint(buf, pos) as i32),
+        TaosDataType::BigInt => Field::BigInt(read_varint(buf, pos)),
+        TaosDataType::Float => {
+            Field::Float(f32::from_le_bytes(buf[*pos..*pos + 4].try_into().unwrap()))
+        }
+        TaosDataType::Double => {
+            Field::Double(f64::from_le_bytes(buf[*pos..*pos + 8].try_into().unwrap()))
+        }
+        TaosDataType::Binary | TaosDataType::NChar => {
+            let len = read_uvarint(buf, pos) as usize;
+            let v = &buf[*pos..*pos + len];
+            *pos += len;
+            return if data_type == TaosDataType::Binary {
+                Field::Binary(BString::from(v))
+            } else {
+                Field::NChar(String::from_utf8(v.to_vec()).unwrap())
+            };
+        }
+        TaosDataType::Timestamp => Field::Timestamp(Timestamp::new(
+            read_varint(buf, pos),
+            TimestampPrecision::Milli,
+        )),
+        TaosDataType::UTinyInt => Field::UTinyInt(read_uvarint(buf, pos) as u8),
+        TaosDataType::USmallInt => Field::USmallInt(read_uvarint(buf, pos) as u16),
+        TaosDataType::UInt => Field::UInt(read_uvarint(buf, pos) as u32),
+        TaosDataType::UBigInt => Field::UBigInt(read_uvarint(buf, pos)),
+        _ => unreachable!(),
+    };
+    // the fixed width values above did not advance `pos` themselves
+    *pos += match data_type {
+        TaosDataType::Bool => 1,
+        TaosDataType::Float => 4,
+        TaosDataType::Double => 8,
+        _ => 0,
+    };
+    field
+}
+
+pub(crate) fn less(a: &Field, b: &Field) -> bool {
+    match (a, b) {
+        (Field::Bool(a), Field::Bool(b)) => a < b,
+        (Field::TinyInt(a), Field::TinyInt(b)) => a < b,
+        (Field::SmallInt(a), Field::SmallInt(b)) => a < b,
+        (Field::Int(a), Field::Int(b)) => a < b,
+        (Field::BigInt(a), Field::BigInt(b)) => a < b,
+        (Field::Float(a), Field::Float(b)) => a < b,
+        (Field::Double(a), Field::Double(b)) => a < b,
+        (Field::Binary(a), Field::Binary(b)) => a < b,
+        (Field::Timestamp(a), Field::Timestamp(b)) => a.as_raw_timestamp() < b.as_raw_timestamp(),
+        (Field::NChar(a), Field::NChar(b)) => a < b,
+        (Field::UTinyInt(a), Field::UTinyInt(b)) => a < b,
+        (Field::USmallInt(a), Field::USmallInt(b)) => a < b,
+        (Field::UInt(a), Field::UInt(b)) => a < b,
+        (Field::UBigInt(a), Field::UBigInt(b)) => a < b,
+        _ => unreachable!(),
+    }
+}
+
+fn min_max(col: &[Field]) -> (&Field, &Field) {
+    let mut min = &col[0];
+    let mut max = &col[0];
+    for field in &col[1..] {
+        if less(field, min) {
+            min = field;
+        }
+        if less(max, field) {
+            max = field;
+        }
+    }
+    (min, max)
+}
+
+fn encode_timestamps(col: &[Field], buf: &mut Vec<u8>) {
+    let mut prev = 0i64;
+    let mut prev_delta = 0i64;
+    for field in col {
+        let ts = field.as_raw_timestamp().unwrap();
+        let delta = ts.wrapping_sub(prev);
+        write_varint(buf, delta.wrapping_sub(prev_delta));
+        prev = ts;
+        prev_delta = delta;
+    }
+}
+
+fn decode_timestamps(rows: usize, buf: &[u8]) -> Vec<Field> {
+    let mut pos = 0;
+    let mut prev = 0i64;
+    let mut prev_delta = 0i64;
+    let mut col = Vec::with_capacity(rows);
+    for _ in 0..rows {
+        let delta = prev_delta.wrapping_add(read_varint(buf, &mut pos));
+        prev = prev.wrapping_add(delta);
+        prev_delta = delta;
+        col.push(Field::Timestamp(Timestamp::new(
+            prev,
+            TimestampPrecision::Milli,
+        )));
+    }
+    col
+}
+
+// One control byte per value, leading zero bytes in the high and trailing zero bytes
+// in the low nibble, followed by the remaining bytes of the XOR. 0xff repeats the value.
+fn encode_xor(bits: impl Iterator<Item = u64>, width: u32, buf: &mut Vec<u8>) {
+    let mut prev = 0u64;
+    for v in bits {
+        let xor = v ^ prev;
+        prev = v;
+        if xor == 0 {
+            buf.push(0xff);
+            continue;
+        }
+        let lead = (xor.leading_zeros() - (64 - width * 8)) / 8;
+        let trail = xor.trailing_zeros() / 8;
+        buf.push(((lead << 4) | trail) as u8);
+        let bytes = (xor >> (trail * 8)).to_le_bytes();
+        buf.extend_from_slice(&bytes[..(width - lead - trail) as usize]);
+    }
+}
+
+fn decode_xor(rows: usize, buf: &[u8], width: u32) -> Vec<u64> {
+    let mut pos = 0;
+    let mut prev = 0u64;
+    let mut values = Vec::with_capacity(rows);
+    for _ in 0..rows {
+        let control = buf[pos] as u32;
+        pos += 1;
+        if control != 0xff {
+            let (lead, trail) = (control >> 4, control & 0xf);
+            let len = (width - lead - trail) as usize;
+            let mut bytes = [0; 8];
+            bytes[..len].copy_from_slice(&buf[pos..pos + len]);
+            pos += len;
+            prev ^= u64::from_le_bytes(bytes) << (trail * 8);
+        }
+        values.push(prev);
+    }
+    values
+}
+
+fn encode_column(data_type: TaosDataType, col: &[Field]) -> Vec<u8> {
+    let mut buf = vec![];
+    match data_type {
+        TaosDataType::Timestamp => encode_timestamps(col, &mut buf),
+        TaosDataType::Float => {
+            let bits = col.iter().map(|field| match field {
+                Field::Float(v) => v.to_bits() as u64,
+                _ => unreachable!(),
+            });
+            encode_xor(bits, 4, &mut buf)
+        }
+        TaosDataType::Double => {
+            let bits = col.iter().map(|field| match field {
+                Field::Double(v) => v.to_bits(),
+                _ => unreachable!(),
+            });
+            encode_xor(bits, 8, &mut buf)
+        }
+        TaosDataType::Bool => {
+            let mut writer = BitWriter::new();
+            for field in col {
+                writer.write_bit(*field == Field::Bool(true));
+            }
+            buf = writer.finish();
+        }
+        _ => {
+            for field in col {
+                write_value(field, &mut buf);
+            }
+        }
+    }
+    buf
+}
+
+fn decode_column(data_type: TaosDataType, rows: usize, buf: &[u8]) -> Vec<Field> {
+    match data_type {
+        TaosDataType::Timestamp => decode_timestamps(rows, buf),
+        TaosDataType::Float => decode_xor(rows, buf, 4)
+            .into_iter()
+            .map(|v| Field::Float(f32::from_bits(v as u32)))
+            .collect(),
+        TaosDataType::Double => decode_xor(rows, buf, 8)
+            .into_iter()
+            .map(|v| Field::Double(f64::from_bits(v)))
+            .collect(),
+        TaosDataType::Bool => {
+            let mut reader = BitReader::new(buf);
+            (0..rows).map(|_| Field::Bool(reader.read_bit())).collect()
+        }
+        _ => {
+            let mut pos = 0;
+            (0..rows)
+                .map(|_| read_value(data_type, buf, &mut pos))
+                .collect()
+        }
+    }
+}
+
+pub fn tscol_serialize(cols: &[Vec<Field>], block_rows: usize) -> Vec<u8> {
+    assert!(block_rows > 0, "block_rows must be at least 1");
+    let rows = cols.first().map_or(0, |col| col.len());
+    let data_types: Vec<_> = cols
+        .iter()
+        .map(|col| {
+            col.iter()
+                .find(|field| **field != Field::Null)
+                .map_or(TaosDataType::Null, Field::data_type)
+        })
+        .collect();
+    let mut buf = TSCOL_MAGIC.to_vec();
+    buf.push(TSCOL_VERSION);
+    buf.extend_from_slice(&(cols.len() as u16).to_le_bytes());
+    buf.extend(data_types.iter().map(|t| *t as u8));
+    let blocks = rows.div_ceil(block_rows);
+    buf.extend_from_slice(&(blocks as u32).to_le_bytes());
+    for block in 0..blocks {
+        let start = block * block_rows;
+        let end = rows.min(start + block_rows);
+        buf.extend_from_slice(&((end - start) as u32).to_le_bytes());
+        for (data_type, col) in data_types.iter().zip(cols) {
+            let col = &col[start..end];
+            let nulls = col.iter().filter(|field| **field == Field::Null).count();
+            write_uvarint(&mut buf, nulls as u64);
+            let values: Cow<[Field]> = if nulls == 0 {
+                Cow::Borrowed(col)
+            } else {
+                let mut bitmap = BitWriter::new();
+                for field in col {
+                    bitmap.write_bit(*field == Field::Null);
+                }
+                buf.extend_from_slice(&bitmap.finish());
+                col.iter()
+                    .filter(|field| **field != Field::Null)
+                    .cloned()
+                    .collect()
+            };
+            if !values.is_empty() {
+                let (min, max) = min_max(&values);
+                write_value(min, &mut buf);
+                write_value(max, &mut buf);
+            }
+            let data = encode_column(*data_type, &values);
+            buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&data);
+        }
+    }
+    buf
+}
+
+fn read_u32(buf: &[u8], pos: &mut usize) -> u32 {
+    let v = u32::from_le_bytes(buf[*pos..*pos + 4].try_into().unwrap());
+    *pos += 4;
+    v
+}
+
+pub struct TsColBlock<'a> {
+    pub rows: usize,
+    // min and max of every column, both null when every value is
+    pub stats: Vec<(Field, Field)>,
+    data_types: &'a [u8],
+    // the number of nulls and their bitmap
+    nulls: Vec<(usize, &'a [u8])>,
+    data: Vec<&'a [u8]>,
+}
+
+impl TsColBlock<'_> {
+    pub fn column(&self, index: usize) -> Vec<Field> {
+        let data_type = TaosDataType::from(self.data_types[index]);
+        let (nulls, bitmap) = self.nulls[index];
+        let values = decode_column(data_type, self.rows - nulls, self.data[index]);
+        if nulls == 0 {
+            return values;
+        }
+        let mut values = values.into_iter();
+        let mut reader = BitReader::new(bitmap);
+        (0..self.rows)
+            .map(|_| match reader.read_bit() {
+                true => Field::Null,
+                false => values.next().unwrap(),
+            })
+            .collect()
+    }
+}
+
+// Walks the block headers without decoding any column data.
+pub fn tscol_blocks(buf: &[u8]) -> Vec<TsColBlock<'_>> {
+    assert_eq!(&buf[..4], TSCOL_MAGIC);
+    assert_eq!(buf[4], TSCOL_VERSION);
+    let ncols = u16::from_le_bytes([buf[5], buf[6]]) as usize;
+    let data_types = &buf[7..7 + ncols];
+    let mut pos = 7 + ncols;
+    let blocks = read_u32(buf, &mut pos);
+    let mut result = Vec::with_capacity(blocks as usize);
+    for _ in 0..blocks {
+        let rows = read_u32(buf, &mut pos) as usize;
+        let mut stats = Vec::with_capacity(ncols);
+        let mut nulls = Vec::with_capacity(ncols);
+        let mut data = Vec::with_capacity(ncols);
+        for data_type in data_types {
+            let data_type = TaosDataType::from(*data_type);
+            let null_count = read_uvarint(buf, &mut pos) as usize;
+            let bitmap_len = match null_count {
+                0 => 0,
+                _ => rows.div_ceil(8),
+            };
+            nulls.push((null_count, &buf[pos..pos + bitmap_len]));
+            pos += bitmap_len;
+            if null_count < rows {
+                let min = read_value(data_type, buf, &mut pos);
+                let max = read_value(data_type, buf, &mut pos);
+                stats.push((min, max));
+            } else {
+                stats.push((Field::Null, Field::Null));
+            }
+            let len = read_u32(buf, &mut pos) as usize;
+            data.push(&buf[pos..pos + len]);
+            pos += len;
+        }
+        result.push(TsColBlock {
+            rows,
+            stats,
+            data_types,
+            nulls,
+            data,
+        });
+    }
+    result
+}
+
+pub fn tscol_deserialize(buf: &[u8]) -> Vec<Vec<Field>> {
+    let ncols = u16::from_le_bytes([buf[5], buf[6]]) as usize;
+    let mut cols = vec![vec![]; ncols];
+    for block in tscol_blocks(buf) {
+        for (index, col) in cols.iter_mut().enumerate() {
+            col.extend(block.column(index));
+        }
+    }
+    cols
+}
+
+pub fn tscol_serialized_write(filename: &str, cols: &[Vec<Field>], block_rows: usize) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&tscol_serialize(cols, block_rows)).unwrap();
+}
+
+pub fn tscol_read(filename: &str) -> u32 {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    let cols = tscol_deserialize(&buf);
+    cols.first().map_or(0, |col| col.len() as u32)
+}
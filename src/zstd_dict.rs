@@ -0,0 +1,104 @@
+use std::{
+    convert::TryInto,
+    fs::File,
+    io::{Read, Write},
+};
+
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+// A zstd dictionary trained on sample messages of any format, for batches too small for
+// zstd to find much to reuse within a single message.
+
+pub struct ZstdDictionary {
+    dictionary: Vec<u8>,
+    encoder: EncoderDictionary<'static>,
+    decoder: DecoderDictionary<'static>,
+}
+
+impl ZstdDictionary {
+    pub fn new(dictionary: Vec<u8>, level: i32) -> Self {
+        let encoder = EncoderDictionary::copy(&dictionary, level);
+        let decoder = DecoderDictionary::copy(&dictionary);
+        Self {
+            dictionary,
+            encoder,
+            decoder,
+        }
+    }
+
+    // zstd needs a few dozen samples at least, and rejects `max_size` under 1KB or so.
+    pub fn train<S: AsRef<[u8]>>(samples: &[S], max_size: usize, level: i32) -> Self {
+        Self::new(zstd::dict::from_samples(samples, max_size).unwrap(), level)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.dictionary
+    }
+
+    pub fn compress(&self, buf: &[u8]) -> Vec<u8> {
+        zstd::bulk::Compressor::with_prepared_dictionary(&self.encoder)
+            .unwrap()
+            .compress(buf)
+            .unwrap()
+    }
+
+    pub fn decompress(&self, buf: &[u8]) -> Vec<u8> {
+        let mut decompressed = vec![];
+        zstd::stream::read::Decoder::with_prepared_dictionary(buf, &self.decoder)
+            .unwrap()
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        decompressed
+    }
+}
+
+// Messages compressed one by one, with `dictionary` or plain zstd at `level`, each
+// written as | length: u32 | zstd frame |.
+pub fn zstd_messages_serialize(
+    messages: &[Vec<u8>],
+    dictionary: Option<&ZstdDictionary>,
+    level: i32,
+) -> Vec<u8> {
+    let mut buf = vec![];
+    for message in messages {
+        let frame = match dictionary {
+            Some(dictionary) => dictionary.compress(message),
+            None => zstd::bulk::compress(message, level).unwrap(),
+        };
+        buf.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&frame);
+    }
+    buf
+}
+
+pub fn zstd_messages_deserialize(buf: &[u8], dictionary: Option<&ZstdDictionary>) -> Vec<Vec<u8>> {
+    let mut messages = vec![];
+    let mut pos = 0;
+    while pos < buf.len() {
+        let len = u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap()) as usize;
+        let frame = &buf[pos + 4..pos + 4 + len];
+        messages.push(match dictionary {
+            Some(dictionary) => dictionary.decompress(frame),
+            None => zstd::stream::decode_all(frame).unwrap(),
+        });
+        pos += 4 + len;
+    }
+    messages
+}
+
+pub fn zstd_messages_serialized_write(
+    filename: &str,
+    messages: &[Vec<u8>],
+    dictionary: Option<&ZstdDictionary>,
+    level: i32,
+) {
+    let mut file = File::create(filename).unwrap();
+    file.write_all(&zstd_messages_serialize(messages, dictionary, level))
+        .unwrap();
+}
+
+pub fn zstd_messages_read(filename: &str, dictionary: Option<&ZstdDictionary>) -> u32 {
+    let mut buf = vec![];
+    File::open(filename).unwrap().read_to_end(&mut buf).unwrap();
+    zstd_messages_deserialize(&buf, dictionary).len() as u32
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use avro_rs::Codec;
+use bstr::BString;
+use common::{temp_dir, temp_path, TempDir};
+use libtaos::Field;
+use serde_json::json;
+use taosx_data_format_bench::{
+    avro::{avro_read_with_schema, avro_serialized_write, AvroColumn},
+    generate_data,
+};
+
+fn write(dir: &TempDir, data_types: &Vec<&str>, size: u32) -> (String, Vec<Vec<Field>>) {
+    let filename = temp_path(dir, "data.avro");
+    let (rows, _) = generate_data(data_types, size);
+    avro_serialized_write(&filename, data_types, &rows, Codec::Deflate, None);
+    (filename, rows)
+}
+
+fn column(name: &'static str) -> AvroColumn<'static> {
+    AvroColumn {
+        name,
+        data_type: name,
+        default: None,
+    }
+}
+
+#[test]
+fn same_schema() {
+    let data_types = vec!["timestamp", "int", "float", "bool", "binary", "nchar"];
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, &data_types, 100);
+    let columns: Vec<_> = data_types.iter().map(|t| column(t)).collect();
+    assert_eq!(avro_read_with_schema(&filename, &columns), rows);
+}
+
+#[test]
+fn added_columns_take_defaults() {
+    let data_types = vec!["timestamp", "int"];
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, &data_types, 100);
+    let columns = vec![
+        column("timestamp"),
+        column("int"),
+        AvroColumn {
+            name: "status",
+            data_type: "tinyint",
+            default: Some(Field::TinyInt(7)),
+        },
+        AvroColumn {
+            name: "location",
+            data_type: "binary",
+            default: Some(Field::Binary(BString::from(vec![0xff, 0x00, b'?', 0xe9]))),
+        },
+        AvroColumn {
+            name: "comment",
+            data_type: "nchar",
+            default: Some(Field::Null),
+        },
+        AvroColumn {
+            name: "voltage",
+            data_type: "double",
+            default: Some(Field::Double(220.5)),
+        },
+        AvroColumn {
+            name: "labels",
+            data_type: "json",
+            default: Some(Field::Json(json!({ "site": "温度", "floor": 3 }))),
+        },
+    ];
+    let read = avro_read_with_schema(&filename, &columns);
+    assert_eq!(read.len(), rows.len());
+    for (read, row) in read.iter().zip(&rows) {
+        assert_eq!(read[..2], row[..]);
+        assert_eq!(
+            read[2..],
+            [
+                Field::TinyInt(7),
+                Field::Binary(BString::from(vec![0xff, 0x00, b'?', 0xe9])),
+                Field::Null,
+                Field::Double(220.5),
+                Field::Json(json!({ "site": "温度", "floor": 3 })),
+            ]
+        );
+    }
+}
+
+#[test]
+fn dropped_and_reordered_columns() {
+    let data_types = vec!["timestamp", "int", "float", "nchar"];
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, &data_types, 100);
+    let columns = vec![column("nchar"), column("timestamp")];
+    let read = avro_read_with_schema(&filename, &columns);
+    for (read, row) in read.iter().zip(&rows) {
+        assert_eq!(read, &vec![row[3].clone(), row[0].clone()]);
+    }
+}
+
+#[test]
+fn widened_types() {
+    let data_types = vec!["tinyint", "int", "uint", "float"];
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, &data_types, 100);
+    let columns = vec![
+        AvroColumn {
+            name: "tinyint",
+            data_type: "smallint",
+            default: None,
+        },
+        AvroColumn {
+            name: "int",
+            data_type: "bigint",
+            default: None,
+        },
+        AvroColumn {
+            name: "uint",
+            data_type: "double",
+            default: None,
+        },
+        AvroColumn {
+            name: "float",
+            data_type: "double",
+            default: None,
+        },
+    ];
+    let read = avro_read_with_schema(&filename, &columns);
+    for (read, row) in read.iter().zip(&rows) {
+        match (&row[0], &row[1], &row[2], &row[3]) {
+            (Field::TinyInt(a), Field::Int(b), Field::UInt(c), Field::Float(d)) => assert_eq!(
+                read,
+                &vec![
+                    Field::SmallInt(*a as i16),
+                    Field::BigInt(*b as i64),
+                    Field::Double(*c as f64),
+                    Field::Double(*d as f64),
+                ]
+            ),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "cannot be read with the reader schema")]
+fn added_column_without_default() {
+    let data_types = vec!["timestamp", "int"];
+    let dir = temp_dir();
+    let (filename, _) = write(&dir, &data_types, 10);
+    avro_read_with_schema(&filename, &[column("timestamp"), column("double")]);
+}
+
+#[test]
+#[should_panic(expected = "cannot be read with the reader schema")]
+fn narrowed_type() {
+    let data_types = vec!["timestamp", "bigint"];
+    let dir = temp_dir();
+    let (filename, _) = write(&dir, &data_types, 10);
+    let columns = vec![
+        column("timestamp"),
+        AvroColumn {
+            name: "bigint",
+            data_type: "int",
+            default: None,
+        },
+    ];
+    avro_read_with_schema(&filename, &columns);
+}
+
+#[test]
+#[should_panic(expected = "cannot be read with the reader schema")]
+fn incompatible_type() {
+    let data_types = vec!["timestamp", "nchar"];
+    let dir = temp_dir();
+    let (filename, _) = write(&dir, &data_types, 10);
+    let columns = vec![
+        column("timestamp"),
+        AvroColumn {
+            name: "nchar",
+            data_type: "int",
+            default: None,
+        },
+    ];
+    avro_read_with_schema(&filename, &columns);
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use avro_rs::Codec;
+use common::{temp_dir, temp_path, TempDir};
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use taosx_data_format_bench::{
+    avro::{avro_read, avro_serialized_write},
+    avro_index::{avro_index_filename, avro_serialize_indexed, AvroBlock, AvroIndexedReader},
+    generate_data,
+};
+
+const START: i64 = 1_600_000_000_000;
+
+// 10000 rows a second apart in blocks of 1000.
+fn write(dir: &TempDir, compression: Codec) -> (String, Vec<Vec<Field>>) {
+    let data_types = vec!["timestamp", "int", "double", "binary", "nchar"];
+    let (mut rows, _) = generate_data(&data_types, 10000);
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = Field::Timestamp(Timestamp::new(
+            START + i as i64 * 1000,
+            TimestampPrecision::Milli,
+        ));
+    }
+    let filename = temp_path(dir, "data.avro");
+    avro_serialized_write(&filename, &data_types, &rows, compression, Some(1000));
+    (filename, rows)
+}
+
+#[test]
+fn indexed_file_is_still_a_container() {
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, Codec::Deflate);
+    assert_eq!(avro_read(&filename), rows.len() as u32);
+}
+
+#[test]
+fn index_records_every_block() {
+    let dir = temp_dir();
+    let (filename, _) = write(&dir, Codec::Null);
+    let reader = AvroIndexedReader::open(&filename);
+    assert_eq!(reader.blocks().len(), 10);
+    assert_eq!(reader.rows(), 10000);
+    assert_eq!(
+        reader.blocks()[3],
+        AvroBlock {
+            offset: reader.blocks()[3].offset,
+            rows: 1000,
+            min_ts: START + 3000 * 1000,
+            max_ts: START + 3999 * 1000,
+        }
+    );
+    assert!(reader
+        .blocks()
+        .windows(2)
+        .all(|pair| pair[0].offset < pair[1].offset));
+}
+
+#[test]
+fn seeks_to_block_by_row_and_timestamp() {
+    for compression in [Codec::Null, Codec::Deflate] {
+        let dir = temp_dir();
+        let (filename, rows) = write(&dir, compression);
+        let mut reader = AvroIndexedReader::open(&filename);
+        assert_eq!(reader.read_block(7), rows[7000..8000]);
+        assert_eq!(reader.block_of_row(6999), Some(6));
+        assert_eq!(reader.read_row(6999).unwrap(), rows[6999]);
+        assert_eq!(reader.read_row(0).unwrap(), rows[0]);
+        assert_eq!(reader.read_row(9999).unwrap(), rows[9999]);
+        assert_eq!(reader.read_row(10000), None);
+
+        assert_eq!(reader.blocks_of_timestamp(START + 4500 * 1000), vec![4]);
+        assert_eq!(reader.read_timestamp(START + 4500 * 1000), rows[4500..4501]);
+        assert!(reader.read_timestamp(START - 1).is_empty());
+    }
+}
+
+#[test]
+fn empty_files_have_a_header() {
+    let dir = temp_dir();
+    let filename = temp_path(&dir, "empty.avro");
+    avro_serialized_write(
+        &filename,
+        &vec!["timestamp", "int"],
+        &vec![],
+        Codec::Deflate,
+        Some(1000),
+    );
+    assert_eq!(avro_read(&filename), 0);
+    let mut reader = AvroIndexedReader::open(&filename);
+    assert_eq!(reader.rows(), 0);
+    assert_eq!(reader.read_row(0), None);
+    assert!(reader.read_timestamp(START).is_empty());
+}
+
+#[test]
+fn unindexed_writes_have_no_index() {
+    let dir = temp_dir();
+    let filename = temp_path(&dir, "data.avro");
+    let data_types = vec!["timestamp", "int"];
+    let (rows, _) = generate_data(&data_types, 10);
+    avro_serialized_write(&filename, &data_types, &rows, Codec::Deflate, None);
+    assert_eq!(avro_read(&filename), 10);
+    assert!(!std::path::Path::new(&avro_index_filename(&filename)).exists());
+}
+
+#[test]
+#[should_panic(expected = "block_rows")]
+fn empty_blocks() {
+    avro_serialize_indexed(&vec!["timestamp"], &[], Codec::Null, 0);
+}
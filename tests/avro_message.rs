@@ -0,0 +1,75 @@
+use avro_rs::Codec;
+use taosx_data_format_bench::{
+    avro::{avro_container_serialize, avro_deserialize, generate_avro_schema},
+    avro_message::{
+        avro_fingerprint, avro_single_object_decode, avro_single_object_deserialize,
+        avro_single_object_serialize, SchemaStore, SINGLE_OBJECT_MAGIC,
+    },
+    generate_data,
+};
+
+const DATA_TYPES: [&str; 14] = [
+    "timestamp",
+    "tinyint",
+    "utinyint",
+    "smallint",
+    "usmallint",
+    "int",
+    "uint",
+    "bigint",
+    "ubigint",
+    "float",
+    "double",
+    "bool",
+    "binary",
+    "nchar",
+];
+
+#[test]
+fn single_object_round_trip() {
+    let data_types = DATA_TYPES.to_vec();
+    let (rows, _) = generate_data(&data_types, 100);
+    let mut store = SchemaStore::new();
+    let fingerprint = store.register(generate_avro_schema(&data_types));
+    let messages = avro_single_object_serialize(&data_types, &rows);
+    for message in &messages {
+        assert_eq!(message[..2], SINGLE_OBJECT_MAGIC);
+        assert_eq!(message[2..10], fingerprint.to_le_bytes());
+    }
+    assert_eq!(avro_single_object_deserialize(&store, &messages), rows);
+    assert_eq!(
+        avro_deserialize(&avro_container_serialize(
+            &data_types,
+            &rows,
+            Codec::Deflate
+        )),
+        rows
+    );
+}
+
+#[test]
+fn fingerprint_follows_schema() {
+    let data_types = DATA_TYPES.to_vec();
+    let schema = generate_avro_schema(&data_types);
+    assert_eq!(avro_fingerprint(&schema), avro_fingerprint(&schema.clone()));
+    let other = generate_avro_schema(&data_types[..2].to_vec());
+    assert_ne!(avro_fingerprint(&schema), avro_fingerprint(&other));
+}
+
+#[test]
+#[should_panic(expected = "unknown schema fingerprint")]
+fn unknown_fingerprint() {
+    let data_types = DATA_TYPES.to_vec();
+    let (rows, _) = generate_data(&data_types, 1);
+    let messages = avro_single_object_serialize(&data_types, &rows);
+    avro_single_object_decode(&SchemaStore::new(), &messages[0]);
+}
+
+#[test]
+fn smaller_than_container_per_row() {
+    let data_types = DATA_TYPES.to_vec();
+    let (rows, _) = generate_data(&data_types, 1);
+    let message = &avro_single_object_serialize(&data_types, &rows)[0];
+    let container = avro_container_serialize(&data_types, &rows, Codec::Null);
+    assert!(message.len() * 4 < container.len());
+}
@@ -0,0 +1,91 @@
+#![allow(dead_code)]
+
+use bstr::BString;
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use serde_json::json;
+pub use tempfile::TempDir;
+
+// Fixtures shared by the integration tests.
+
+pub const EVERY_TYPE: [&str; 14] = [
+    "timestamp",
+    "bool",
+    "tinyint",
+    "smallint",
+    "int",
+    "bigint",
+    "float",
+    "double",
+    "binary",
+    "nchar",
+    "utinyint",
+    "usmallint",
+    "uint",
+    "ubigint",
+];
+
+// Rows of `EVERY_TYPE` in `precision`: one of nulls but for the timestamp, then the
+// smallest and largest value of each type, with bytes that are not UTF-8 and multi-byte
+// characters for binary and nchar.
+pub fn every_type_rows(precision: TimestampPrecision) -> Vec<Vec<Field>> {
+    let ts = |raw| Field::Timestamp(Timestamp::new(raw, precision));
+    let mut nulls = vec![Field::Null; EVERY_TYPE.len()];
+    nulls[0] = ts(1_700_000_000_000);
+    vec![
+        nulls,
+        vec![
+            ts(1_700_000_000_001),
+            Field::Bool(false),
+            Field::TinyInt(i8::MIN),
+            Field::SmallInt(i16::MIN),
+            Field::Int(i32::MIN),
+            Field::BigInt(i64::MIN),
+            Field::Float(f32::MIN),
+            Field::Double(f64::MIN),
+            Field::Binary(BString::from(vec![0xff, 0x00, b'"', b'\'', b',', b' '])),
+            Field::NChar("温度, \"quoted\" 'x' =\\".to_string()),
+            Field::UTinyInt(0),
+            Field::USmallInt(0),
+            Field::UInt(0),
+            Field::UBigInt(0),
+        ],
+        vec![
+            ts(1_700_000_000_002),
+            Field::Bool(true),
+            Field::TinyInt(i8::MAX),
+            Field::SmallInt(i16::MAX),
+            Field::Int(i32::MAX),
+            Field::BigInt(i64::MAX),
+            Field::Float(f32::MAX),
+            Field::Double(-0.5),
+            Field::Binary(BString::from("")),
+            Field::NChar(String::new()),
+            Field::UTinyInt(u8::MAX),
+            Field::USmallInt(u16::MAX),
+            Field::UInt(u32::MAX),
+            Field::UBigInt(u64::MAX),
+        ],
+    ]
+}
+
+// A JSON tag value, the one type TDengine only has for tags.
+pub fn json_field() -> Field {
+    Field::Json(json!({ "site": "温度", "floor": 3, "tags": ["a", null] }))
+}
+
+// `every_type_rows` for the text formats, which only carry binary values that are UTF-8.
+pub fn every_type_text_rows(precision: TimestampPrecision) -> Vec<Vec<Field>> {
+    let mut rows = every_type_rows(precision);
+    rows[1][8] = Field::Binary(BString::from("\"b\\,=\" x"));
+    rows
+}
+
+// A directory unique to this run, removed with everything in it when dropped.
+pub fn temp_dir() -> TempDir {
+    tempfile::tempdir().unwrap()
+}
+
+// `name` in `dir`, as the string paths the writers take.
+pub fn temp_path(dir: &TempDir, name: &str) -> String {
+    dir.path().join(name).to_string_lossy().to_string()
+}
@@ -0,0 +1,120 @@
+use libtaos::Field;
+use parquet::basic::Compression;
+use rand::Rng;
+use taosx_data_format_bench::{
+    float_codec::{
+        chimp_decode_f32, chimp_decode_f64, chimp_encode_f32, chimp_encode_f64,
+        float_column_avro_deserialize, float_column_avro_serialize, float_column_deserialize,
+        float_column_serialize, gorilla_decode_f32, gorilla_decode_f64, gorilla_encode_f32,
+        gorilla_encode_f64, FloatCodec,
+    },
+    generate_data,
+};
+
+fn f64_samples() -> Vec<Vec<f64>> {
+    let mut rng = rand::thread_rng();
+    vec![
+        vec![],
+        vec![42.5],
+        vec![1.0; 100],
+        (0..1000).map(|_| rng.gen()).collect(),
+        (0..1000).map(|_| rng.gen::<f64>() * 1e300).collect(),
+        (0..1000).map(|i| 20.0 + (i / 10) as f64 * 0.25).collect(),
+        (0..1000).map(|i| (i as f64 / 50.0).sin() * 100.0).collect(),
+        vec![
+            0.0,
+            -0.0,
+            f64::MIN,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::EPSILON,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+            5e-324,
+            0.0,
+            0.0,
+        ],
+    ]
+}
+
+fn f32_samples() -> Vec<Vec<f32>> {
+    f64_samples()
+        .into_iter()
+        .map(|values| values.into_iter().map(|v| v as f32).collect())
+        .collect()
+}
+
+fn bits_f64(values: &[f64]) -> Vec<u64> {
+    values.iter().map(|v| v.to_bits()).collect()
+}
+
+fn bits_f32(values: &[f32]) -> Vec<u32> {
+    values.iter().map(|v| v.to_bits()).collect()
+}
+
+#[test]
+fn gorilla_round_trip_f64() {
+    for values in f64_samples() {
+        let decoded = gorilla_decode_f64(&gorilla_encode_f64(&values));
+        assert_eq!(bits_f64(&decoded), bits_f64(&values));
+    }
+}
+
+#[test]
+fn gorilla_round_trip_f32() {
+    for values in f32_samples() {
+        let decoded = gorilla_decode_f32(&gorilla_encode_f32(&values));
+        assert_eq!(bits_f32(&decoded), bits_f32(&values));
+    }
+}
+
+#[test]
+fn chimp_round_trip_f64() {
+    for values in f64_samples() {
+        let decoded = chimp_decode_f64(&chimp_encode_f64(&values));
+        assert_eq!(bits_f64(&decoded), bits_f64(&values));
+    }
+}
+
+#[test]
+fn chimp_round_trip_f32() {
+    for values in f32_samples() {
+        let decoded = chimp_decode_f32(&chimp_encode_f32(&values));
+        assert_eq!(bits_f32(&decoded), bits_f32(&values));
+    }
+}
+
+#[test]
+fn repeated_values_compress() {
+    let values = vec![3.25f64; 1000];
+    assert!(gorilla_encode_f64(&values).len() < 200);
+    assert!(chimp_encode_f64(&values).len() < 300);
+}
+
+#[test]
+fn column_round_trip() {
+    let (_, cols) = generate_data(&vec!["float", "double"], 1000);
+    for col in &cols {
+        for codec in [FloatCodec::Gorilla, FloatCodec::Chimp] {
+            for compression in [
+                Compression::UNCOMPRESSED,
+                Compression::SNAPPY,
+                Compression::ZSTD,
+            ] {
+                let buf = float_column_serialize(col, codec, compression);
+                assert_eq!(&float_column_deserialize(&buf, codec, compression), col);
+            }
+            for avro in [avro_rs::Codec::Null, avro_rs::Codec::Deflate] {
+                let buf = float_column_avro_serialize(col, codec, avro);
+                assert_eq!(&float_column_avro_deserialize(&buf, codec, avro), col);
+            }
+        }
+    }
+    let col = vec![Field::Double(1.5)];
+    let buf = float_column_serialize(&col, FloatCodec::Chimp, Compression::UNCOMPRESSED);
+    assert_eq!(
+        float_column_deserialize(&buf, FloatCodec::Chimp, Compression::UNCOMPRESSED),
+        col
+    );
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::every_type_rows;
+use libtaos::{Field, TimestampPrecision};
+use taosx_data_format_bench::{
+    general_codec::{fixed_width_buffer, general_codecs},
+    rows_to_cols,
+};
+
+#[test]
+fn null_first_columns() {
+    // every column but the timestamp starts with a null
+    let cols = rows_to_cols(&every_type_rows(TimestampPrecision::Milli));
+    let buf = fixed_width_buffer(&cols);
+    let nulls = vec![Field::Null; cols[0].len()];
+    let no_nulls: Vec<_> = cols
+        .iter()
+        .map(|col| {
+            let mut col = col.clone();
+            col[0] = col[1].clone();
+            col
+        })
+        .collect();
+    assert_eq!(buf.len(), fixed_width_buffer(&no_nulls).len());
+
+    let var = vec![Field::Null, Field::NChar("abc".to_string()), Field::Null];
+    let mut expected = vec![];
+    for value in [&b""[..], b"abc", b""] {
+        expected.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        expected.extend_from_slice(value);
+        expected.resize(expected.len() + 3 - value.len(), 0);
+    }
+    assert_eq!(fixed_width_buffer(&[var]), expected);
+    assert_eq!(fixed_width_buffer(&[nulls]), vec![0; 4 * cols[0].len()]);
+    assert_eq!(
+        fixed_width_buffer(&[vec![Field::Null, Field::Int(-1)]]),
+        vec![0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]
+    );
+}
+
+#[test]
+fn codecs_round_trip() {
+    let cols = rows_to_cols(&every_type_rows(TimestampPrecision::Milli));
+    let buf = fixed_width_buffer(&cols);
+    for codec in general_codecs() {
+        assert_eq!(codec.decompress(&codec.compress(&buf)), buf, "{}", codec);
+    }
+}
@@ -0,0 +1,105 @@
+use rand::Rng;
+use taosx_data_format_bench::{
+    generate_data,
+    int_codec::{
+        frame_of_reference_decode, frame_of_reference_encode, int_column_decode, int_column_encode,
+        int_decode_i64, int_encode_i64, simple8b_decode, simple8b_encode, varint_decode,
+        varint_encode, IntCodec,
+    },
+};
+
+const CODECS: [IntCodec; 3] = [
+    IntCodec::Varint,
+    IntCodec::Simple8b,
+    IntCodec::FrameOfReference,
+];
+
+fn u64_samples() -> Vec<Vec<u64>> {
+    let mut rng = rand::thread_rng();
+    vec![
+        vec![],
+        vec![7],
+        vec![0; 1000],
+        (0..1000).map(|_| rng.gen()).collect(),
+        (0..1000).map(|_| rng.gen_range(0..16)).collect(),
+        (0..1000).map(|i| 1_000_000 + i).collect(),
+        (0..1000)
+            .map(|i| if i % 100 == 0 { u64::MAX } else { i % 3 })
+            .collect(),
+        vec![0, u64::MAX, 1 << 60, (1 << 60) - 1, 1 << 59, 0, 0],
+    ]
+}
+
+#[test]
+fn varint_round_trip() {
+    for values in u64_samples() {
+        assert_eq!(varint_decode(&varint_encode(&values)), values);
+    }
+}
+
+#[test]
+fn simple8b_round_trip() {
+    for values in u64_samples() {
+        assert_eq!(simple8b_decode(&simple8b_encode(&values)), values);
+    }
+}
+
+#[test]
+fn frame_of_reference_round_trip() {
+    for values in u64_samples() {
+        assert_eq!(
+            frame_of_reference_decode(&frame_of_reference_encode(&values)),
+            values
+        );
+    }
+}
+
+#[test]
+fn signed_round_trip() {
+    let mut rng = rand::thread_rng();
+    let samples: Vec<Vec<i64>> = vec![
+        vec![],
+        (0..1000).map(|_| rng.gen()).collect(),
+        (0..1000).map(|_| rng.gen_range(-100..100)).collect(),
+        vec![i64::MIN, i64::MAX, 0, -1, 1],
+    ];
+    for values in samples {
+        for codec in CODECS {
+            assert_eq!(
+                int_decode_i64(&int_encode_i64(&values, codec), codec),
+                values
+            );
+        }
+    }
+}
+
+#[test]
+fn small_values_compress() {
+    let values: Vec<u64> = (0..1000).map(|i| i % 4).collect();
+    assert!(varint_encode(&values).len() <= 1002);
+    assert!(simple8b_encode(&values).len() < 300);
+    assert!(frame_of_reference_encode(&values).len() < 300);
+}
+
+#[test]
+fn column_round_trip() {
+    let data_types = vec![
+        "tinyint",
+        "utinyint",
+        "smallint",
+        "usmallint",
+        "int",
+        "uint",
+        "bigint",
+        "ubigint",
+    ];
+    let (_, cols) = generate_data(&data_types, 1000);
+    for col in &cols {
+        for codec in CODECS {
+            assert_eq!(
+                &int_column_decode(&int_column_encode(col, codec), codec),
+                col
+            );
+        }
+    }
+}
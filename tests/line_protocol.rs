@@ -0,0 +1,132 @@
+mod common;
+
+use common::{every_type_text_rows, json_field, EVERY_TYPE};
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use taosx_data_format_bench::{
+    line_protocol::{line_protocol_parse, line_protocol_serialize},
+    SuperTable,
+};
+
+#[test]
+fn round_trip() {
+    let data_types = EVERY_TYPE.to_vec();
+    let mut tag_types = EVERY_TYPE.to_vec();
+    tag_types.push("json");
+    for precision in [
+        TimestampPrecision::Milli,
+        TimestampPrecision::Micro,
+        TimestampPrecision::Nano,
+    ] {
+        let stable = SuperTable {
+            name: "meters, all",
+            data_types: &data_types,
+            tag_types: &tag_types,
+            precision,
+        };
+        let rows = every_type_text_rows(precision);
+        for tag_row in &rows {
+            let mut tags = tag_row.clone();
+            tags.push(json_field());
+            let lines = line_protocol_serialize(&stable, &tags, &rows);
+            // the row of nulls has no field to write
+            let expected: Vec<_> = rows[1..]
+                .iter()
+                .map(|row| (tags.clone(), row.clone()))
+                .collect();
+            assert_eq!(line_protocol_parse(&stable, &lines).unwrap(), expected);
+        }
+    }
+}
+
+#[test]
+fn special_doubles_and_nulls() {
+    let data_types = vec!["timestamp", "double", "int"];
+    let tag_types = vec!["nchar", "json"];
+    let stable = SuperTable {
+        name: "d",
+        data_types: &data_types,
+        tag_types: &tag_types,
+        precision: TimestampPrecision::Milli,
+    };
+    let ts = |raw| Field::Timestamp(Timestamp::new(raw, TimestampPrecision::Milli));
+    let rows = vec![
+        vec![ts(1), Field::Double(f64::INFINITY), Field::Null],
+        vec![ts(2), Field::Double(f64::NEG_INFINITY), Field::Int(1)],
+        vec![ts(3), Field::Null, Field::Null],
+        vec![ts(4), Field::Null, Field::Int(-1)],
+    ];
+    let tags = vec![Field::Null, Field::Null];
+    let lines = line_protocol_serialize(&stable, &tags, &rows);
+    assert_eq!(lines.lines().count(), 3);
+    let parsed: Vec<_> = line_protocol_parse(&stable, &lines)
+        .unwrap()
+        .into_iter()
+        .map(|(tags, row)| {
+            assert_eq!(tags, vec![Field::Null, Field::Null]);
+            row
+        })
+        .collect();
+    assert_eq!(
+        parsed,
+        vec![rows[0].clone(), rows[1].clone(), rows[3].clone()]
+    );
+
+    let nan = line_protocol_serialize(
+        &stable,
+        &tags,
+        &[vec![ts(5), Field::Double(f64::NAN), Field::Null]],
+    );
+    match &line_protocol_parse(&stable, &nan).unwrap()[0].1[1] {
+        Field::Double(v) => assert!(v.is_nan()),
+        field => panic!("{:?}", field),
+    }
+}
+
+#[test]
+fn bools_missing_timestamps_and_bad_lines() {
+    let data_types = vec!["timestamp", "bool", "int"];
+    let tag_types = vec!["int"];
+    let stable = SuperTable {
+        name: "d",
+        data_types: &data_types,
+        tag_types: &tag_types,
+        precision: TimestampPrecision::Milli,
+    };
+    let bools = [
+        "t", "T", "true", "True", "TRUE", "f", "F", "false", "False", "FALSE",
+    ];
+    let lines: String = bools.iter().map(|b| format!("d bool={} 1\n", b)).collect();
+    let parsed = line_protocol_parse(&stable, &lines).unwrap();
+    for (i, (_, row)) in parsed.iter().enumerate() {
+        assert_eq!(row[1], Field::Bool(i < 5));
+    }
+
+    let before = chrono::Utc::now().timestamp_millis();
+    let parsed = line_protocol_parse(&stable, "d,t_int=1 int=2i32").unwrap();
+    match &parsed[0].1[0] {
+        Field::Timestamp(ts) => assert!(ts.as_raw_timestamp() >= before),
+        field => panic!("{:?}", field),
+    }
+
+    for (lines, error) in [
+        ("d bool=yes 1", "line 1: \"yes\" is not a valid bool"),
+        (
+            "\nd bool=t 1\nd nope=1 1",
+            "line 3: no column nope in the super table",
+        ),
+        (
+            "d,t_nope=1 bool=t 1",
+            "line 1: no column t_nope in the super table",
+        ),
+        ("d,t_int=x bool=t 1", "line 1: \"x\" is not a valid int"),
+        ("d int=1.5 1", "line 1: \"1.5\" is not a valid int"),
+        ("d bool=t x", "line 1: \"x\" is not a valid timestamp"),
+        ("d bool", "line 1: \"bool\" is not a name=value pair"),
+        (
+            "d",
+            "line 1: expected a series key, fields and an optional timestamp",
+        ),
+    ] {
+        assert_eq!(line_protocol_parse(&stable, lines).unwrap_err(), error);
+    }
+}
@@ -0,0 +1,123 @@
+use libtaos::Field;
+use parquet::basic::Compression;
+use rand::Rng;
+use taosx_data_format_bench::{
+    float_codec::{float_column_encode, FloatCodec},
+    generate_data,
+    lossy::{
+        lossy_column, lossy_f32, lossy_f64, max_error, sz_column_decode, sz_column_deserialize,
+        sz_column_encode, sz_column_serialize, ErrorBound,
+    },
+};
+
+fn columns() -> Vec<Vec<Field>> {
+    let mut rng = rand::thread_rng();
+    let (_, mut cols) = generate_data(&vec!["float", "double"], 1000);
+    cols.push(
+        (0..1000)
+            .map(|i| Field::Double((i as f64 / 30.0).sin() * 50.0 + 20.0))
+            .collect(),
+    );
+    cols.push(
+        (0..1000)
+            .map(|_| Field::Double(rng.gen_range(-1e12..1e12)))
+            .collect(),
+    );
+    cols.push(
+        (0..1000)
+            .map(|i| Field::Float((i as f32 / 30.0).cos() * 1e4))
+            .collect(),
+    );
+    cols
+}
+
+const BOUNDS: [ErrorBound; 4] = [
+    ErrorBound::Absolute(0.5),
+    ErrorBound::Absolute(1e-3),
+    ErrorBound::Relative(1e-2),
+    ErrorBound::Relative(1e-5),
+];
+
+fn absolute(col: &[Field], bound: ErrorBound) -> f64 {
+    let values: Vec<_> = col
+        .iter()
+        .map(|field| match field {
+            Field::Float(v) => *v as f64,
+            Field::Double(v) => *v,
+            _ => unreachable!(),
+        })
+        .collect();
+    bound.absolute(&values)
+}
+
+#[test]
+fn lossy_column_within_bound() {
+    for col in columns() {
+        for bound in BOUNDS {
+            let lossy = lossy_column(&col, bound);
+            assert_eq!(lossy.len(), col.len());
+            assert_eq!(lossy[0].data_type(), col[0].data_type());
+            assert!(
+                max_error(&col, &lossy) <= absolute(&col, bound),
+                "{}",
+                bound
+            );
+        }
+    }
+}
+
+#[test]
+fn sz_within_bound() {
+    for col in columns() {
+        for bound in BOUNDS {
+            let decoded = sz_column_decode(&sz_column_encode(&col, bound));
+            assert_eq!(decoded.len(), col.len());
+            assert!(
+                max_error(&col, &decoded) <= absolute(&col, bound),
+                "{}",
+                bound
+            );
+            let buf = sz_column_serialize(&col, bound, Compression::ZSTD);
+            assert_eq!(sz_column_deserialize(&buf, Compression::ZSTD), decoded);
+        }
+    }
+}
+
+#[test]
+fn zero_bound_is_lossless() {
+    for col in columns() {
+        assert_eq!(lossy_column(&col, ErrorBound::Absolute(0.0)), col);
+        let buf = sz_column_encode(&col, ErrorBound::Absolute(0.0));
+        assert_eq!(sz_column_decode(&buf), col);
+    }
+}
+
+#[test]
+fn special_values() {
+    let values = vec![
+        0.0,
+        -0.0,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        1e-310,
+        f64::MAX,
+    ];
+    let lossy = lossy_f64(&values, ErrorBound::Absolute(1e-3));
+    assert_eq!(lossy[2], f64::INFINITY);
+    assert_eq!(lossy[3], f64::NEG_INFINITY);
+    assert!(lossy[5].is_finite());
+    assert!(lossy_f64(&[f64::NAN], ErrorBound::Absolute(1.0))[0].is_nan());
+    let values = vec![1.5f32, f32::MAX, -3.25];
+    let lossy = lossy_f32(&values, ErrorBound::Absolute(0.1));
+    assert!(lossy.iter().all(|v| v.is_finite()));
+}
+
+#[test]
+fn smooth_data_shrinks() {
+    let col = &columns()[2];
+    let lossless = float_column_encode(col, FloatCodec::Gorilla).len();
+    let bound = ErrorBound::Absolute(1e-3);
+    let lossy = float_column_encode(&lossy_column(col, bound), FloatCodec::Gorilla).len();
+    assert!(lossy < lossless);
+    assert!(sz_column_encode(col, bound).len() < lossless);
+}
@@ -0,0 +1,244 @@
+mod common;
+
+use std::fs;
+
+use avro_rs::Codec;
+use common::{temp_dir, temp_path};
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use parquet::basic::Compression;
+use taosx_data_format_bench::{
+    avro::{avro_deserialize, avro_serialized_write},
+    generate_data,
+    manifest::{committed_write, Manifest, ManifestEntry, MANIFEST},
+    rolling_sink::{RollingPolicy, RollingSink, SinkFormat},
+};
+
+const DATA_TYPES: [&str; 3] = ["timestamp", "int", "double"];
+
+fn names(dir: &str) -> Vec<String> {
+    let mut names: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+fn ts(row: &[Field]) -> i64 {
+    match &row[0] {
+        Field::Timestamp(v) => v.as_raw_timestamp(),
+        _ => unreachable!(),
+    }
+}
+
+// Writes 100 rows a ms apart from `min_ts` to `name` in `dir` and returns its entry.
+fn write(dir: &str, name: &str, min_ts: i64) -> (ManifestEntry, Vec<Vec<Field>>) {
+    let (mut rows, _) = generate_data(&DATA_TYPES.to_vec(), 100);
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = Field::Timestamp(Timestamp::new(min_ts + i as i64, TimestampPrecision::Milli));
+    }
+    committed_write(&format!("{}/{}", dir, name), |temp| {
+        avro_serialized_write(temp, &DATA_TYPES.to_vec(), &rows, Codec::Deflate, None)
+    });
+    let entry = ManifestEntry {
+        path: name.to_string(),
+        schema_version: 1,
+        rows: rows.len() as u64,
+        codec: "deflate".to_string(),
+        min_ts: rows.iter().map(|row| ts(row)).min().unwrap(),
+        max_ts: rows.iter().map(|row| ts(row)).max().unwrap(),
+    };
+    (entry, rows)
+}
+
+#[test]
+fn committed_write_leaves_only_the_file() {
+    let temp = temp_dir();
+    let dir = temp_path(&temp, "dataset");
+    fs::create_dir_all(&dir).unwrap();
+    let (_, rows) = write(&dir, "part-0001.avro", 0);
+    assert_eq!(names(&dir), ["part-0001.avro"]);
+    let read = avro_deserialize(&fs::read(format!("{}/part-0001.avro", dir)).unwrap());
+    assert_eq!(read, rows);
+}
+
+#[test]
+fn add_and_remove() {
+    let temp = temp_dir();
+    let dir = temp_path(&temp, "dataset");
+    let mut manifest = Manifest::open(&dir);
+    assert_eq!(manifest.version(), 0);
+    assert!(manifest.files().is_empty());
+
+    let (a, _) = write(&dir, "a.avro", 0);
+    let (b, _) = write(&dir, "b.avro", 1000);
+    assert_eq!(manifest.add(vec![a.clone(), b.clone()]), Ok(1));
+    assert_eq!(manifest.rows(), 200);
+
+    let reopened = Manifest::open(&dir);
+    assert_eq!(reopened.version(), 1);
+    assert_eq!(reopened.files(), [a.clone(), b.clone()]);
+
+    // compaction: swap both files for one in a single version
+    let (c, _) = write(&dir, "c.avro", 0);
+    assert_eq!(
+        manifest.commit(vec![c.clone()], &["a.avro", "b.avro"]),
+        Ok(2)
+    );
+    assert_eq!(Manifest::open(&dir).files(), [c]);
+    assert_eq!(names(&dir), [MANIFEST, "c.avro"]);
+
+    assert_eq!(manifest.remove(&["c.avro"]), Ok(3));
+    assert!(Manifest::open(&dir).files().is_empty());
+    assert_eq!(names(&dir), [MANIFEST]);
+}
+
+#[test]
+fn files_in_range() {
+    let temp = temp_dir();
+    let dir = temp_path(&temp, "dataset");
+    let mut manifest = Manifest::open(&dir);
+    let entries: Vec<_> = (0..5)
+        .map(|i| write(&dir, &format!("{}.avro", i), i * 100).0)
+        .collect();
+    manifest.add(entries.clone()).unwrap();
+    let paths = |start, end| -> Vec<String> {
+        manifest
+            .files_in_range(start, end)
+            .into_iter()
+            .map(|entry| entry.path.clone())
+            .collect()
+    };
+    assert_eq!(paths(150, 250), ["1.avro", "2.avro"]);
+    assert_eq!(paths(199, 200), ["1.avro", "2.avro"]);
+    assert_eq!(paths(0, 0), ["0.avro"]);
+    assert!(paths(500, 600).is_empty());
+}
+
+#[test]
+fn stale_commit_fails() {
+    let temp = temp_dir();
+    let dir = temp_path(&temp, "dataset");
+    let mut first = Manifest::open(&dir);
+    let mut second = Manifest::open(&dir);
+    let (a, _) = write(&dir, "a.avro", 0);
+    let (b, _) = write(&dir, "b.avro", 100);
+    first.add(vec![a.clone()]).unwrap();
+    assert!(second.add(vec![b.clone()]).is_err());
+    assert_eq!(Manifest::open(&dir).files(), [a.clone()].as_slice());
+
+    let mut second = Manifest::open(&dir);
+    assert_eq!(second.add(vec![b.clone()]), Ok(2));
+    assert_eq!(Manifest::open(&dir).files(), [a, b]);
+}
+
+#[test]
+fn locked_commit_fails() {
+    let temp = temp_dir();
+    let dir = temp_path(&temp, "dataset");
+    let mut manifest = Manifest::open(&dir);
+    let (a, _) = write(&dir, "a.avro", 0);
+    fs::write(format!("{}/_manifest.lock", dir), b"").unwrap();
+    assert!(manifest.add(vec![a.clone()]).is_err());
+    assert_eq!(manifest.version(), 0);
+
+    fs::remove_file(format!("{}/_manifest.lock", dir)).unwrap();
+    assert_eq!(manifest.add(vec![a]), Ok(1));
+    assert_eq!(names(&dir), [MANIFEST, "a.avro"]);
+}
+
+#[test]
+fn stale_locks_are_broken() {
+    let temp = temp_dir();
+    let dir = temp_path(&temp, "dataset");
+    let mut manifest = Manifest::open(&dir);
+    let (a, _) = write(&dir, "a.avro", 0);
+    let mut child = std::process::Command::new("true").spawn().unwrap();
+    child.wait().unwrap();
+    let lock = format!("{}/_manifest.lock", dir);
+    fs::write(&lock, child.id().to_string()).unwrap();
+    let lock = fs::File::options().write(true).open(&lock).unwrap();
+    let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+    lock.set_modified(an_hour_ago).unwrap();
+    assert_eq!(manifest.add(vec![a]), Ok(1));
+    assert_eq!(names(&dir), [MANIFEST, "a.avro"]);
+}
+
+#[test]
+fn live_locks_are_kept() {
+    let temp = temp_dir();
+    let dir = temp_path(&temp, "dataset");
+    let mut manifest = Manifest::open(&dir);
+    let (a, _) = write(&dir, "a.avro", 0);
+    let lock = format!("{}/_manifest.lock", dir);
+    fs::write(&lock, std::process::id().to_string()).unwrap();
+    let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+    fs::File::options()
+        .write(true)
+        .open(&lock)
+        .unwrap()
+        .set_modified(an_hour_ago)
+        .unwrap();
+    assert!(manifest.add(vec![a]).is_err());
+    assert_eq!(
+        fs::read_to_string(&lock).unwrap(),
+        std::process::id().to_string()
+    );
+}
+
+#[test]
+fn rolling_sink_files_commit() {
+    let temp = temp_dir();
+    let dir = temp_path(&temp, "dataset");
+    fs::create_dir_all(&dir).unwrap();
+    let policy = RollingPolicy {
+        max_rows: Some(300),
+        ..Default::default()
+    };
+    let format = SinkFormat::Parquet {
+        compression: Compression::SNAPPY,
+        row_group_rows: 100,
+    };
+    let template = format!("{}/part-{{seq}}.parquet", dir);
+    let mut sink = RollingSink::new(format, &DATA_TYPES, policy, &template);
+    let (mut rows, _) = generate_data(&DATA_TYPES.to_vec(), 1000);
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = Field::Timestamp(Timestamp::new(i as i64 * 1000, TimestampPrecision::Micro));
+    }
+    let mut summaries = sink.append_batch(&rows);
+    summaries.extend(sink.close());
+
+    let mut manifest = Manifest::open(&dir);
+    let entries: Vec<_> = summaries
+        .iter()
+        .map(|summary| summary.manifest_entry(&dir, 1))
+        .collect();
+    assert_eq!(manifest.add(entries), Ok(1));
+    assert_eq!(manifest.rows(), 1000);
+    let files = Manifest::open(&dir).files().to_vec();
+    assert_eq!(files[1].path, "part-0002.parquet");
+    assert_eq!(files[1].codec, "snappy");
+    assert_eq!((files[1].min_ts, files[1].max_ts), (300, 599));
+}
+
+#[test]
+fn invalid_changes_fail() {
+    let temp = temp_dir();
+    let dir = temp_path(&temp, "dataset");
+    let mut manifest = Manifest::open(&dir);
+    let (a, _) = write(&dir, "a.avro", 0);
+    let missing = ManifestEntry {
+        path: "missing.avro".to_string(),
+        ..a.clone()
+    };
+    assert!(manifest.add(vec![a.clone(), missing]).is_err());
+    assert!(manifest.remove(&["a.avro"]).is_err());
+    assert_eq!(manifest.version(), 0);
+    assert!(manifest.files().is_empty());
+    assert_eq!(names(&dir), ["a.avro"]);
+
+    // a failed commit leaves no lock behind
+    fs::write(format!("{}/{}", dir, MANIFEST), b"{").unwrap();
+    assert!(manifest.add(vec![a]).is_err());
+    assert_eq!(names(&dir), [MANIFEST, "a.avro"]);
+}
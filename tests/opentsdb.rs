@@ -0,0 +1,79 @@
+mod common;
+
+use common::{every_type_text_rows, json_field, EVERY_TYPE};
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use taosx_data_format_bench::{
+    opentsdb::{
+        opentsdb_json_parse, opentsdb_json_serialize, opentsdb_telnet_parse,
+        opentsdb_telnet_serialize,
+    },
+    SuperTable,
+};
+
+type Points = Vec<(Vec<Field>, Vec<Field>)>;
+type Serialize = fn(&SuperTable, &[Field], &[Vec<Field>]) -> String;
+type Parse = fn(&SuperTable, &str) -> Points;
+
+const FORMATS: [(Serialize, Parse); 2] = [
+    (opentsdb_telnet_serialize, opentsdb_telnet_parse),
+    (opentsdb_json_serialize, opentsdb_json_parse),
+];
+
+#[test]
+fn round_trip() {
+    let data_types = EVERY_TYPE.to_vec();
+    let mut tag_types = EVERY_TYPE.to_vec();
+    tag_types.push("json");
+    for precision in [
+        TimestampPrecision::Milli,
+        TimestampPrecision::Micro,
+        TimestampPrecision::Nano,
+    ] {
+        let stable = SuperTable {
+            name: "meters",
+            data_types: &data_types,
+            tag_types: &tag_types,
+            precision,
+        };
+        let rows = every_type_text_rows(precision);
+        for tag_row in &rows {
+            let mut tags = tag_row.clone();
+            tags.push(json_field());
+            // the row of nulls has no point to write
+            let expected: Points = rows[1..]
+                .iter()
+                .map(|row| (tags.clone(), row.clone()))
+                .collect();
+            for (serialize, parse) in FORMATS {
+                assert_eq!(parse(&stable, &serialize(&stable, &tags, &rows)), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn special_doubles_and_sparse_rows() {
+    let data_types = vec!["timestamp", "double", "float"];
+    let tag_types = vec!["nchar"];
+    let stable = SuperTable {
+        name: "d",
+        data_types: &data_types,
+        tag_types: &tag_types,
+        precision: TimestampPrecision::Micro,
+    };
+    let ts = |raw| Field::Timestamp(Timestamp::new(raw, TimestampPrecision::Micro));
+    let rows = vec![
+        vec![ts(1), Field::Double(f64::INFINITY), Field::Null],
+        vec![ts(2), Field::Null, Field::Float(f32::NEG_INFINITY)],
+        vec![ts(3), Field::Double(1.5), Field::Float(-2.5)],
+    ];
+    for tags in [
+        vec![Field::Null],
+        vec![Field::NChar("a b=\"c\"".to_string())],
+    ] {
+        let expected: Points = rows.iter().map(|row| (tags.clone(), row.clone())).collect();
+        for (serialize, parse) in FORMATS {
+            assert_eq!(parse(&stable, &serialize(&stable, &tags, &rows)), expected);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use bstr::BString;
+use common::{temp_dir, temp_path, TempDir};
+use libtaos::Field;
+use parquet::basic::Compression;
+use taosx_data_format_bench::{
+    generate_data,
+    my_parquet::{parquet_read, parquet_serialized_write_row_groups},
+    parquet_index::{
+        parquet_read_eq, parquet_serialized_write_indexed, xxhash64, ParquetIndexOptions,
+    },
+    rows_to_cols,
+};
+
+const OPTIONS: ParquetIndexOptions = ParquetIndexOptions {
+    page_index: true,
+    bloom_filter_columns: &["binary"],
+    bloom_filter_fpp: 0.01,
+    page_rows: 100,
+};
+
+fn device(i: usize) -> Field {
+    Field::Binary(BString::from(format!("device-{:05}", i)))
+}
+
+// every device in two random row groups out of ten
+fn random_devices(i: usize, row: &mut [Field]) {
+    row[1] = Field::Int(i as i32);
+    row[2] = device(i * 7919 % 5000);
+}
+
+// 10000 rows in row groups of 1000, with `int` and the device id in `binary` set by `fill`.
+fn write(
+    dir: &TempDir,
+    fill: impl Fn(usize, &mut [Field]),
+    options: Option<&ParquetIndexOptions>,
+) -> (String, Vec<Vec<Field>>) {
+    let data_types = vec!["timestamp", "int", "binary", "double"];
+    let (mut rows, _) = generate_data(&data_types, 10000);
+    for (i, row) in rows.iter_mut().enumerate() {
+        fill(i, row);
+    }
+    let filename = temp_path(dir, "data.parquet");
+    let cols = rows_to_cols(&rows);
+    match options {
+        Some(options) => parquet_serialized_write_indexed(
+            &filename,
+            &data_types,
+            &cols,
+            Compression::SNAPPY,
+            1000,
+            options,
+        ),
+        None => parquet_serialized_write_row_groups(
+            &filename,
+            &data_types,
+            &cols,
+            Compression::SNAPPY,
+            1000,
+        ),
+    }
+    (filename, rows)
+}
+
+#[test]
+fn indexed_file_is_still_parquet() {
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, random_devices, Some(&OPTIONS));
+    assert_eq!(parquet_read(&filename), rows.len() as u32);
+}
+
+#[test]
+fn bloom_filters_skip_row_groups() {
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, random_devices, Some(&OPTIONS));
+    let value = device(1234);
+    let expected: Vec<_> = rows.iter().filter(|row| row[2] == value).cloned().collect();
+    let (read, stats) = parquet_read_eq(&filename, "binary", &value);
+    assert_eq!(read, expected);
+    assert_eq!(stats.row_groups, 10);
+    assert!(stats.row_groups_read < 5, "{:?}", stats);
+
+    let (read, stats) = parquet_read_eq(&filename, "binary", &device(99999));
+    assert!(read.is_empty());
+    assert!(stats.row_groups_read < 5, "{:?}", stats);
+}
+
+#[test]
+fn without_indexes_every_row_group_is_read() {
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, random_devices, None);
+    let value = device(1234);
+    let expected: Vec<_> = rows.iter().filter(|row| row[2] == value).cloned().collect();
+    let (read, stats) = parquet_read_eq(&filename, "binary", &value);
+    assert_eq!(read, expected);
+    assert_eq!(stats.row_groups_read, 10);
+}
+
+#[test]
+fn column_index_skips_pages() {
+    let options = ParquetIndexOptions {
+        bloom_filter_columns: &[],
+        ..OPTIONS
+    };
+    // pages of 100 rows 1000 apart, so a row group's min and max admit values no page has
+    let fill = |i: usize, row: &mut [Field]| {
+        row[1] = Field::Int((i + i / 100 * 1000) as i32);
+        row[2] = device(i);
+    };
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, fill, Some(&options));
+    let (read, stats) = parquet_read_eq(&filename, "int", &Field::Int(4321 + 43 * 1000));
+    assert_eq!(read, rows[4321..4322]);
+    assert_eq!(stats.row_groups_read, 1);
+    assert_eq!(stats.rows_scanned, 100);
+
+    let (read, stats) = parquet_read_eq(&filename, "int", &Field::Int(4350 + 43 * 1000 + 500));
+    assert!(read.is_empty());
+    assert_eq!(stats.row_groups_read, 0);
+}
+
+#[test]
+fn matches_across_pages() {
+    let options = ParquetIndexOptions {
+        bloom_filter_columns: &[],
+        ..OPTIONS
+    };
+    // every value in 150 rows, so a value spans two or three pages
+    let fill = |i: usize, row: &mut [Field]| {
+        row[1] = Field::Int((i / 150) as i32);
+        row[2] = device(i % 7);
+    };
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, fill, Some(&options));
+    let (read, stats) = parquet_read_eq(&filename, "int", &Field::Int(29));
+    assert_eq!(read, rows[4350..4500]);
+    assert_eq!(stats.rows_scanned, 200);
+
+    // 6900..7050 straddles two row groups
+    let (read, stats) = parquet_read_eq(&filename, "int", &Field::Int(46));
+    assert_eq!(read, rows[6900..7050]);
+    assert_eq!(stats.row_groups_read, 2);
+    assert_eq!(stats.rows_scanned, 200);
+    let (read, stats) = parquet_read_eq(&filename, "int", &Field::Int(66));
+    assert_eq!(read, rows[9900..10000]);
+    assert_eq!(stats.rows_scanned, 100);
+}
+
+#[test]
+fn nulls_are_never_equal() {
+    // every other page of `int` all nulls, and the odd null in `binary`
+    let fill = |i: usize, row: &mut [Field]| {
+        row[1] = match i / 100 % 2 {
+            0 => Field::Int((i % 10) as i32),
+            _ => Field::Null,
+        };
+        row[2] = match i % 3 {
+            0 => Field::Null,
+            _ => device(i % 50),
+        };
+    };
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, fill, Some(&OPTIONS));
+    let value = Field::Int(4);
+    let expected: Vec<_> = rows.iter().filter(|row| row[1] == value).cloned().collect();
+    let (read, _) = parquet_read_eq(&filename, "int", &value);
+    assert_eq!(read, expected);
+    let value = device(7);
+    let expected: Vec<_> = rows.iter().filter(|row| row[2] == value).cloned().collect();
+    let (read, _) = parquet_read_eq(&filename, "binary", &value);
+    assert_eq!(read, expected);
+}
+
+#[test]
+fn xxhash64_vectors() {
+    assert_eq!(xxhash64(b"", 0), 0xef46db3751d8e999);
+    assert_eq!(xxhash64(b"a", 0), 0xd24ec4f1a98c6e5b);
+    assert_eq!(xxhash64(b"abc", 0), 0x44bc2cf5ad770999);
+    assert_eq!(
+        xxhash64(b"Nobody inspects the spammish repetition", 0),
+        0xfbcea83c8a378bf1
+    );
+}
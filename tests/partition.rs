@@ -0,0 +1,227 @@
+mod common;
+
+use std::fs;
+
+use avro_rs::Codec;
+use bstr::BString;
+use common::{temp_dir, temp_path};
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use parquet::basic::Compression;
+use taosx_data_format_bench::{
+    generate_data,
+    partition::{
+        partition_discover, partitioned_read, partitioned_write, Granularity, PartitionFormat,
+        PruneStats,
+    },
+    SuperTable,
+};
+
+// 2026-10-18T00:00:00Z
+const START: i64 = 1_792_281_600_000;
+const HOUR: i64 = 3_600_000;
+
+// Two sub tables, "d1" and "d2", with a row every 10 minutes over 3 days.
+fn tables(data_types: &Vec<&str>) -> Vec<(Vec<Field>, Vec<Vec<Field>>)> {
+    ["d1", "d2"]
+        .iter()
+        .enumerate()
+        .map(|(i, device)| {
+            let (mut rows, _) = generate_data(data_types, 3 * 24 * 6);
+            for (j, row) in rows.iter_mut().enumerate() {
+                row[0] = Field::Timestamp(Timestamp::new(
+                    START + j as i64 * 600_000 + i as i64,
+                    TimestampPrecision::Milli,
+                ));
+            }
+            let tags = vec![Field::Int(i as i32), Field::Binary(BString::from(*device))];
+            (tags, rows)
+        })
+        .collect()
+}
+
+#[test]
+fn writes_hive_style_directories() {
+    let temp = temp_dir();
+    let root = temp_path(&temp, "root");
+    let data_types = vec!["timestamp", "int", "double"];
+    let tag_types = vec!["int", "binary"];
+    let stable = SuperTable {
+        name: "meters",
+        data_types: &data_types,
+        tag_types: &tag_types,
+        precision: TimestampPrecision::Milli,
+    };
+    let files = partitioned_write(
+        &root,
+        &stable,
+        Granularity::Day,
+        &["t_binary"],
+        PartitionFormat::Parquet(Compression::SNAPPY),
+        &tables(&data_types),
+    );
+    assert_eq!(files.len(), 6);
+    assert_eq!(
+        files[0],
+        format!(
+            "{}/stable=meters/date=2026-10-18/t_binary=d1/part-0001.parquet",
+            root
+        )
+    );
+
+    let partitions = partition_discover(&root, &stable);
+    assert_eq!(partitions.len(), 6);
+    assert_eq!(
+        partitions[1].values,
+        vec![
+            ("date".to_string(), "2026-10-18".to_string()),
+            ("t_binary".to_string(), "d2".to_string())
+        ]
+    );
+    assert_eq!(partitions[1].start, START);
+    assert_eq!(partitions[1].end, START + 24 * HOUR);
+
+    // another write adds a part
+    let files = partitioned_write(
+        &root,
+        &stable,
+        Granularity::Day,
+        &["t_binary"],
+        PartitionFormat::Parquet(Compression::SNAPPY),
+        &tables(&data_types)[..1],
+    );
+    assert!(files[0].ends_with("/t_binary=d1/part-0002.parquet"));
+    assert_eq!(partition_discover(&root, &stable).len(), 6);
+
+    // and numbers it after the last part, not the count of them
+    fs::remove_file(files[0].replace("part-0002", "part-0001")).unwrap();
+    let files = partitioned_write(
+        &root,
+        &stable,
+        Granularity::Day,
+        &["t_binary"],
+        PartitionFormat::Parquet(Compression::SNAPPY),
+        &tables(&data_types)[..1],
+    );
+    assert!(files[0].ends_with("/t_binary=d1/part-0003.parquet"));
+
+    // stray directories are no partitions
+    for stray in ["date=notes", "date=2026-10-18/hour=xx"] {
+        let dir = format!("{}/stable=meters/{}", root, stray);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(format!("{}/part-0001.parquet", dir), b"").unwrap();
+    }
+    assert_eq!(partition_discover(&root, &stable).len(), 6);
+}
+
+#[test]
+fn json_tags_partition_escaped() {
+    let temp = temp_dir();
+    let root = temp_path(&temp, "root");
+    let data_types = vec!["timestamp", "int"];
+    let tag_types = vec!["json"];
+    let stable = SuperTable {
+        name: "meters",
+        data_types: &data_types,
+        tag_types: &tag_types,
+        precision: TimestampPrecision::Milli,
+    };
+    let (mut rows, _) = generate_data(&data_types, 1);
+    rows[0][0] = Field::Timestamp(Timestamp::new(START, TimestampPrecision::Milli));
+    let tags = vec![Field::Json(serde_json::json!({"site": "a/b=c"}))];
+    let files = partitioned_write(
+        &root,
+        &stable,
+        Granularity::Day,
+        &["t_json"],
+        PartitionFormat::Avro(Codec::Null),
+        &[(tags, rows)],
+    );
+    assert_eq!(
+        files,
+        vec![format!(
+            "{}/stable=meters/date=2026-10-18/t_json={{\"site\":\"a%2Fb%3Dc\"}}/part-0001.avro",
+            root
+        )]
+    );
+}
+
+#[test]
+fn prunes_partitions_by_time() {
+    for format in [
+        PartitionFormat::Avro(Codec::Deflate),
+        PartitionFormat::Parquet(Compression::SNAPPY),
+    ] {
+        let temp = temp_dir();
+        let root = temp_path(&temp, "root");
+        let data_types = vec!["timestamp", "int", "double", "nchar"];
+        let tag_types = vec!["int", "binary"];
+        let stable = SuperTable {
+            name: "meters",
+            data_types: &data_types,
+            tag_types: &tag_types,
+            precision: TimestampPrecision::Milli,
+        };
+        let tables = tables(&data_types);
+        partitioned_write(&root, &stable, Granularity::Hour, &[], format, &tables);
+
+        // 13:30 to 15:29:59.999 on the second day
+        let start = START + 24 * HOUR + 13 * HOUR + HOUR / 2;
+        let end = start + 2 * HOUR - 1;
+        let (read, stats) = partitioned_read(&root, &stable, start, end);
+        assert_eq!(
+            stats,
+            PruneStats {
+                partitions: 72,
+                partitions_read: 3,
+                files_read: 3,
+            }
+        );
+        let mut expected: Vec<_> = tables
+            .iter()
+            .flat_map(|(_, rows)| rows.clone())
+            .filter(|row| match &row[0] {
+                Field::Timestamp(v) => (start..=end).contains(&v.as_raw_timestamp()),
+                _ => unreachable!(),
+            })
+            .collect();
+        let ts = |row: &Vec<Field>| match &row[0] {
+            Field::Timestamp(v) => v.as_raw_timestamp(),
+            _ => unreachable!(),
+        };
+        expected.sort_by_key(ts);
+        let mut read = read;
+        read.sort_by_key(ts);
+        assert_eq!(read, expected);
+        assert_eq!(read.len(), 24);
+    }
+}
+
+#[test]
+fn monthly_partitions_span_the_month() {
+    let temp = temp_dir();
+    let root = temp_path(&temp, "root");
+    let data_types = vec!["timestamp", "int"];
+    let tag_types = vec!["int", "binary"];
+    let stable = SuperTable {
+        name: "meters",
+        data_types: &data_types,
+        tag_types: &tag_types,
+        precision: TimestampPrecision::Milli,
+    };
+    partitioned_write(
+        &root,
+        &stable,
+        Granularity::Month,
+        &["t_int"],
+        PartitionFormat::Avro(Codec::Null),
+        &tables(&data_types),
+    );
+    let partitions = partition_discover(&root, &stable);
+    assert_eq!(partitions.len(), 2);
+    assert!(partitions[0].path.ends_with("/month=2026-10/t_int=0"));
+    // 2026-10-01 to 2026-11-01
+    assert_eq!(partitions[0].start, 1_790_812_800_000);
+    assert_eq!(partitions[0].end, 1_793_491_200_000);
+    let (read, _) = partitioned_read(&root, &stable, i64::MIN, i64::MAX);
+    assert_eq!(read.len(), 2 * 3 * 24 * 6);
+}
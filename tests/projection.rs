@@ -0,0 +1,100 @@
+mod common;
+
+use avro_rs::{
+    types::{Record, Value},
+    Codec, Schema, Writer,
+};
+use common::{temp_dir, temp_path};
+use libtaos::Field;
+use parquet::basic::Compression;
+use taosx_data_format_bench::{
+    avro::{avro_read_projected, avro_serialized_write},
+    generate_data,
+    my_parquet::{parquet_read_projected, parquet_serialized_write},
+};
+
+const DATA_TYPES: [&str; 14] = [
+    "timestamp",
+    "tinyint",
+    "utinyint",
+    "smallint",
+    "usmallint",
+    "int",
+    "uint",
+    "bigint",
+    "ubigint",
+    "float",
+    "double",
+    "bool",
+    "binary",
+    "nchar",
+];
+
+fn projected(rows: &[Vec<Field>], columns: &[&str]) -> Vec<Vec<Field>> {
+    let indexes: Vec<_> = columns
+        .iter()
+        .map(|c| DATA_TYPES.iter().position(|t| t == c).unwrap())
+        .collect();
+    rows.iter()
+        .map(|row| indexes.iter().map(|i| row[*i].clone()).collect())
+        .collect()
+}
+
+#[test]
+fn projections_read_only_their_columns() {
+    let data_types = DATA_TYPES.to_vec();
+    let (rows, cols) = generate_data(&data_types, 500);
+    let dir = temp_dir();
+    let parquet = temp_path(&dir, "projection.parquet");
+    let avro = temp_path(&dir, "projection.avro");
+    let avro_deflate = temp_path(&dir, "projection_deflate.avro");
+    parquet_serialized_write(&parquet, &data_types, &cols, Compression::SNAPPY);
+    avro_serialized_write(&avro, &data_types, &rows, Codec::Null, None);
+    avro_serialized_write(&avro_deflate, &data_types, &rows, Codec::Deflate, None);
+    for columns in [
+        vec!["timestamp"],
+        vec!["timestamp", "double"],
+        vec!["nchar", "timestamp", "binary", "bool"],
+        DATA_TYPES.to_vec(),
+    ] {
+        let expected = projected(&rows, &columns);
+        assert_eq!(parquet_read_projected(&parquet, &columns), expected);
+        assert_eq!(avro_read_projected(&avro, &columns), expected);
+        assert_eq!(avro_read_projected(&avro_deflate, &columns), expected);
+    }
+}
+
+#[test]
+fn avro_projections_step_over_any_type() {
+    let schema = Schema::parse_str(
+        r#"{"type": "record", "name": "m1", "fields": [
+            {"name": "int", "type": "int"},
+            {"name": "tags", "type": {"type": "array", "items": "string"}},
+            {"name": "props", "type": {"type": "map", "values": "long"}},
+            {"name": "state", "type": {"type": "enum", "name": "s", "symbols": ["on", "off"]}},
+            {"name": "id", "type": {"type": "fixed", "name": "id", "size": 4}},
+            {"name": "double", "type": "double"}
+        ]}"#,
+    )
+    .unwrap();
+    let dir = temp_dir();
+    let filename = temp_path(&dir, "any_type.avro");
+    let mut writer = Writer::new(&schema, std::fs::File::create(&filename).unwrap());
+    for i in 0..3 {
+        let mut record = Record::new(&schema).unwrap();
+        record.put("int", i);
+        record.put("tags", Value::Array(vec!["a".into()]));
+        record.put("props", Value::Map([("k".to_string(), 1i64.into())].into()));
+        record.put("state", Value::Enum(1, "off".to_string()));
+        record.put("id", Value::Fixed(4, vec![i as u8; 4]));
+        record.put("double", i as f64 / 2.0);
+        writer.append(record).unwrap();
+    }
+    writer.flush().unwrap();
+    assert_eq!(
+        avro_read_projected(&filename, &["double", "int"]),
+        (0..3)
+            .map(|i| vec![Field::Double(i as f64 / 2.0), Field::Int(i)])
+            .collect::<Vec<_>>()
+    );
+}
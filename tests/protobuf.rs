@@ -0,0 +1,62 @@
+mod common;
+
+use common::{every_type_rows, json_field};
+use libtaos::{Field, TimestampPrecision};
+use taosx_data_format_bench::{
+    protobuf::{
+        protobuf_column_deserialize, protobuf_column_serialize, protobuf_row_deserialize,
+        protobuf_row_serialize,
+    },
+    rows_to_cols,
+};
+
+const PRECISIONS: [TimestampPrecision; 3] = [
+    TimestampPrecision::Milli,
+    TimestampPrecision::Micro,
+    TimestampPrecision::Nano,
+];
+
+// every type, with a json column added as the rows format carries any value
+fn rows(precision: TimestampPrecision) -> Vec<Vec<Field>> {
+    let mut rows = every_type_rows(precision);
+    for (i, row) in rows.iter_mut().enumerate() {
+        row.push(if i == 0 { Field::Null } else { json_field() });
+    }
+    rows
+}
+
+#[test]
+fn rows_round_trip() {
+    for precision in PRECISIONS {
+        let rows = rows(precision);
+        assert_eq!(
+            protobuf_row_deserialize(&protobuf_row_serialize(&rows)),
+            rows
+        );
+    }
+}
+
+#[test]
+fn columns_round_trip() {
+    for precision in PRECISIONS {
+        // every column starts with a null, but for the timestamp
+        let cols = rows_to_cols(&rows(precision));
+        assert_eq!(
+            protobuf_column_deserialize(&protobuf_column_serialize(&cols)),
+            cols
+        );
+    }
+}
+
+#[test]
+fn columns_of_only_nulls_or_nothing() {
+    let cols = vec![
+        vec![Field::Null; 3],
+        vec![],
+        vec![Field::Int(1), Field::Null],
+    ];
+    assert_eq!(
+        protobuf_column_deserialize(&protobuf_column_serialize(&cols)),
+        cols
+    );
+}
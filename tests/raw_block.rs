@@ -0,0 +1,51 @@
+mod common;
+
+use bstr::BString;
+use common::every_type_rows;
+use libtaos::{Field, TimestampPrecision};
+use parquet::basic::Compression;
+use taosx_data_format_bench::{
+    raw_block::{raw_block_deserialize, raw_block_serialize},
+    rows_to_cols,
+};
+
+// Raw blocks carry no precision, timestamps come back in milliseconds.
+#[test]
+fn round_trip() {
+    let cols = rows_to_cols(&every_type_rows(TimestampPrecision::Milli));
+    for compression in [
+        Compression::UNCOMPRESSED,
+        Compression::SNAPPY,
+        Compression::LZ4,
+        Compression::ZSTD,
+    ] {
+        let buf = raw_block_serialize(&cols, compression);
+        assert_eq!(raw_block_deserialize(&buf, compression), cols);
+    }
+}
+
+#[test]
+fn nulls_and_long_values() {
+    let longest = Field::Binary(BString::from(vec![b'x'; u16::MAX as usize]));
+    let cols = vec![
+        vec![Field::Null, Field::Null],
+        vec![Field::Null, longest],
+        vec![Field::NChar("温度".to_string()), Field::Null],
+    ];
+    let buf = raw_block_serialize(&cols, Compression::UNCOMPRESSED);
+    assert_eq!(raw_block_deserialize(&buf, Compression::UNCOMPRESSED), cols);
+    assert_eq!(
+        raw_block_deserialize(
+            &raw_block_serialize(&[], Compression::UNCOMPRESSED),
+            Compression::UNCOMPRESSED
+        ),
+        Vec::<Vec<Field>>::new()
+    );
+}
+
+#[test]
+#[should_panic(expected = "at most 65535 bytes")]
+fn too_long_values() {
+    let cols = vec![vec![Field::Binary(BString::from(vec![b'x'; 1 << 16]))]];
+    raw_block_serialize(&cols, Compression::UNCOMPRESSED);
+}
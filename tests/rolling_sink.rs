@@ -0,0 +1,178 @@
+mod common;
+
+use std::fs;
+
+use avro_rs::Codec;
+use common::{temp_dir, temp_path, TempDir};
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use parquet::basic::Compression;
+use taosx_data_format_bench::{
+    avro::{avro_deserialize, generate_avro_schema},
+    generate_data,
+    my_parquet::parquet_read_projected,
+    rolling_sink::{FileSummary, RollingPolicy, RollingSink, SinkFormat},
+};
+
+const DATA_TYPES: [&str; 4] = ["timestamp", "int", "double", "nchar"];
+const START: i64 = 1_600_000_000_000;
+const PARQUET: SinkFormat = SinkFormat::Parquet {
+    compression: Compression::SNAPPY,
+    row_group_rows: 1000,
+};
+
+// A directory for the sink's files.
+fn dir(temp: &TempDir) -> String {
+    let dir = temp_path(temp, "files");
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// 10 batches of 1000 rows a second apart.
+fn batches() -> Vec<Vec<Vec<Field>>> {
+    let (mut rows, _) = generate_data(&DATA_TYPES.to_vec(), 10000);
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = Field::Timestamp(Timestamp::new(
+            START + i as i64 * 1000,
+            TimestampPrecision::Milli,
+        ));
+    }
+    rows.chunks(1000).map(|chunk| chunk.to_vec()).collect()
+}
+
+fn write_all(mut sink: RollingSink, batches: &[Vec<Vec<Field>>]) -> Vec<FileSummary> {
+    let mut summaries = vec![];
+    for batch in batches {
+        summaries.extend(sink.append_batch(batch));
+    }
+    summaries.extend(sink.close());
+    summaries
+}
+
+#[test]
+fn rolls_by_row_count() {
+    let temp = temp_dir();
+    let dir = dir(&temp);
+    let policy = RollingPolicy {
+        max_rows: Some(3000),
+        ..Default::default()
+    };
+    let template = format!("{}/part-{{seq}}.parquet", dir);
+    let sink = RollingSink::new(PARQUET, &DATA_TYPES, policy, &template);
+    let batches = batches();
+    let summaries = write_all(sink, &batches);
+    let rows: Vec<_> = batches.concat();
+
+    assert_eq!(
+        summaries.iter().map(|s| s.rows).collect::<Vec<_>>(),
+        vec![3000, 3000, 3000, 1000]
+    );
+    assert_eq!(summaries[1].path, format!("{}/part-0002.parquet", dir));
+    assert_eq!(summaries[1].min_ts, START + 3000 * 1000);
+    assert_eq!(summaries[1].max_ts, START + 5999 * 1000);
+    for (i, summary) in summaries.iter().enumerate() {
+        assert_eq!(summary.bytes, fs::metadata(&summary.path).unwrap().len());
+        assert_eq!(
+            parquet_read_projected(&summary.path, &DATA_TYPES),
+            rows[i * 3000..(rows.len()).min((i + 1) * 3000)]
+        );
+    }
+}
+
+#[test]
+fn rolls_by_time_bucket() {
+    let temp = temp_dir();
+    let dir = dir(&temp);
+    // 40 minute buckets, the rows spanning 2 hours 46 minutes
+    let policy = RollingPolicy {
+        time_bucket: Some(40 * 60 * 1000),
+        ..Default::default()
+    };
+    let template = format!("{}/{{bucket}}.parquet", dir);
+    let sink = RollingSink::new(PARQUET, &DATA_TYPES, policy, &template);
+    let summaries = write_all(sink, &batches());
+
+    assert_eq!(summaries.len(), 5);
+    assert_eq!(summaries.iter().map(|s| s.rows).sum::<u64>(), 10000);
+    for summary in &summaries {
+        let bucket = summary.min_ts.div_euclid(2_400_000) * 2_400_000;
+        assert_eq!(summary.max_ts.div_euclid(2_400_000) * 2_400_000, bucket);
+        assert_eq!(summary.path, format!("{}/{}.parquet", dir, bucket));
+    }
+}
+
+#[test]
+fn rolls_avro_by_size() {
+    let temp = temp_dir();
+    let dir = dir(&temp);
+    let schema = generate_avro_schema(&DATA_TYPES.to_vec());
+    let format = SinkFormat::Avro {
+        schema: &schema,
+        compression: Codec::Null,
+        block_bytes: 16000,
+    };
+    let policy = RollingPolicy {
+        max_bytes: Some(100_000),
+        ..Default::default()
+    };
+    let template = format!("{}/{{first_ts}}.avro", dir);
+    let sink = RollingSink::new(format, &DATA_TYPES, policy, &template);
+    let batches = batches();
+    let summaries = write_all(sink, &batches);
+
+    assert!(summaries.len() > 1);
+    let mut read = vec![];
+    for summary in &summaries {
+        assert!(summary.bytes < 100_000 + 200_000, "{:?}", summary);
+        assert_eq!(summary.path, format!("{}/{}.avro", dir, summary.min_ts));
+        read.extend(avro_deserialize(&fs::read(&summary.path).unwrap()));
+    }
+    assert_eq!(read, batches.concat());
+}
+
+#[test]
+fn time_buckets_are_in_ms_at_any_precision() {
+    let temp = temp_dir();
+    let dir = dir(&temp);
+    let policy = RollingPolicy {
+        time_bucket: Some(40 * 60 * 1000),
+        ..Default::default()
+    };
+    let template = format!("{}/{{bucket}}.parquet", dir);
+    let sink = RollingSink::new(PARQUET, &DATA_TYPES, policy, &template);
+    let mut batches = batches();
+    for row in batches.iter_mut().flatten() {
+        if let Field::Timestamp(v) = &row[0] {
+            row[0] = Field::Timestamp(Timestamp::new(
+                v.as_raw_timestamp() * 1000,
+                TimestampPrecision::Micro,
+            ));
+        }
+    }
+    let summaries = write_all(sink, &batches);
+
+    assert_eq!(summaries.len(), 5);
+    for summary in &summaries {
+        let bucket = summary.min_ts.div_euclid(2_400_000) * 2_400_000;
+        assert_eq!(summary.path, format!("{}/{}.parquet", dir, bucket));
+    }
+}
+
+#[test]
+#[should_panic(expected = "max_rows")]
+fn empty_files() {
+    let policy = RollingPolicy {
+        max_rows: Some(0),
+        ..Default::default()
+    };
+    RollingSink::new(PARQUET, &DATA_TYPES, policy, "{seq}.parquet");
+}
+
+#[test]
+#[should_panic(expected = "time_bucket")]
+fn empty_time_buckets() {
+    let policy = RollingPolicy {
+        time_bucket: Some(0),
+        ..Default::default()
+    };
+    RollingSink::new(PARQUET, &DATA_TYPES, policy, "{seq}.parquet");
+}
@@ -0,0 +1,92 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+use taosx_data_format_bench::{
+    avro::generate_avro_schema,
+    generate_data,
+    schema_registry::{
+        avro_confluent_deserialize, avro_confluent_serialize, MockSchemaRegistry,
+        SchemaRegistryClient, CONFLUENT_MAGIC,
+    },
+};
+
+const DATA_TYPES: [&str; 14] = [
+    "timestamp",
+    "tinyint",
+    "utinyint",
+    "smallint",
+    "usmallint",
+    "int",
+    "uint",
+    "bigint",
+    "ubigint",
+    "float",
+    "double",
+    "bool",
+    "binary",
+    "nchar",
+];
+
+#[test]
+fn confluent_round_trip() {
+    let registry = MockSchemaRegistry::start();
+    let data_types = DATA_TYPES.to_vec();
+    let (rows, _) = generate_data(&data_types, 100);
+    let mut producer = SchemaRegistryClient::new(registry.url());
+    let messages = avro_confluent_serialize(&mut producer, "meters-value", &data_types, &rows);
+    for message in &messages {
+        assert_eq!(message[0], CONFLUENT_MAGIC);
+        assert_eq!(message[1..5], 1u32.to_be_bytes());
+    }
+    // a consumer knows nothing but the registry
+    let mut consumer = SchemaRegistryClient::new(registry.url());
+    assert_eq!(avro_confluent_deserialize(&mut consumer, &messages), rows);
+}
+
+#[test]
+fn register_is_idempotent() {
+    let registry = MockSchemaRegistry::start();
+    let data_types = DATA_TYPES.to_vec();
+    let schema = generate_avro_schema(&data_types);
+    let other = generate_avro_schema(&data_types[..3].to_vec());
+    let mut first = SchemaRegistryClient::new(registry.url());
+    let mut second = SchemaRegistryClient::new(registry.url());
+    let id = first.register("meters-value", &schema);
+    assert_eq!(second.register("meters-value", &schema), id);
+    assert_eq!(second.register("other-value", &schema), id);
+    assert_ne!(second.register("meters-value", &other), id);
+    assert_eq!(
+        serde_json::to_string(first.schema(id)).unwrap(),
+        serde_json::to_string(&schema).unwrap()
+    );
+}
+
+#[test]
+#[should_panic(expected = "fetching schema 7 failed")]
+fn unknown_schema_id() {
+    let registry = MockSchemaRegistry::start();
+    SchemaRegistryClient::new(registry.url()).schema(7);
+}
+
+#[test]
+fn bad_requests() {
+    let registry = MockSchemaRegistry::start();
+    let host = registry.url().trim_start_matches("http://");
+    for request in [
+        "POST /subjects/m/versions HTTP/1.1\r\nContent-Length: lots\r\n\r\n{}",
+        "\r\n\r\n",
+    ] {
+        let mut stream = TcpStream::connect(host).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 400 "), "{}", response);
+    }
+    // a client that leaves without a word does not take the registry down
+    drop(TcpStream::connect(host).unwrap());
+    let mut client = SchemaRegistryClient::new(registry.url());
+    let schema = generate_avro_schema(&DATA_TYPES.to_vec());
+    assert_eq!(client.register("m", &schema), 1);
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use bstr::BString;
+use common::{every_type_text_rows, json_field, EVERY_TYPE};
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use taosx_data_format_bench::{
+    sql::{sql_parse, sql_serialize},
+    SuperTable,
+};
+
+#[test]
+fn round_trip() {
+    let data_types = EVERY_TYPE.to_vec();
+    let mut tag_types = EVERY_TYPE.to_vec();
+    tag_types.push("json");
+    for precision in [
+        TimestampPrecision::Milli,
+        TimestampPrecision::Micro,
+        TimestampPrecision::Nano,
+    ] {
+        let stable = SuperTable {
+            name: "meters tags",
+            data_types: &data_types,
+            tag_types: &tag_types,
+            precision,
+        };
+        let rows = every_type_text_rows(precision);
+        for tag_row in &rows {
+            let mut tags = tag_row.clone();
+            tags.push(json_field());
+            let expected: Vec<_> = rows.iter().map(|row| (tags.clone(), row.clone())).collect();
+            for batch_size in [1, 2, 1000] {
+                let sql = sql_serialize(&stable, "d0, values", &tags, &rows, batch_size);
+                assert_eq!(sql.lines().count(), rows.len().div_ceil(batch_size));
+                assert_eq!(sql_parse(&stable, &sql), expected);
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "batch_size")]
+fn empty_batches() {
+    let types = vec!["timestamp"];
+    let stable = SuperTable {
+        name: "meters",
+        data_types: &types,
+        tag_types: &types,
+        precision: TimestampPrecision::Milli,
+    };
+    sql_serialize(&stable, "d0", &[Field::Null], &[], 0);
+}
+
+#[test]
+fn quoted_strings_and_names() {
+    let data_types = vec!["timestamp", "binary", "nchar"];
+    let tag_types = vec!["binary"];
+    let stable = SuperTable {
+        name: "meters",
+        data_types: &data_types,
+        tag_types: &tag_types,
+        precision: TimestampPrecision::Milli,
+    };
+    let tags = vec![Field::Binary(BString::from("it's"))];
+    let rows = vec![vec![
+        Field::Timestamp(Timestamp::new(1, TimestampPrecision::Milli)),
+        Field::Binary(BString::from("a\\b'c")),
+        Field::NChar("温度".to_string()),
+    ]];
+    let sql = sql_serialize(&stable, "d0", &tags, &rows, 1);
+    assert_eq!(
+        sql,
+        "INSERT INTO `d0` USING `meters` TAGS ('it\\'s') VALUES (1, 'a\\\\b\\'c', '温度');\n"
+    );
+    assert_eq!(sql_parse(&stable, &sql), vec![(tags, rows[0].clone())]);
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use std::fs;
+
+use avro_rs::Codec;
+use common::{temp_dir, temp_path};
+use libtaos::Field;
+use parquet::{
+    basic::Compression,
+    file::{reader::FileReader, serialized_reader::SerializedFileReader},
+};
+use taosx_data_format_bench::{
+    avro::{avro_deserialize, generate_avro_schema, AvroStreamWriter},
+    generate_data,
+    my_parquet::{parquet_read_projected, ParquetStreamWriter},
+    rows_to_cols,
+};
+
+const DATA_TYPES: [&str; 5] = ["timestamp", "int", "double", "binary", "nchar"];
+
+// Batches of uneven sizes, 10 to 1000 rows.
+fn batches() -> Vec<Vec<Vec<Field>>> {
+    (0..20)
+        .map(|i| generate_data(&DATA_TYPES.to_vec(), 10 + i * 173 % 1000).0)
+        .collect()
+}
+
+#[test]
+fn parquet_row_groups_fill_across_batches() {
+    let dir = temp_dir();
+    let filename = temp_path(&dir, "row_groups.parquet");
+    let batches = batches();
+    let mut writer =
+        ParquetStreamWriter::new(&filename, &DATA_TYPES.to_vec(), Compression::SNAPPY, 1000);
+    for batch in &batches {
+        writer.append_batch(&rows_to_cols(batch));
+    }
+    let rows: Vec<Vec<Field>> = batches.concat();
+    assert_eq!(writer.rows(), rows.len() as u64);
+    assert!(writer.bytes_written() > 0);
+    assert_eq!(writer.close(), rows.len() as u64);
+
+    let reader = SerializedFileReader::try_from(filename.as_str()).unwrap();
+    let row_groups: Vec<_> = reader
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|row_group| row_group.num_rows())
+        .collect();
+    assert_eq!(row_groups.len(), rows.len().div_ceil(1000));
+    assert!(row_groups[..row_groups.len() - 1]
+        .iter()
+        .all(|&rows| rows == 1000));
+    assert_eq!(parquet_read_projected(&filename, &DATA_TYPES), rows);
+}
+
+#[test]
+fn parquet_small_batches_stay_buffered() {
+    let dir = temp_dir();
+    let filename = temp_path(&dir, "buffered.parquet");
+    let mut writer =
+        ParquetStreamWriter::new(&filename, &DATA_TYPES.to_vec(), Compression::SNAPPY, 1000);
+    let (rows, cols) = generate_data(&DATA_TYPES.to_vec(), 100);
+    let header = writer.bytes_written();
+    writer.append_batch(&cols);
+    assert_eq!(writer.bytes_written(), header);
+    writer.close();
+    assert_eq!(parquet_read_projected(&filename, &DATA_TYPES), rows);
+}
+
+#[test]
+fn avro_blocks_flush_as_they_fill() {
+    let dir = temp_dir();
+    let filename = temp_path(&dir, "blocks.avro");
+    let schema = generate_avro_schema(&DATA_TYPES.to_vec());
+    let batches = batches();
+    let mut writer = AvroStreamWriter::new(&filename, &schema, &DATA_TYPES, Codec::Deflate, 16000);
+    let mut written = vec![];
+    for batch in &batches {
+        writer.append_batch(batch);
+        written.push(writer.bytes_written());
+    }
+    assert!(written.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(written.last().unwrap() > &written[0]);
+    let rows: Vec<Vec<Field>> = batches.concat();
+    assert_eq!(writer.close(), rows.len() as u64);
+    assert_eq!(avro_deserialize(&fs::read(&filename).unwrap()), rows);
+}
+
+#[test]
+fn empty_avro_files_have_a_header() {
+    let dir = temp_dir();
+    let filename = temp_path(&dir, "empty.avro");
+    let schema = generate_avro_schema(&DATA_TYPES.to_vec());
+    let writer = AvroStreamWriter::new(&filename, &schema, &DATA_TYPES, Codec::Deflate, 16000);
+    assert_eq!(writer.close(), 0);
+    assert!(avro_deserialize(&fs::read(&filename).unwrap()).is_empty());
+}
+
+// Nulls in runs longer than a page and as single values.
+fn with_nulls(rows: &mut [Vec<Field>]) {
+    for (i, row) in rows.iter_mut().enumerate() {
+        if i % 3000 < 1500 {
+            row[1] = Field::Null;
+        }
+        if i % 7 == 0 {
+            row[3] = Field::Null;
+        }
+        row[4] = Field::Null;
+    }
+}
+
+#[test]
+fn nulls_round_trip() {
+    let dir = temp_dir();
+    let mut rows = batches().concat();
+    with_nulls(&mut rows);
+
+    let filename = temp_path(&dir, "nulls.parquet");
+    let mut writer =
+        ParquetStreamWriter::new(&filename, &DATA_TYPES.to_vec(), Compression::SNAPPY, 1000);
+    for batch in rows.chunks(700) {
+        writer.append_batch(&rows_to_cols(batch));
+    }
+    writer.close();
+    assert_eq!(parquet_read_projected(&filename, &DATA_TYPES), rows);
+
+    let filename = temp_path(&dir, "nulls.avro");
+    let schema = generate_avro_schema(&DATA_TYPES.to_vec());
+    let mut writer = AvroStreamWriter::new(&filename, &schema, &DATA_TYPES, Codec::Deflate, 16000);
+    for batch in rows.chunks(700) {
+        writer.append_batch(batch);
+    }
+    writer.close();
+    assert_eq!(avro_deserialize(&fs::read(&filename).unwrap()), rows);
+}
+
+#[test]
+fn parquet_without_columns() {
+    let dir = temp_dir();
+    let filename = temp_path(&dir, "no_columns.parquet");
+    let mut writer = ParquetStreamWriter::new(&filename, &vec![], Compression::SNAPPY, 1000);
+    writer.append_batch(&[]);
+    assert_eq!(writer.close(), 0);
+}
+
+#[test]
+#[should_panic(expected = "row_group_rows")]
+fn empty_row_groups() {
+    let dir = temp_dir();
+    ParquetStreamWriter::new(
+        &temp_path(&dir, "empty_row_groups.parquet"),
+        &DATA_TYPES.to_vec(),
+        Compression::SNAPPY,
+        0,
+    );
+}
@@ -0,0 +1,90 @@
+use libtaos::Field;
+use taosx_data_format_bench::{
+    generate_data,
+    string_codec::{
+        dictionary_decode, dictionary_encode, fsst_decode, fsst_encode, string_column_decode,
+        string_column_encode, DictionaryColumn, FsstColumn, StringCodec, StringColumn,
+    },
+};
+
+fn samples() -> Vec<Vec<Vec<u8>>> {
+    let words = ["cpu", "memory", "disk", "network", "host-01", "host-02"];
+    vec![
+        vec![],
+        vec![b"".to_vec()],
+        vec![b"only one".to_vec()],
+        (0..1000)
+            .map(|i| format!("{}.{}", words[i % 6], words[i % 5]).into_bytes())
+            .collect(),
+        (0..1000)
+            .map(|i| format!("sensor-{:05}", i).into_bytes())
+            .collect(),
+        (0..300).map(|i| vec![(i % 256) as u8; i % 20]).collect(),
+        (0..300)
+            .map(|i| (0..i as u32).map(|b| b as u8).collect())
+            .collect(),
+    ]
+}
+
+fn as_slices(values: &[Vec<u8>]) -> Vec<&[u8]> {
+    values.iter().map(|v| v.as_slice()).collect()
+}
+
+#[test]
+fn dictionary_round_trip() {
+    for values in samples() {
+        let buf = dictionary_encode(&as_slices(&values));
+        assert_eq!(dictionary_decode(&buf), values);
+        let col = DictionaryColumn::new(&buf);
+        assert_eq!(col.len(), values.len());
+        for (index, v) in values.iter().enumerate().rev() {
+            assert_eq!(col.get(index), v.as_slice());
+        }
+    }
+}
+
+#[test]
+fn dictionary_wide_codes() {
+    let values: Vec<Vec<u8>> = (0..70000u32).map(|i| i.to_le_bytes().to_vec()).collect();
+    let buf = dictionary_encode(&as_slices(&values));
+    assert_eq!(dictionary_decode(&buf), values);
+}
+
+#[test]
+fn fsst_round_trip() {
+    for values in samples() {
+        let buf = fsst_encode(&as_slices(&values));
+        assert_eq!(fsst_decode(&buf), values);
+        let col = FsstColumn::new(&buf);
+        assert_eq!(col.len(), values.len());
+        for (index, v) in values.iter().enumerate().rev() {
+            assert_eq!(&col.get(index), v);
+        }
+    }
+}
+
+#[test]
+fn repetitive_strings_compress() {
+    let values = &samples()[3];
+    let raw: usize = values.iter().map(|v| v.len()).sum();
+    assert!(dictionary_encode(&as_slices(values)).len() < raw / 4);
+    // on top of the 4 byte offset per value kept for random access
+    assert!(fsst_encode(&as_slices(values)).len() < raw / 2 + values.len() * 4);
+}
+
+#[test]
+fn column_round_trip() {
+    let (_, mut cols) = generate_data(&vec!["binary", "nchar"], 1000);
+    cols[1][3] = Field::NChar("温度 sensor".to_string());
+    for col in &cols {
+        for codec in [StringCodec::Dictionary, StringCodec::Fsst] {
+            let buf = string_column_encode(col, codec);
+            assert_eq!(&string_column_decode(&buf, codec), col);
+            let column = StringColumn::new(&buf, codec);
+            assert_eq!(column.len(), col.len());
+            for index in [0, 3, 500, 999] {
+                assert_eq!(column.get(index), col[index]);
+            }
+        }
+    }
+}
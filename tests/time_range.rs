@@ -0,0 +1,130 @@
+mod common;
+
+use common::{temp_dir, temp_path, TempDir};
+use libtaos::{Field, Timestamp, TimestampPrecision};
+use parquet::basic::Compression;
+use taosx_data_format_bench::{
+    generate_data,
+    my_parquet::{parquet_read_time_range, parquet_serialized_write_row_groups, ScanStats},
+    parquet_index::{parquet_serialized_write_indexed, ParquetIndexOptions},
+    rows_to_cols,
+};
+
+const START: i64 = 1_600_000_000_000;
+
+// 10000 rows a second apart in row groups of 1000, with pages of 100 and their indexes
+// when `page_index` is set.
+fn write(dir: &TempDir, page_index: bool) -> (String, Vec<Vec<Field>>) {
+    let data_types = vec!["timestamp", "int", "double", "nchar"];
+    let (mut rows, _) = generate_data(&data_types, 10000);
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = Field::Timestamp(Timestamp::new(
+            START + i as i64 * 1000,
+            TimestampPrecision::Milli,
+        ));
+    }
+    let filename = temp_path(dir, "data.parquet");
+    let cols = rows_to_cols(&rows);
+    if page_index {
+        let options = ParquetIndexOptions {
+            page_index: true,
+            bloom_filter_columns: &[],
+            bloom_filter_fpp: 0.01,
+            page_rows: 100,
+        };
+        parquet_serialized_write_indexed(
+            &filename,
+            &data_types,
+            &cols,
+            Compression::SNAPPY,
+            1000,
+            &options,
+        );
+    } else {
+        parquet_serialized_write_row_groups(
+            &filename,
+            &data_types,
+            &cols,
+            Compression::SNAPPY,
+            1000,
+        );
+    }
+    (filename, rows)
+}
+
+#[test]
+fn skips_row_groups_outside_range() {
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, false);
+    let (read, stats) = parquet_read_time_range(
+        &filename,
+        "timestamp",
+        START + 2500 * 1000,
+        START + 4499 * 1000,
+    );
+    assert_eq!(read, rows[2500..4500]);
+    assert_eq!(
+        stats,
+        ScanStats {
+            row_groups: 10,
+            row_groups_read: 3,
+            rows_scanned: 3000,
+        }
+    );
+}
+
+#[test]
+fn range_bounds_are_inclusive() {
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, false);
+    let (read, stats) = parquet_read_time_range(
+        &filename,
+        "timestamp",
+        START + 999 * 1000,
+        START + 1000 * 1000,
+    );
+    assert_eq!(read, rows[999..1001]);
+    assert_eq!(stats.row_groups_read, 2);
+}
+
+#[test]
+fn empty_and_full_ranges() {
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, false);
+    let (read, stats) = parquet_read_time_range(&filename, "timestamp", 0, START - 1);
+    assert!(read.is_empty());
+    assert_eq!(stats.row_groups_read, 0);
+    let (read, stats) = parquet_read_time_range(&filename, "timestamp", i64::MIN, i64::MAX);
+    assert_eq!(read, rows);
+    assert_eq!(stats.rows_scanned, rows.len());
+}
+
+#[test]
+fn page_index_skips_pages_outside_range() {
+    let dir = temp_dir();
+    let (filename, rows) = write(&dir, true);
+    let (read, stats) = parquet_read_time_range(
+        &filename,
+        "timestamp",
+        START + 2550 * 1000,
+        START + 4449 * 1000,
+    );
+    assert_eq!(read, rows[2550..4450]);
+    assert_eq!(
+        stats,
+        ScanStats {
+            row_groups: 10,
+            row_groups_read: 3,
+            rows_scanned: 2000,
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "row_group_rows")]
+fn empty_row_groups() {
+    let dir = temp_dir();
+    let filename = temp_path(&dir, "data.parquet");
+    let cols = vec![vec![Field::Int(1)]];
+    parquet_serialized_write_row_groups(&filename, &vec!["int"], &cols, Compression::SNAPPY, 0);
+}
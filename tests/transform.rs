@@ -0,0 +1,93 @@
+use parquet::basic::Compression;
+use rand::Rng;
+use taosx_data_format_bench::{
+    generate_data,
+    transform::{transform_column_deserialize, transform_column_serialize, Pipeline, Transform},
+};
+
+const TRANSFORMS: [Transform; 5] = [
+    Transform::Delta,
+    Transform::DeltaOfDelta,
+    Transform::Xor,
+    Transform::ByteShuffle,
+    Transform::BitShuffle,
+];
+
+fn pipelines(compression: Compression) -> Vec<Pipeline> {
+    let mut pipelines = vec![Pipeline::new(vec![], compression)];
+    for transform in TRANSFORMS {
+        pipelines.push(Pipeline::new(vec![transform], compression));
+    }
+    pipelines.push(Pipeline::new(
+        vec![Transform::Delta, Transform::ByteShuffle],
+        compression,
+    ));
+    pipelines.push(Pipeline::new(
+        vec![Transform::BitShuffle, Transform::Xor, Transform::BitShuffle],
+        compression,
+    ));
+    pipelines
+}
+
+#[test]
+fn transforms_invert() {
+    let mut rng = rand::thread_rng();
+    for width in [1, 2, 4, 8] {
+        for rows in [0, 1, 7, 8, 9, 100] {
+            let buf: Vec<u8> = (0..rows * width).map(|_| rng.gen()).collect();
+            for pipeline in pipelines(Compression::UNCOMPRESSED) {
+                let encoded = pipeline.encode(&buf, width);
+                assert_eq!(
+                    pipeline.decode(&encoded, width, rows),
+                    buf,
+                    "{}",
+                    pipeline.name()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn delta_of_sequence() {
+    let buf: Vec<u8> = (0..100i64)
+        .flat_map(|i| (1_600_000_000_000 + i * 1000).to_le_bytes())
+        .collect();
+    let delta = Transform::Delta.apply(&buf, 8);
+    assert_eq!(&delta[8..16], &1000i64.to_le_bytes());
+    let delta_of_delta = Transform::DeltaOfDelta.apply(&buf, 8);
+    assert!(delta_of_delta[16..].iter().all(|b| *b == 0));
+    assert_eq!(Transform::DeltaOfDelta.invert(&delta_of_delta, 8), buf);
+}
+
+#[test]
+fn columns_round_trip() {
+    let data_types = vec![
+        "timestamp",
+        "tinyint",
+        "utinyint",
+        "smallint",
+        "usmallint",
+        "int",
+        "uint",
+        "bigint",
+        "ubigint",
+        "float",
+        "double",
+        "bool",
+    ];
+    let (_, cols) = generate_data(&data_types, 1001);
+    for compression in [
+        Compression::UNCOMPRESSED,
+        Compression::SNAPPY,
+        Compression::LZ4,
+        Compression::ZSTD,
+    ] {
+        for pipeline in pipelines(compression) {
+            for col in &cols {
+                let buf = transform_column_serialize(col, &pipeline);
+                assert_eq!(&transform_column_deserialize(&buf, &pipeline), col);
+            }
+        }
+    }
+}
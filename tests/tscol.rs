@@ -0,0 +1,42 @@
+mod common;
+
+use common::every_type_rows;
+use libtaos::{Field, TimestampPrecision};
+use taosx_data_format_bench::{
+    rows_to_cols,
+    tscol::{tscol_blocks, tscol_deserialize, tscol_serialize},
+};
+
+// Timestamps are read back in milliseconds, as tscol keeps no precision.
+#[test]
+fn round_trip() {
+    let mut rows = every_type_rows(TimestampPrecision::Milli);
+    // and a block of only nulls when split by one row
+    rows.push(rows[0].clone());
+    let cols = rows_to_cols(&rows);
+    for block_rows in [1, 2, 3, 4096] {
+        assert_eq!(tscol_deserialize(&tscol_serialize(&cols, block_rows)), cols);
+    }
+}
+
+#[test]
+fn stats_skip_nulls() {
+    let cols = vec![
+        vec![Field::Null, Field::Int(3), Field::Null, Field::Int(-1)],
+        vec![Field::Null; 4],
+    ];
+    let buf = tscol_serialize(&cols, 2);
+    let blocks = tscol_blocks(&buf);
+    assert_eq!(blocks[0].stats[0], (Field::Int(3), Field::Int(3)));
+    assert_eq!(blocks[1].stats[0], (Field::Int(-1), Field::Int(-1)));
+    assert_eq!(blocks[0].stats[1], (Field::Null, Field::Null));
+    assert_eq!(tscol_deserialize(&buf), cols);
+    let empty = vec![vec![]; 2];
+    assert_eq!(tscol_deserialize(&tscol_serialize(&empty, 2)), empty);
+}
+
+#[test]
+#[should_panic(expected = "block_rows")]
+fn empty_blocks() {
+    tscol_serialize(&[vec![Field::Int(1)]], 0);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::every_type_rows;
+use libtaos::{Field, TimestampPrecision};
+use taosx_data_format_bench::{
+    my_flatbuffers::{flatbuffers_deserialize, flatbuffers_root, flatbuffers_serialize},
+    my_rkyv::{archived_column_get, rkyv_access, rkyv_deserialize, rkyv_serialize},
+    rows_to_cols,
+};
+
+const PRECISIONS: [TimestampPrecision; 3] = [
+    TimestampPrecision::Milli,
+    TimestampPrecision::Micro,
+    TimestampPrecision::Nano,
+];
+
+// the rows of every type, with every column but the timestamp starting with a null
+fn cols(precision: TimestampPrecision) -> Vec<Vec<Field>> {
+    rows_to_cols(&every_type_rows(precision))
+}
+
+#[test]
+fn flatbuffers_round_trip() {
+    for precision in PRECISIONS {
+        let cols = cols(precision);
+        let buf = flatbuffers_serialize(&cols);
+        assert_eq!(flatbuffers_deserialize(&buf), cols);
+        let batch = flatbuffers_root(&buf);
+        for (i, col) in cols.iter().enumerate() {
+            for (row, field) in col.iter().enumerate() {
+                assert_eq!(&batch.column(i).get(row), field);
+            }
+        }
+    }
+}
+
+#[test]
+fn rkyv_round_trip() {
+    for precision in PRECISIONS {
+        let cols = cols(precision);
+        let buf = rkyv_serialize(&cols);
+        assert_eq!(rkyv_deserialize(&buf), cols);
+        let batch = rkyv_access(&buf);
+        for (i, col) in cols.iter().enumerate() {
+            for (row, field) in col.iter().enumerate() {
+                assert_eq!(&archived_column_get(&batch.columns[i], row), field);
+            }
+        }
+    }
+}
+
+#[test]
+fn empty_and_null_columns() {
+    let nulls = vec![
+        vec![],
+        vec![Field::Null; 3],
+        vec![Field::Null, Field::Int(1)],
+    ];
+    // and a batch of 0 rows
+    let empty = vec![vec![]; cols(TimestampPrecision::Milli).len()];
+    for cols in [nulls, empty] {
+        assert_eq!(flatbuffers_deserialize(&flatbuffers_serialize(&cols)), cols);
+        assert_eq!(rkyv_deserialize(&rkyv_serialize(&cols)), cols);
+    }
+}
+
+#[test]
+fn json_columns() {
+    let cols = vec![vec![Field::Null, common::json_field(), Field::Null]];
+    assert_eq!(flatbuffers_deserialize(&flatbuffers_serialize(&cols)), cols);
+    assert_eq!(rkyv_deserialize(&rkyv_serialize(&cols)), cols);
+    assert_eq!(
+        flatbuffers_root(&flatbuffers_serialize(&cols))
+            .column(0)
+            .get(1),
+        cols[0][1]
+    );
+}
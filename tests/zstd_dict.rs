@@ -0,0 +1,71 @@
+use libtaos::TimestampPrecision;
+use taosx_data_format_bench::{
+    avro::{avro_datum_deserialize, avro_datum_serialize},
+    generate_data, generate_tags,
+    line_protocol::line_protocol_serialize,
+    zstd_dict::{zstd_messages_deserialize, zstd_messages_serialize, ZstdDictionary},
+    SuperTable,
+};
+
+fn data_types() -> Vec<&'static str> {
+    vec!["timestamp", "int", "double", "bool", "binary", "nchar"]
+}
+
+// Avro datums without a container header, as messages would carry them.
+fn messages(count: usize, rows: u32) -> Vec<Vec<u8>> {
+    (0..count)
+        .map(|_| avro_datum_serialize(&data_types(), &generate_data(&data_types(), rows).0))
+        .collect()
+}
+
+#[test]
+fn dictionary_round_trip() {
+    let dictionary = ZstdDictionary::train(&messages(200, 2), 8 * 1024, 3);
+    assert!(!dictionary.as_bytes().is_empty());
+    for message in messages(20, 2) {
+        assert_eq!(
+            dictionary.decompress(&dictionary.compress(&message)),
+            message
+        );
+    }
+    let (rows, _) = generate_data(&data_types(), 5);
+    let message = avro_datum_serialize(&data_types(), &rows);
+    let decompressed = dictionary.decompress(&dictionary.compress(&message));
+    assert_eq!(avro_datum_deserialize(&data_types(), &decompressed), rows);
+    let reloaded = ZstdDictionary::new(dictionary.as_bytes().to_vec(), 3);
+    let message = &messages(1, 5)[0];
+    assert_eq!(&reloaded.decompress(&dictionary.compress(message)), message);
+}
+
+// Random Avro datums have nothing for a dictionary to share, so this uses line protocol,
+// which repeats the measurement, tag and field names in every message.
+#[test]
+fn dictionary_shrinks_small_messages() {
+    let data_types = data_types();
+    let tag_types = vec!["int", "binary"];
+    let stable = SuperTable {
+        name: "meters",
+        data_types: &data_types,
+        tag_types: &tag_types,
+        precision: TimestampPrecision::Milli,
+    };
+    let tags = generate_tags(&tag_types);
+    let messages = |count| -> Vec<Vec<u8>> {
+        (0..count)
+            .map(|_| {
+                line_protocol_serialize(&stable, &tags, &generate_data(&data_types, 1).0)
+                    .into_bytes()
+            })
+            .collect()
+    };
+    let dictionary = ZstdDictionary::train(&messages(200), 8 * 1024, 3);
+    let messages = messages(50);
+    let plain = zstd_messages_serialize(&messages, None, 3);
+    let trained = zstd_messages_serialize(&messages, Some(&dictionary), 3);
+    assert!(trained.len() < plain.len() * 2 / 3);
+    assert_eq!(zstd_messages_deserialize(&plain, None), messages);
+    assert_eq!(
+        zstd_messages_deserialize(&trained, Some(&dictionary)),
+        messages
+    );
+}